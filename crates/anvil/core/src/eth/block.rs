@@ -66,7 +66,7 @@ impl Block {
                 timestamp: partial_header.timestamp,
                 extra_data: partial_header.extra_data,
                 mix_hash: partial_header.mix_hash,
-                withdrawals_root: None,
+                withdrawals_root: partial_header.withdrawals_root,
                 blob_gas_used: partial_header.blob_gas_used,
                 excess_blob_gas: partial_header.excess_blob_gas,
                 parent_beacon_block_root: partial_header.parent_beacon_block_root,
@@ -95,6 +95,7 @@ pub struct PartialHeader {
     pub timestamp: u64,
     pub extra_data: Bytes,
     pub mix_hash: B256,
+    pub withdrawals_root: Option<B256>,
     pub blob_gas_used: Option<u128>,
     pub excess_blob_gas: Option<u128>,
     pub parent_beacon_block_root: Option<B256>,
@@ -119,6 +120,7 @@ impl From<Header> for PartialHeader {
             mix_hash: value.mix_hash,
             nonce: value.nonce,
             base_fee: value.base_fee_per_gas,
+            withdrawals_root: value.withdrawals_root,
             blob_gas_used: value.blob_gas_used,
             excess_blob_gas: value.excess_blob_gas,
             parent_beacon_block_root: value.parent_beacon_block_root,
@@ -5,7 +5,7 @@ use alloy_rpc_types::{
     pubsub::{Params as SubscriptionParams, SubscriptionKind},
     request::TransactionRequest,
     state::StateOverride,
-    trace::geth::{GethDebugTracingOptions, GethDefaultTracingOptions},
+    trace::geth::{GethDebugTracingCallOptions, GethDebugTracingOptions},
     BlockId, BlockNumberOrTag as BlockNumber, Filter, Index,
 };
 use alloy_serde::WithOtherFields;
@@ -280,6 +280,10 @@ pub enum EthRequest {
     #[cfg_attr(feature = "serde", serde(rename = "debug_getRawTransaction", with = "sequence"))]
     DebugGetRawTransaction(TxHash),
 
+    /// geth's `debug_getRawBlock`  endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "debug_getRawBlock", with = "sequence"))]
+    DebugGetRawBlock(BlockId),
+
     /// geth's `debug_traceTransaction`  endpoint
     #[cfg_attr(feature = "serde", serde(rename = "debug_traceTransaction"))]
     DebugTraceTransaction(
@@ -292,7 +296,21 @@ pub enum EthRequest {
     DebugTraceCall(
         WithOtherFields<TransactionRequest>,
         #[cfg_attr(feature = "serde", serde(default))] Option<BlockId>,
-        #[cfg_attr(feature = "serde", serde(default))] GethDefaultTracingOptions,
+        #[cfg_attr(feature = "serde", serde(default))] Box<GethDebugTracingCallOptions>,
+    ),
+
+    /// geth's `debug_traceBlockByNumber`  endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "debug_traceBlockByNumber"))]
+    DebugTraceBlockByNumber(
+        BlockNumber,
+        #[cfg_attr(feature = "serde", serde(default))] GethDebugTracingOptions,
+    ),
+
+    /// geth's `debug_traceBlockByHash`  endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "debug_traceBlockByHash"))]
+    DebugTraceBlockByHash(
+        B256,
+        #[cfg_attr(feature = "serde", serde(default))] GethDebugTracingOptions,
     ),
 
     /// Trace transaction endpoint for parity's `trace_transaction`
@@ -340,6 +358,12 @@ pub enum EthRequest {
         )
     )]
     AutoImpersonateAccount(bool),
+    /// Returns all impersonated accounts and whether auto impersonation is enabled
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "anvil_impersonatedAccounts", with = "empty_params")
+    )]
+    ImpersonatedAccounts(()),
     /// Returns true if automatic mining is enabled, and false.
     #[cfg_attr(
         feature = "serde",
@@ -408,6 +432,10 @@ pub enum EthRequest {
     #[cfg_attr(feature = "serde", serde(rename = "anvil_setRpcUrl", with = "sequence"))]
     SetRpcUrl(String),
 
+    /// Removes the given number of most recent blocks and resets the state accordingly
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_rewind", with = "sequence"))]
+    Rewind(u64),
+
     /// Modifies the balance of an account.
     #[cfg_attr(
         feature = "serde",
@@ -878,6 +906,20 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_custom_impersonated_accounts() {
+        let s = r#"{"method": "anvil_impersonatedAccounts", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_custom_rewind() {
+        let s = r#"{"method": "anvil_rewind", "params": [3]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_custom_get_automine() {
         let s = r#"{"method": "anvil_getAutomine", "params": []}"#;
@@ -1454,6 +1496,17 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_debug_raw_block() {
+        let s = r#"{"jsonrpc":"2.0","method":"debug_getRawBlock","params":["latest"],"id":1}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"jsonrpc":"2.0","method":"debug_getRawBlock","params":["0x3ed3a89bc10115a321aee238c02de214009f8532a65368e5df5eaf732ee7167c"],"id":1}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_debug_trace_transaction() {
         let s = r#"{"method": "debug_traceTransaction", "params":
@@ -1473,6 +1526,23 @@ true}]}"#;
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_debug_trace_block() {
+        let s = r#"{"method": "debug_traceBlockByNumber", "params": ["0x1"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "debug_traceBlockByNumber", "params": ["latest", {"tracer": "callTracer"}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "debug_traceBlockByHash", "params":
+["0x4a3b0fce2cb9707b0baa68640cf2fe858c8bb4121b2a8cb904ff369d38a560ff", {"disableStorage":
+true}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_debug_trace_call() {
         let s = r#"{"method": "debug_traceCall", "params": [{"data":"0xcfae3217","from":"0xd84de507f3fada7df80908082d3239466db55a71","to":"0xcbe828fdc46e3b1c351ec90b1a5e7d9742c0398d"}]}"#;
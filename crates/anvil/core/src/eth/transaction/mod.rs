@@ -624,6 +624,23 @@ impl TypedTransaction {
         }
     }
 
+    /// Returns the gas price the transaction effectively pays in a block with the given base fee
+    ///
+    /// For dynamic fee transactions this is `min(max_fee_per_gas, base_fee +
+    /// max_priority_fee_per_gas)`.
+    pub fn effective_gas_price(&self, base_fee: Option<u128>) -> u128 {
+        let (max_fee_per_gas, max_priority_fee_per_gas) = match self {
+            Self::EIP1559(tx) => (tx.tx().max_fee_per_gas, tx.tx().max_priority_fee_per_gas),
+            Self::EIP4844(tx) => {
+                (tx.tx().tx().max_fee_per_gas, tx.tx().tx().max_priority_fee_per_gas)
+            }
+            _ => return self.gas_price(),
+        };
+        base_fee.map_or(max_fee_per_gas, |base_fee| {
+            max_fee_per_gas.min(base_fee.saturating_add(max_priority_fee_per_gas))
+        })
+    }
+
     pub fn gas_limit(&self) -> u128 {
         match self {
             Self::Legacy(tx) => tx.tx().gas_limit,
@@ -1109,7 +1126,7 @@ pub struct TransactionEssentials {
 }
 
 /// Represents all relevant information of an executed transaction
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TransactionInfo {
     pub transaction_hash: B256,
     pub transaction_index: u64,
@@ -1121,6 +1138,10 @@ pub struct TransactionInfo {
     pub out: Option<Bytes>,
     pub nonce: u64,
     pub gas_used: u128,
+    /// The gas refund the transaction accrued, before it's capped per EIP-3529
+    pub gas_refunded: u64,
+    /// Logs emitted before the transaction reverted, these are not part of the receipt
+    pub pre_revert_logs: Vec<Log>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -1413,7 +1434,7 @@ impl Decodable2718 for TypedReceipt {
     }
 }
 
-pub type ReceiptResponse = TransactionReceipt<TypedReceipt<alloy_rpc_types::Log>>;
+pub type ReceiptResponse = WithOtherFields<TransactionReceipt<TypedReceipt<alloy_rpc_types::Log>>>;
 
 pub fn convert_to_anvil_receipt(receipt: AnyTransactionReceipt) -> Option<ReceiptResponse> {
     let WithOtherFields {
@@ -1436,7 +1457,7 @@ pub fn convert_to_anvil_receipt(receipt: AnyTransactionReceipt) -> Option<Receip
         other,
     } = receipt;
 
-    Some(TransactionReceipt {
+    Some(WithOtherFields::new(TransactionReceipt {
         transaction_hash,
         transaction_index,
         block_hash,
@@ -1469,7 +1490,7 @@ pub fn convert_to_anvil_receipt(receipt: AnyTransactionReceipt) -> Option<Receip
             }),
             _ => return None,
         },
-    })
+    }))
 }
 
 #[cfg(test)]
@@ -1,4 +1,5 @@
-use alloy_primitives::{B256, U256};
+use alloy_primitives::{Address, B256, U256};
+use std::collections::BTreeMap;
 
 #[cfg(feature = "serde")]
 use serde::Serializer;
@@ -26,3 +27,35 @@ impl serde::Serialize for Work {
         }
     }
 }
+
+/// Represents the result of `anvil_impersonatedAccounts`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ImpersonatedAccounts {
+    /// All accounts that are explicitly impersonated
+    pub accounts: Vec<Address>,
+    /// Whether every account is impersonated automatically
+    pub auto_impersonate: bool,
+}
+
+/// A single storage slot of a [StorageRange]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageRangeEntry {
+    /// The unhashed storage key
+    pub key: B256,
+    /// The value of the slot
+    pub value: B256,
+}
+
+/// A page of an account's storage, like geth's `debug_storageRangeAt` returns it
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct StorageRange {
+    /// The slots of the page, keyed by the keccak hash of their key
+    pub storage: BTreeMap<B256, StorageRangeEntry>,
+    /// The hashed key of the first slot of the next page, if any
+    pub next_key: Option<B256>,
+}
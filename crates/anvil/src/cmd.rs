@@ -82,6 +82,13 @@ pub struct NodeArgs {
     #[arg(long, value_parser = Hardfork::from_str)]
     pub hardfork: Option<Hardfork>,
 
+    /// Activates a hardfork once the block timestamp reaches the given value, e.g.
+    /// `cancun@1710338135`.
+    ///
+    /// Can be passed multiple times to schedule several hardforks.
+    #[arg(long, value_name = "HARDFORK@TIMESTAMP", value_parser = parse_hardfork_activation)]
+    pub hardfork_activation: Vec<(u64, Hardfork)>,
+
     /// Block time in seconds for interval mining.
     #[arg(short, long, visible_alias = "blockTime", value_name = "SECONDS", value_parser = duration_from_secs_f64)]
     pub block_time: Option<Duration>,
@@ -113,6 +120,23 @@ pub struct NodeArgs {
     #[arg(long, default_value = "fees")]
     pub order: TransactionOrder,
 
+    /// Mine the transactions of a block sorted by their effective gas price against the block's
+    /// base fee, while keeping the nonce order of each sender.
+    #[arg(long)]
+    pub order_by_effective_gas_price: bool,
+
+    /// Don't compute the state root of mined blocks.
+    ///
+    /// This speeds up mining with large states, the `stateRoot` of mined blocks is zero instead.
+    #[arg(long)]
+    pub no_state_root: bool,
+
+    /// The maximum number of transactions to include in a block.
+    ///
+    /// Remaining transactions stay in the pool and are mined in later blocks.
+    #[arg(long, value_name = "NUM")]
+    pub max_transactions_per_block: Option<usize>,
+
     /// Initialize the genesis block with the given `genesis.json` file.
     #[arg(long, value_name = "PATH", value_parser= read_genesis_file)]
     pub init: Option<Genesis>,
@@ -166,6 +190,18 @@ pub struct NodeArgs {
     #[arg(long)]
     pub transaction_block_keeper: Option<usize>,
 
+    /// Also remove the states of blocks whose transactions are removed by
+    /// `--transaction-block-keeper`.
+    #[arg(long, requires = "transaction_block_keeper")]
+    pub prune_states_with_transactions: bool,
+
+    /// Keep the state of every block, moving older states to disk instead of discarding them.
+    ///
+    /// This allows querying the state at any past block, at the cost of disk usage growing with
+    /// every mined block.
+    #[arg(long, conflicts_with_all = ["prune_history", "prune_states_with_transactions"])]
+    pub archive: bool,
+
     #[command(flatten)]
     pub evm_opts: AnvilEvmArgs,
 
@@ -218,6 +254,7 @@ impl NodeArgs {
             .fork_request_timeout(self.evm_opts.fork_request_timeout.map(Duration::from_millis))
             .fork_request_retries(self.evm_opts.fork_request_retries)
             .fork_retry_backoff(self.evm_opts.fork_retry_backoff.map(Duration::from_millis))
+            .fork_genesis_accounts_concurrency(self.evm_opts.fork_genesis_concurrency)
             .fork_compute_units_per_second(compute_units_per_second)
             .with_eth_rpc_url(self.evm_opts.fork_url.map(|fork| fork.url))
             .with_base_fee(self.evm_opts.block_base_fee_per_gas)
@@ -228,6 +265,9 @@ impl NodeArgs {
             .set_config_out(self.config_out)
             .with_chain_id(self.evm_opts.chain_id)
             .with_transaction_order(self.order)
+            .with_order_by_effective_gas_price(self.order_by_effective_gas_price)
+            .with_compute_state_root(!self.no_state_root)
+            .with_max_transactions_per_block(self.max_transactions_per_block)
             .with_genesis(self.init)
             .with_steps_tracing(self.evm_opts.steps_tracing)
             .with_auto_impersonate(self.evm_opts.auto_impersonate)
@@ -236,10 +276,14 @@ impl NodeArgs {
             .set_pruned_history(self.prune_history)
             .with_init_state(self.load_state.or_else(|| self.state.and_then(|s| s.state)))
             .with_transaction_block_keeper(self.transaction_block_keeper)
+            .with_prune_states_with_transactions(self.prune_states_with_transactions)
+            .with_archive_mode(self.archive)
             .with_optimism(self.evm_opts.optimism)
             .with_disable_default_create2_deployer(self.evm_opts.disable_default_create2_deployer)
             .with_slots_in_an_epoch(self.slots_in_an_epoch)
             .with_memory_limit(self.evm_opts.memory_limit)
+            .with_call_timeout(self.evm_opts.call_timeout.map(Duration::from_millis))
+            .with_hardfork_activations(self.hardfork_activation)
     }
 
     fn account_generator(&self) -> AccountGenerator {
@@ -392,6 +436,12 @@ pub struct AnvilEvmArgs {
     #[arg(id = "retries", long = "retries", help_heading = "Fork config", requires = "fork_url")]
     pub fork_request_retries: Option<u32>,
 
+    /// Maximum number of genesis accounts that are fetched concurrently from the remote endpoint.
+    ///
+    /// Default value 10
+    #[arg(long, requires = "fork_url", value_name = "NUM", help_heading = "Fork config")]
+    pub fork_genesis_concurrency: Option<usize>,
+
     /// Fetch state from a specific block number over a remote endpoint.
     ///
     /// See --fork-url.
@@ -522,6 +572,12 @@ pub struct AnvilEvmArgs {
     /// The memory limit per EVM execution in bytes.
     #[arg(long)]
     pub memory_limit: Option<u64>,
+
+    /// Timeout in ms after which `eth_call`s and traced calls are aborted.
+    ///
+    /// Protects a shared node from calls that keep the EVM busy for a long time.
+    #[arg(long, value_name = "MS")]
+    pub call_timeout: Option<u64>,
 }
 
 /// Resolves an alias passed as fork-url to the matching url defined in the rpc_endpoints section
@@ -696,6 +752,14 @@ fn read_genesis_file(path: &str) -> Result<Genesis, String> {
     foundry_common::fs::read_json_file(path.as_ref()).map_err(|err| err.to_string())
 }
 
+/// Clap's value parser for hardfork activations in the form of `<hardfork>@<timestamp>`.
+fn parse_hardfork_activation(s: &str) -> Result<(u64, Hardfork), String> {
+    let (hardfork, timestamp) =
+        s.split_once('@').ok_or_else(|| format!("expected <hardfork>@<timestamp>, got {s}"))?;
+    let timestamp = timestamp.parse::<u64>().map_err(|e| e.to_string())?;
+    Ok((timestamp, hardfork.parse()?))
+}
+
 fn duration_from_secs_f64(s: &str) -> Result<Duration, String> {
     let s = s.parse::<f64>().map_err(|e| e.to_string())?;
     if s == 0.0 {
@@ -762,6 +826,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_parse_fork_genesis_concurrency() {
+        let args: NodeArgs = NodeArgs::parse_from([
+            "anvil",
+            "--fork-url",
+            "http://localhost:8545",
+            "--fork-genesis-concurrency",
+            "3",
+        ]);
+        assert_eq!(args.evm_opts.fork_genesis_concurrency, Some(3));
+        assert_eq!(args.into_node_config().fork_genesis_accounts_concurrency, 3);
+
+        let args = NodeArgs::try_parse_from(["anvil", "--fork-genesis-concurrency", "3"]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn can_parse_hardfork_activations() {
+        let args: NodeArgs = NodeArgs::parse_from([
+            "anvil",
+            "--hardfork",
+            "paris",
+            "--hardfork-activation",
+            "shanghai@100",
+            "--hardfork-activation",
+            "cancun@200",
+        ]);
+        assert_eq!(
+            args.hardfork_activation,
+            vec![(100, Hardfork::Shanghai), (200, Hardfork::Cancun)]
+        );
+        let config = args.into_node_config();
+        assert_eq!(config.hardfork, Some(Hardfork::Paris));
+        assert_eq!(
+            config.hardfork_activations.into_iter().collect::<Vec<_>>(),
+            vec![(100, Hardfork::Shanghai), (200, Hardfork::Cancun)]
+        );
+
+        assert!(NodeArgs::try_parse_from(["anvil", "--hardfork-activation", "cancun"]).is_err());
+        assert!(NodeArgs::try_parse_from(["anvil", "--hardfork-activation", "foo@1"]).is_err());
+    }
+
     #[test]
     fn can_parse_prune_config() {
         let args: NodeArgs = NodeArgs::parse_from(["anvil", "--prune-history"]);
@@ -771,6 +877,31 @@ mod tests {
         assert_eq!(args.prune_history, Some(Some(100)));
     }
 
+    #[test]
+    fn can_parse_prune_states_with_transactions() {
+        let args: NodeArgs = NodeArgs::parse_from([
+            "anvil",
+            "--transaction-block-keeper",
+            "64",
+            "--prune-states-with-transactions",
+        ]);
+        assert!(args.prune_states_with_transactions);
+        assert!(args.into_node_config().prune_states_with_transactions);
+
+        let args = NodeArgs::try_parse_from(["anvil", "--prune-states-with-transactions"]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn can_parse_archive() {
+        let args: NodeArgs = NodeArgs::parse_from(["anvil", "--archive"]);
+        assert!(args.archive);
+        assert!(args.into_node_config().archive_mode);
+
+        let args = NodeArgs::try_parse_from(["anvil", "--archive", "--prune-history"]);
+        assert!(args.is_err());
+    }
+
     #[test]
     fn can_parse_disable_block_gas_limit() {
         let args: NodeArgs = NodeArgs::parse_from(["anvil", "--disable-block-gas-limit"]);
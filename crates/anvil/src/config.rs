@@ -44,7 +44,7 @@ use rand::thread_rng;
 use revm::primitives::BlobExcessGasAndPrice;
 use serde_json::{json, to_writer, Value};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::Write as FmtWrite,
     fs::File,
     net::{IpAddr, Ipv4Addr},
@@ -101,6 +101,8 @@ pub struct NodeConfig {
     pub blob_excess_gas_and_price: Option<BlobExcessGasAndPrice>,
     /// The hardfork to use
     pub hardfork: Option<Hardfork>,
+    /// Hardforks that are activated once the timestamp of a mined block reaches their key
+    pub hardfork_activations: BTreeMap<u64, Hardfork>,
     /// Signer accounts that will be initialised with `genesis_balance` in the genesis block
     pub genesis_accounts: Vec<PrivateKeySigner>,
     /// Native token balance of every genesis account in the genesis block
@@ -149,6 +151,8 @@ pub struct NodeConfig {
     pub fork_request_retries: u32,
     /// The initial retry backoff
     pub fork_retry_backoff: Duration,
+    /// Maximum number of genesis accounts that are fetched concurrently in forking mode
+    pub fork_genesis_accounts_concurrency: usize,
     /// available CUPS
     pub compute_units_per_second: u64,
     /// The ipc path
@@ -167,14 +171,29 @@ pub struct NodeConfig {
     pub init_state: Option<SerializableState>,
     /// max number of blocks with transactions in memory
     pub transaction_block_keeper: Option<usize>,
+    /// Whether the states of blocks whose transactions are removed by the
+    /// `transaction_block_keeper` are removed as well
+    pub prune_states_with_transactions: bool,
+    /// Whether to keep the state of every block (archive mode)
+    ///
+    /// Takes precedence over `prune_history` and `prune_states_with_transactions`.
+    pub archive_mode: bool,
     /// Disable the default CREATE2 deployer
     pub disable_default_create2_deployer: bool,
     /// Enable Optimism deposit transaction
     pub enable_optimism: bool,
     /// Slots in an epoch
     pub slots_in_an_epoch: u64,
+    /// Whether to mine the transactions of a block sorted by their effective gas price
+    pub order_by_effective_gas_price: bool,
+    /// Whether to compute the state root of mined blocks
+    pub compute_state_root: bool,
+    /// The maximum number of transactions to include in a block
+    pub max_transactions_per_block: Option<usize>,
     /// The memory limit per EVM execution in bytes.
     pub memory_limit: Option<u64>,
+    /// The wall-clock time after which `eth_call`s and traced calls are aborted
+    pub call_timeout: Option<Duration>,
     /// Factory used by `anvil` to extend the EVM's precompiles.
     pub precompile_factory: Option<Arc<dyn PrecompileFactory>>,
 }
@@ -382,6 +401,7 @@ impl Default for NodeConfig {
             disable_block_gas_limit: false,
             gas_price: None,
             hardfork: None,
+            hardfork_activations: Default::default(),
             signer_accounts: genesis_accounts.clone(),
             genesis_timestamp: None,
             genesis_accounts,
@@ -411,6 +431,7 @@ impl Default for NodeConfig {
             fork_headers: vec![],
             fork_request_retries: 5,
             fork_retry_backoff: Duration::from_millis(1_000),
+            fork_genesis_accounts_concurrency: 10,
             fork_chain_id: None,
             // alchemy max cpus <https://docs.alchemy.com/reference/compute-units#what-are-cups-compute-units-per-second>
             compute_units_per_second: ALCHEMY_FREE_TIER_CUPS,
@@ -419,10 +440,16 @@ impl Default for NodeConfig {
             prune_history: Default::default(),
             init_state: None,
             transaction_block_keeper: None,
+            prune_states_with_transactions: false,
+            archive_mode: false,
             disable_default_create2_deployer: false,
             enable_optimism: false,
             slots_in_an_epoch: 32,
+            order_by_effective_gas_price: false,
+            compute_state_root: true,
+            max_transactions_per_block: None,
             memory_limit: None,
+            call_timeout: None,
             precompile_factory: None,
         }
     }
@@ -552,6 +579,26 @@ impl NodeConfig {
         self
     }
 
+    /// Sets whether the states of blocks outside the transaction block keeper window are removed
+    /// together with their transactions.
+    ///
+    /// This bounds the memory used by historic states to the same window of blocks.
+    #[must_use]
+    pub fn with_prune_states_with_transactions(mut self, prune_states: bool) -> Self {
+        self.prune_states_with_transactions = prune_states;
+        self
+    }
+
+    /// Sets whether the state of every block is kept (archive mode).
+    ///
+    /// This allows querying the state at any past block, at the cost of disk usage: the most recent
+    /// states are kept in memory, older states are written to disk and never discarded.
+    #[must_use]
+    pub fn with_archive_mode(mut self, archive_mode: bool) -> Self {
+        self.archive_mode = archive_mode;
+        self
+    }
+
     /// Sets the base fee
     #[must_use]
     pub fn with_base_fee(mut self, base_fee: Option<u128>) -> Self {
@@ -589,6 +636,20 @@ impl NodeConfig {
         self
     }
 
+    /// Schedules hardforks by the block timestamp they're activated at
+    ///
+    /// Each hardfork is used for all blocks with a timestamp of at least its activation timestamp,
+    /// until the next scheduled hardfork is activated. Blocks before the first activation use the
+    /// configured hardfork.
+    #[must_use]
+    pub fn with_hardfork_activations(
+        mut self,
+        activations: impl IntoIterator<Item = (u64, Hardfork)>,
+    ) -> Self {
+        self.hardfork_activations.extend(activations);
+        self
+    }
+
     /// Sets the genesis accounts
     #[must_use]
     pub fn with_genesis_accounts(mut self, accounts: Vec<PrivateKeySigner>) -> Self {
@@ -760,6 +821,15 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the maximum number of genesis accounts that are fetched concurrently in forking mode
+    #[must_use]
+    pub fn fork_genesis_accounts_concurrency(mut self, concurrency: Option<usize>) -> Self {
+        if let Some(concurrency) = concurrency {
+            self.fork_genesis_accounts_concurrency = concurrency.max(1);
+        }
+        self
+    }
+
     /// Sets the number of assumed available compute units per second
     ///
     /// See also, <https://docs.alchemy.com/reference/compute-units#what-are-cups-compute-units-per-second>
@@ -811,6 +881,36 @@ impl NodeConfig {
         self
     }
 
+    /// Sets whether the transactions of a block are mined sorted by their effective gas price
+    #[must_use]
+    pub fn with_order_by_effective_gas_price(mut self, order_by_effective_gas_price: bool) -> Self {
+        self.order_by_effective_gas_price = order_by_effective_gas_price;
+        self
+    }
+
+    /// Sets whether the state root of mined blocks is computed, enabled by default
+    ///
+    /// This requires the full state, so the state root remains zero in forking mode.
+    #[must_use]
+    pub fn with_compute_state_root(mut self, compute_state_root: bool) -> Self {
+        self.compute_state_root = compute_state_root;
+        self
+    }
+
+    /// Sets the maximum number of transactions to include in a block
+    #[must_use]
+    pub fn with_max_transactions_per_block(mut self, max_transactions: Option<usize>) -> Self {
+        self.max_transactions_per_block = max_transactions;
+        self
+    }
+
+    /// Sets the wall-clock time after which `eth_call`s and traced calls are aborted
+    #[must_use]
+    pub fn with_call_timeout(mut self, call_timeout: Option<Duration>) -> Self {
+        self.call_timeout = call_timeout;
+        self
+    }
+
     /// Returns the ipc path for the ipc endpoint if any
     pub fn get_ipc_path(&self) -> Option<String> {
         match &self.ipc_path {
@@ -945,7 +1045,8 @@ impl NodeConfig {
             fees,
             Arc::new(RwLock::new(fork)),
             self.enable_steps_tracing,
-            self.prune_history,
+            // archive mode keeps the full state history
+            if self.archive_mode { Default::default() } else { self.prune_history },
             self.transaction_block_keeper,
             self.block_time,
             Arc::new(tokio::sync::RwLock::new(self.clone())),
@@ -1,20 +1,17 @@
-use super::{
-    backend::mem::{state, BlockRequest, State},
-    sign::build_typed_transaction,
-};
+use super::{backend::mem::BlockRequest, sign::build_typed_transaction};
 use crate::{
     eth::{
         backend,
         backend::{
             db::SerializableState,
-            mem::{MIN_CREATE_GAS, MIN_TRANSACTION_GAS},
-            notifications::NewBlockNotifications,
+            mem::storage::MinedBlockOutcome,
+            notifications::{NewBlockNotifications, NewPendingTransactionNotifications},
             validate::TransactionValidator,
         },
         error::{
             BlockchainError, FeeHistoryError, InvalidTransactionError, Result, ToRpcResponseResult,
         },
-        fees::{FeeDetails, FeeHistoryCache, MIN_SUGGESTED_PRIORITY_FEE},
+        fees::{FeeDetails, FeeHistoryCache, PoolFeeLandscape, MIN_SUGGESTED_PRIORITY_FEE},
         macros::node_info,
         miner::FixedBlockTimeMiner,
         pool::{
@@ -35,7 +32,7 @@ use alloy_consensus::{transaction::eip4844::TxEip4844Variant, TxEnvelope};
 use alloy_dyn_abi::TypedData;
 use alloy_eips::eip2718::Encodable2718;
 use alloy_network::eip2718::Decodable2718;
-use alloy_primitives::{Address, Bytes, TxHash, TxKind, B256, B64, U256, U64};
+use alloy_primitives::{Address, Bytes, TxHash, B256, B64, U256, U64};
 use alloy_rpc_types::{
     anvil::{
         ForkedNetwork, Forking, Metadata, MineOptions, NodeEnvironment, NodeForkConfig, NodeInfo,
@@ -43,7 +40,10 @@ use alloy_rpc_types::{
     request::TransactionRequest,
     state::StateOverride,
     trace::{
-        geth::{DefaultFrame, GethDebugTracingOptions, GethDefaultTracingOptions, GethTrace},
+        geth::{
+            DefaultFrame, GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace,
+            TraceResult,
+        },
         parity::LocalizedTransactionTrace,
     },
     txpool::{TxpoolContent, TxpoolInspect, TxpoolInspectSummary, TxpoolStatus},
@@ -62,18 +62,13 @@ use anvil_core::{
         },
         EthRequest,
     },
-    types::Work,
+    types::{ImpersonatedAccounts, Work},
 };
 use anvil_rpc::{error::RpcError, response::ResponseResult};
 use foundry_common::provider::ProviderBuilder;
 use foundry_evm::{
-    backend::DatabaseError,
     decode::RevertDecoder,
-    revm::{
-        db::DatabaseRef,
-        interpreter::{return_ok, return_revert, InstructionResult},
-        primitives::BlockEnv,
-    },
+    revm::interpreter::{return_ok, return_revert, InstructionResult},
 };
 use futures::channel::{mpsc::Receiver, oneshot};
 use parking_lot::RwLock;
@@ -278,13 +273,20 @@ impl EthApi {
             EthRequest::DebugGetRawTransaction(hash) => {
                 self.raw_transaction(hash).await.to_rpc_result()
             }
+            EthRequest::DebugGetRawBlock(block) => self.raw_block(block).await.to_rpc_result(),
             // non eth-standard rpc calls
             EthRequest::DebugTraceTransaction(tx, opts) => {
                 self.debug_trace_transaction(tx, opts).await.to_rpc_result()
             }
             // non eth-standard rpc calls
             EthRequest::DebugTraceCall(tx, block, opts) => {
-                self.debug_trace_call(tx, block, opts).await.to_rpc_result()
+                self.debug_trace_call(tx, block, *opts).await.to_rpc_result()
+            }
+            EthRequest::DebugTraceBlockByNumber(number, opts) => {
+                self.debug_trace_block_by_number(number, opts).await.to_rpc_result()
+            }
+            EthRequest::DebugTraceBlockByHash(hash, opts) => {
+                self.debug_trace_block_by_hash(hash, opts).await.to_rpc_result()
             }
             EthRequest::TraceTransaction(tx) => self.trace_transaction(tx).await.to_rpc_result(),
             EthRequest::TraceBlock(block) => self.trace_block(block).await.to_rpc_result(),
@@ -297,6 +299,9 @@ impl EthApi {
             EthRequest::AutoImpersonateAccount(enable) => {
                 self.anvil_auto_impersonate_account(enable).await.to_rpc_result()
             }
+            EthRequest::ImpersonatedAccounts(()) => {
+                self.anvil_impersonated_accounts().to_rpc_result()
+            }
             EthRequest::GetAutoMine(()) => self.anvil_get_auto_mine().to_rpc_result(),
             EthRequest::Mine(blocks, interval) => {
                 self.anvil_mine(blocks, interval).await.to_rpc_result()
@@ -378,6 +383,7 @@ impl EthApi {
                 self.evm_mine_detailed(mine.and_then(|p| p.params)).await.to_rpc_result()
             }
             EthRequest::SetRpcUrl(url) => self.anvil_set_rpc_url(url).to_rpc_result(),
+            EthRequest::Rewind(depth) => self.anvil_rewind(depth).await.to_rpc_result(),
             EthRequest::EthSendUnsignedTransaction(tx) => {
                 self.eth_send_unsigned_transaction(*tx).await.to_rpc_result()
             }
@@ -477,15 +483,18 @@ impl EthApi {
     }
 
     async fn inner_raw_transaction(&self, hash: B256) -> Result<Option<Bytes>> {
-        match self.pool.get_transaction(hash) {
-            Some(tx) => Ok(Some(tx.transaction.encoded_2718().into())),
-            None => match self.backend.transaction_by_hash(hash).await? {
-                Some(tx) => TxEnvelope::try_from(tx.inner)
-                    .map_or(Err(BlockchainError::FailedToDecodeTransaction), |tx| {
-                        Ok(Some(tx.encoded_2718().into()))
-                    }),
-                None => Ok(None),
-            },
+        if let Some(tx) = self.pool.get_transaction(hash) {
+            return Ok(Some(tx.transaction.encoded_2718().into()))
+        }
+        if let Some(raw) = self.backend.raw_transaction(hash) {
+            return Ok(Some(raw))
+        }
+        match self.backend.transaction_by_hash(hash).await? {
+            Some(tx) => TxEnvelope::try_from(tx.inner)
+                .map_or(Err(BlockchainError::FailedToDecodeTransaction), |tx| {
+                    Ok(Some(tx.encoded_2718().into()))
+                }),
+            None => Ok(None),
         }
     }
 
@@ -773,9 +782,7 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_getUncleCountByBlockHash`
     pub async fn block_uncles_count_by_hash(&self, hash: B256) -> Result<U256> {
         node_info!("eth_getUncleCountByBlockHash");
-        let block =
-            self.backend.block_by_hash(hash).await?.ok_or(BlockchainError::BlockNotFound)?;
-        Ok(U256::from(block.uncles.len()))
+        Ok(U256::from(self.backend.uncle_count(hash).await?))
     }
 
     /// Returns the number of uncles in a block with given block number.
@@ -783,12 +790,7 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_getUncleCountByBlockNumber`
     pub async fn block_uncles_count_by_number(&self, block_number: BlockNumber) -> Result<U256> {
         node_info!("eth_getUncleCountByBlockNumber");
-        let block = self
-            .backend
-            .block_by_number(block_number)
-            .await?
-            .ok_or(BlockchainError::BlockNotFound)?;
-        Ok(U256::from(block.uncles.len()))
+        Ok(U256::from(self.backend.uncle_count(block_number).await?))
     }
 
     /// Returns the code at given address at given time (block number).
@@ -986,8 +988,9 @@ impl EthApi {
             priority,
         };
 
-        let tx = self.pool.add_transaction(pool_transaction)?;
+        let tx = self.pool.add_transaction(pool_transaction.clone())?;
         trace!(target: "node", "Added transaction: [{:?}] sender={:?}", tx.hash(), from);
+        self.backend.notify_on_new_pending_transaction(Arc::new(pool_transaction));
         Ok(*tx.hash())
     }
 
@@ -1050,16 +1053,65 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_createAccessList`
     pub async fn create_access_list(
         &self,
-        mut request: WithOtherFields<TransactionRequest>,
+        request: WithOtherFields<TransactionRequest>,
         block_number: Option<BlockId>,
     ) -> Result<AccessListWithGasUsed> {
         node_info!("eth_createAccessList");
+        let (access_list, gas_used, error) =
+            self.do_create_access_list(request, block_number, false).await?;
+        if let Some(err) = error {
+            return Err(err)
+        }
+        Ok(AccessListWithGasUsed { access_list, gas_used: U256::from(gas_used) })
+    }
+
+    /// Creates the access list of the request like `eth_createAccessList`, but doesn't fail if
+    /// the execution reverts.
+    ///
+    /// Instead, the access list collected until the revert is returned with the reason in
+    /// [AccessListResult::error]. The gas used is estimated with the access list applied, or that
+    /// of executing the request if it failed.
+    pub async fn create_access_list_with_error(
+        &self,
+        request: WithOtherFields<TransactionRequest>,
+        block_number: Option<BlockId>,
+    ) -> Result<AccessListResult> {
+        let (access_list, gas_used, error) =
+            self.do_create_access_list(request, block_number, true).await?;
+        Ok(AccessListResult { access_list, gas_used, error: error.map(|err| err.to_string()) })
+    }
+
+    /// Creates the access list of the request and the gas it uses with the access list applied.
+    ///
+    /// If `estimate_gas` is set, the gas is re-estimated with the access list applied instead of
+    /// returning the gas used by executing it. If the execution fails, the access list collected
+    /// until then is returned with the error.
+    async fn do_create_access_list(
+        &self,
+        mut request: WithOtherFields<TransactionRequest>,
+        block_number: Option<BlockId>,
+        estimate_gas: bool,
+    ) -> Result<(AccessList, u128, Option<BlockchainError>)> {
         let block_request = self.block_request(block_number).await?;
         // check if the number predates the fork, if in fork mode
         if let BlockRequest::Number(number) = block_request {
             if let Some(fork) = self.get_fork() {
                 if fork.predates_fork(number) {
-                    return Ok(fork.create_access_list(&request, Some(number.into())).await?)
+                    return match fork.create_access_list(&request, Some(number.into())).await {
+                        Ok(AccessListWithGasUsed { access_list, .. }) if estimate_gas => {
+                            request.access_list = Some(access_list.clone());
+                            let gas = fork.estimate_gas(&request, Some(number.into())).await?;
+                            Ok((access_list, gas, None))
+                        }
+                        Ok(AccessListWithGasUsed { access_list, gas_used }) => {
+                            Ok((access_list, gas_used.saturating_to(), None))
+                        }
+                        // the forked node rejected the request, e.g. because it reverted
+                        Err(err) if err.is_error_resp() => {
+                            Ok((AccessList::default(), 0, Some(err.into())))
+                        }
+                        Err(err) => Err(err.into()),
+                    }
                 }
             }
         }
@@ -1072,7 +1124,7 @@ impl EthApi {
                     FeeDetails::zero(),
                     block_env.clone(),
                 )?;
-                ensure_return_ok(exit, &out)?;
+                let error = ensure_return_ok(exit, &out).err();
 
                 // execute again but with access list set
                 request.access_list = Some(access_list.clone());
@@ -1081,14 +1133,20 @@ impl EthApi {
                     &state,
                     request.clone(),
                     FeeDetails::zero(),
-                    block_env,
+                    block_env.clone(),
                 )?;
-                ensure_return_ok(exit, &out)?;
+                let error = error.or_else(|| ensure_return_ok(exit, &out).err());
+                if error.is_some() || !estimate_gas {
+                    return Ok((AccessList(access_list.0), gas_used, error))
+                }
 
-                Ok(AccessListWithGasUsed {
-                    access_list: AccessList(access_list.0),
-                    gas_used: U256::from(gas_used),
-                })
+                let gas = self.backend.estimate_gas_with_state(
+                    &state,
+                    request,
+                    FeeDetails::zero(),
+                    block_env,
+                )?;
+                Ok((AccessList(access_list.0), gas, None))
             })
             .await?
     }
@@ -1201,15 +1259,7 @@ impl EthApi {
         idx: Index,
     ) -> Result<Option<Block>> {
         node_info!("eth_getUncleByBlockHashAndIndex");
-        let number =
-            self.backend.ensure_block_number(Some(BlockId::Hash(block_hash.into()))).await?;
-        if let Some(fork) = self.get_fork() {
-            if fork.predates_fork_inclusive(number) {
-                return Ok(fork.uncle_by_block_hash_and_index(block_hash, idx.into()).await?)
-            }
-        }
-        // It's impossible to have uncles outside of fork mode
-        Ok(None)
+        self.backend.uncle_by_index(block_hash, idx.into()).await
     }
 
     /// Returns an uncles at given block and index.
@@ -1221,14 +1271,7 @@ impl EthApi {
         idx: Index,
     ) -> Result<Option<Block>> {
         node_info!("eth_getUncleByBlockNumberAndIndex");
-        let number = self.backend.ensure_block_number(Some(BlockId::Number(block_number))).await?;
-        if let Some(fork) = self.get_fork() {
-            if fork.predates_fork_inclusive(number) {
-                return Ok(fork.uncle_by_block_number_and_index(number, idx.into()).await?)
-            }
-        }
-        // It's impossible to have uncles outside of fork mode
-        Ok(None)
+        self.backend.uncle_by_index(block_number, idx.into()).await
     }
 
     /// Returns logs matching given filter object.
@@ -1501,6 +1544,14 @@ impl EthApi {
         }
     }
 
+    /// Returns the RLP encoded block.
+    ///
+    /// Handler for RPC call: `debug_getRawBlock`
+    pub async fn raw_block(&self, block: BlockId) -> Result<Option<Bytes>> {
+        node_info!("debug_getRawBlock");
+        Ok(self.backend.raw_block(block))
+    }
+
     /// Returns traces for the transaction hash for geth's tracing endpoint
     ///
     /// Handler for RPC call: `debug_traceTransaction`
@@ -1520,7 +1571,7 @@ impl EthApi {
         &self,
         request: WithOtherFields<TransactionRequest>,
         block_number: Option<BlockId>,
-        opts: GethDefaultTracingOptions,
+        opts: GethDebugTracingCallOptions,
     ) -> Result<DefaultFrame> {
         node_info!("debug_traceCall");
         let block_request = self.block_request(block_number).await?;
@@ -1532,7 +1583,40 @@ impl EthApi {
         )?
         .or_zero_fees();
 
-        self.backend.call_with_tracing(request, fees, Some(block_request), opts).await
+        let GethDebugTracingCallOptions { tracing_options, state_overrides, .. } = opts;
+        self.backend
+            .call_with_tracing(
+                request,
+                fees,
+                Some(block_request),
+                tracing_options.config,
+                state_overrides,
+            )
+            .await
+    }
+
+    /// Returns traces for all transactions of the block for geth's tracing endpoint
+    ///
+    /// Handler for RPC call: `debug_traceBlockByNumber`
+    pub async fn debug_trace_block_by_number(
+        &self,
+        number: BlockNumber,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>> {
+        node_info!("debug_traceBlockByNumber");
+        self.backend.debug_trace_block(number.into(), opts).await
+    }
+
+    /// Returns traces for all transactions of the block for geth's tracing endpoint
+    ///
+    /// Handler for RPC call: `debug_traceBlockByHash`
+    pub async fn debug_trace_block_by_hash(
+        &self,
+        hash: B256,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>> {
+        node_info!("debug_traceBlockByHash");
+        self.backend.debug_trace_block(hash.into(), opts).await
     }
 
     /// Returns traces for the transaction hash via parity's tracing endpoint
@@ -1582,6 +1666,18 @@ impl EthApi {
         Ok(())
     }
 
+    /// Returns all accounts that are currently impersonated and whether auto impersonation is
+    /// enabled
+    ///
+    /// Handler for ETH RPC call: `anvil_impersonatedAccounts`
+    pub fn anvil_impersonated_accounts(&self) -> Result<ImpersonatedAccounts> {
+        node_info!("anvil_impersonatedAccounts");
+        Ok(ImpersonatedAccounts {
+            accounts: self.backend.impersonated_accounts(),
+            auto_impersonate: self.backend.is_auto_impersonating(),
+        })
+    }
+
     /// Returns true if auto mining is enabled, and false.
     ///
     /// Handler for ETH RPC call: `anvil_getAutomine`
@@ -1902,7 +1998,7 @@ impl EthApi {
     /// Handler for RPC call: `evm_setNextBlockTimestamp`
     pub fn evm_set_next_block_timestamp(&self, seconds: u64) -> Result<()> {
         node_info!("evm_setNextBlockTimestamp");
-        self.backend.time().set_next_block_timestamp(seconds)
+        self.backend.set_next_block_timestamp(seconds)
     }
 
     /// Sets the specific timestamp and returns the number of seconds between the given timestamp
@@ -1990,6 +2086,7 @@ impl EthApi {
                         if let Some(output) = receipt.out {
                             // insert revert reason if failure
                             if !receipt
+                                .inner
                                 .inner
                                 .inner
                                 .as_receipt_with_bloom()
@@ -2055,6 +2152,14 @@ impl EthApi {
         Ok(())
     }
 
+    /// Removes the given number of most recent blocks and resets the state to the new latest block
+    ///
+    /// Handler for ETH RPC call: `anvil_rewind`
+    pub async fn anvil_rewind(&self, depth: u64) -> Result<()> {
+        node_info!("anvil_rewind");
+        self.backend.rewind(depth).await
+    }
+
     /// Turn on call traces for transactions that are returned to the user when they execute a
     /// transaction (instead of just txhash/receipt)
     ///
@@ -2181,6 +2286,16 @@ impl EthApi {
 
         Ok(content)
     }
+
+    /// Returns the priority fees that the ready transactions in the pool are bidding, bucketed
+    /// into tiers, given the base fee of the next block
+    pub fn pool_fee_landscape(&self) -> PoolFeeLandscape {
+        let transactions = self.pool.ready_transactions().collect::<Vec<_>>();
+        PoolFeeLandscape::new(
+            self.backend.base_fee(),
+            transactions.iter().map(|tx| &tx.pending_transaction.transaction.transaction),
+        )
+    }
 }
 
 impl EthApi {
@@ -2252,150 +2367,25 @@ impl EthApi {
             }
         }
 
-        self.backend
-            .with_database_at(Some(block_request), |mut state, block| {
-                if let Some(overrides) = overrides {
-                    state = Box::new(state::apply_state_override(
-                        overrides.into_iter().collect(),
-                        state,
-                    )?);
-                }
-                self.do_estimate_gas_with_state(request, state, block)
-            })
-            .await?
-    }
-
-    /// Estimates the gas usage of the `request` with the state.
-    ///
-    /// This will execute the transaction request and find the best gas limit via binary search.
-    fn do_estimate_gas_with_state<D>(
-        &self,
-        mut request: WithOtherFields<TransactionRequest>,
-        state: D,
-        block_env: BlockEnv,
-    ) -> Result<u128>
-    where
-        D: DatabaseRef<Error = DatabaseError>,
-    {
-        // If the request is a simple native token transfer we can optimize
-        // We assume it's a transfer if we have no input data.
-        let to = request.to.as_ref().and_then(TxKind::to);
-
-        // check certain fields to see if the request could be a simple transfer
-        let maybe_transfer = request.input.input().is_none() &&
-            request.access_list.is_none() &&
-            request.blob_versioned_hashes.is_none();
-
-        if maybe_transfer {
-            if let Some(to) = to {
-                if let Ok(target_code) = self.backend.get_code_with_state(&state, *to) {
-                    if target_code.as_ref().is_empty() {
-                        return Ok(MIN_TRANSACTION_GAS);
-                    }
-                }
-            }
-        }
-
-        let fees = FeeDetails::new(
+        let fees = match FeeDetails::new(
             request.gas_price,
             request.max_fee_per_gas,
             request.max_priority_fee_per_gas,
             request.max_fee_per_blob_gas,
-        )?
-        .or_zero_fees();
-
-        // get the highest possible gas limit, either the request's set value or the currently
-        // configured gas limit
-        let mut highest_gas_limit = request.gas.unwrap_or(block_env.gas_limit.to());
-
-        let gas_price = fees.gas_price.unwrap_or_default();
-        // If we have non-zero gas price, cap gas limit by sender balance
-        if gas_price > 0 {
-            if let Some(from) = request.from {
-                let mut available_funds = self.backend.get_balance_with_state(&state, from)?;
-                if let Some(value) = request.value {
-                    if value > available_funds {
-                        return Err(InvalidTransactionError::InsufficientFunds.into());
-                    }
-                    // safe: value < available_funds
-                    available_funds -= value;
-                }
-                // amount of gas the sender can afford with the `gas_price`
-                let allowance =
-                    available_funds.checked_div(U256::from(gas_price)).unwrap_or_default();
-                highest_gas_limit = std::cmp::min(highest_gas_limit, allowance.saturating_to());
-            }
-        }
-
-        let mut call_to_estimate = request.clone();
-        call_to_estimate.gas = Some(highest_gas_limit);
-
-        // execute the call without writing to db
-        let ethres =
-            self.backend.call_with_state(&state, call_to_estimate, fees.clone(), block_env.clone());
-
-        let gas_used = match ethres.try_into()? {
-            GasEstimationCallResult::Success(gas) => Ok(gas),
-            GasEstimationCallResult::OutOfGas => {
-                Err(InvalidTransactionError::BasicOutOfGas(highest_gas_limit).into())
+        ) {
+            Ok(fees) => fees.or_zero_fees(),
+            Err(err) => {
+                // plain transfers are estimated without executing them, so their fees are not
+                // validated
+                return self
+                    .backend
+                    .transfer_gas(&request, Some(block_request), overrides)
+                    .await?
+                    .ok_or(err)
             }
-            GasEstimationCallResult::Revert(output) => {
-                Err(InvalidTransactionError::Revert(output).into())
-            }
-            GasEstimationCallResult::EvmError(err) => {
-                warn!(target: "node", "estimation failed due to {:?}", err);
-                Err(BlockchainError::EvmError(err))
-            }
-        }?;
-
-        // at this point we know the call succeeded but want to find the _best_ (lowest) gas the
-        // transaction succeeds with. we find this by doing a binary search over the
-        // possible range NOTE: this is the gas the transaction used, which is less than the
-        // transaction requires to succeed
-
-        // Get the starting lowest gas needed depending on the transaction kind.
-        let mut lowest_gas_limit = determine_base_gas_by_kind(&request);
-
-        // pick a point that's close to the estimated gas
-        let mut mid_gas_limit =
-            std::cmp::min(gas_used * 3, (highest_gas_limit + lowest_gas_limit) / 2);
-
-        // Binary search for the ideal gas limit
-        while (highest_gas_limit - lowest_gas_limit) > 1 {
-            request.gas = Some(mid_gas_limit);
-            let ethres = self.backend.call_with_state(
-                &state,
-                request.clone(),
-                fees.clone(),
-                block_env.clone(),
-            );
-
-            match ethres.try_into()? {
-                GasEstimationCallResult::Success(_) => {
-                    // If the transaction succeeded, we can set a ceiling for the highest gas limit
-                    // at the current midpoint, as spending any more gas would
-                    // make no sense (as the TX would still succeed).
-                    highest_gas_limit = mid_gas_limit;
-                }
-                GasEstimationCallResult::OutOfGas |
-                GasEstimationCallResult::Revert(_) |
-                GasEstimationCallResult::EvmError(_) => {
-                    // If the transaction failed, we can set a floor for the lowest gas limit at the
-                    // current midpoint, as spending any less gas would make no
-                    // sense (as the TX would still revert due to lack of gas).
-                    //
-                    // We don't care about the reason here, as we known that trasaction is correct
-                    // as it succeeded earlier
-                    lowest_gas_limit = mid_gas_limit;
-                }
-            };
-            // new midpoint
-            mid_gas_limit = (highest_gas_limit + lowest_gas_limit) / 2;
-        }
-
-        trace!(target : "node", "Estimated Gas for call {:?}", highest_gas_limit);
+        };
 
-        Ok(highest_gas_limit)
+        self.backend.estimate_gas(request, fees, Some(block_request), overrides).await
     }
 
     /// Updates the `TransactionOrder`
@@ -2444,6 +2434,11 @@ impl EthApi {
         self.pool.add_ready_listener()
     }
 
+    /// Returns a new stream that yields every transaction that was added to the pool
+    pub fn new_pending_transaction_notifications(&self) -> NewPendingTransactionNotifications {
+        self.backend.new_pending_transaction_notifications()
+    }
+
     /// Returns a new accessor for certain storage elements
     pub fn storage_info(&self) -> StorageInfo {
         StorageInfo::new(Arc::clone(&self.backend))
@@ -2454,13 +2449,14 @@ impl EthApi {
         self.backend.is_fork()
     }
 
-    /// Mines exactly one block
-    pub async fn mine_one(&self) {
+    /// Mines exactly one block with the ready transactions of the pool and returns its outcome
+    pub async fn mine_one(&self) -> MinedBlockOutcome {
         let transactions = self.pool.ready_transactions().collect::<Vec<_>>();
         let outcome = self.backend.mine_block(transactions).await;
 
         trace!(target: "node", blocknumber = ?outcome.block_number, "mined block");
-        self.pool.on_mined_block(outcome);
+        self.pool.on_mined_block(outcome.clone());
+        outcome
     }
 
     /// Returns the pending block with tx hashes
@@ -2625,8 +2621,9 @@ impl EthApi {
         let priority = self.transaction_priority(&pending_transaction.transaction);
         let pool_transaction =
             PoolTransaction { requires, provides, pending_transaction, priority };
-        let tx = self.pool.add_transaction(pool_transaction)?;
+        let tx = self.pool.add_transaction(pool_transaction.clone())?;
         trace!(target: "node", "Added transaction: [{:?}] sender={:?}", tx.hash(), from);
+        self.backend.notify_on_new_pending_transaction(Arc::new(pool_transaction));
         Ok(*tx.hash())
     }
 
@@ -2635,6 +2632,24 @@ impl EthApi {
         self.backend.get_db().read().await.maybe_state_root()
     }
 
+    /// Returns whether the transaction with the given hash is mined, pending in the pool or
+    /// unknown
+    pub fn transaction_status(&self, hash: B256) -> TxStatus {
+        // the pool is checked first, since mined transactions are removed from the pool only after
+        // they were added to the storage
+        if self.pool.contains(&hash) {
+            return TxStatus::Pending
+        }
+        match self.backend.mined_transaction(hash) {
+            Some(tx) => TxStatus::Mined {
+                block: tx.block_number,
+                index: tx.info.transaction_index,
+                success: tx.receipt.as_receipt_with_bloom().receipt.status.coerce_status(),
+            },
+            None => TxStatus::Unknown,
+        }
+    }
+
     /// additional validation against hardfork
     fn ensure_typed_transaction_supported(&self, tx: &TypedTransaction) -> Result<()> {
         match &tx {
@@ -2677,93 +2692,32 @@ fn ensure_return_ok(exit: InstructionResult, out: &Option<Output>) -> Result<Byt
     }
 }
 
-/// Determines the minimum gas needed for a transaction depending on the transaction kind.
-fn determine_base_gas_by_kind(request: &WithOtherFields<TransactionRequest>) -> u128 {
-    match transaction_request_to_typed(request.clone()) {
-        Some(request) => match request {
-            TypedTransactionRequest::Legacy(req) => match req.to {
-                TxKind::Call(_) => MIN_TRANSACTION_GAS,
-                TxKind::Create => MIN_CREATE_GAS,
-            },
-            TypedTransactionRequest::EIP1559(req) => match req.to {
-                TxKind::Call(_) => MIN_TRANSACTION_GAS,
-                TxKind::Create => MIN_CREATE_GAS,
-            },
-            TypedTransactionRequest::EIP2930(req) => match req.to {
-                TxKind::Call(_) => MIN_TRANSACTION_GAS,
-                TxKind::Create => MIN_CREATE_GAS,
-            },
-            TypedTransactionRequest::EIP4844(_) => MIN_TRANSACTION_GAS,
-            TypedTransactionRequest::Deposit(req) => match req.kind {
-                TxKind::Call(_) => MIN_TRANSACTION_GAS,
-                TxKind::Create => MIN_CREATE_GAS,
-            },
-        },
-        // Tighten the gas limit upwards if we don't know the transaction type to avoid deployments
-        // failing.
-        _ => MIN_CREATE_GAS,
-    }
-}
-
-/// Keeps result of a call to revm EVM used for gas estimation
-enum GasEstimationCallResult {
-    Success(u128),
-    OutOfGas,
-    Revert(Option<Bytes>),
-    EvmError(InstructionResult),
+/// The status of a transaction, see [EthApi::transaction_status]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TxStatus {
+    /// The transaction is included in a block
+    Mined {
+        /// The number of the block
+        block: u64,
+        /// The index of the transaction in the block
+        index: u64,
+        /// Whether the transaction succeeded
+        success: bool,
+    },
+    /// The transaction is in the pool, waiting to be mined
+    Pending,
+    /// The transaction is neither mined nor in the pool
+    Unknown,
 }
 
-/// Converts the result of a call to revm EVM into a [`GasEstimationCallResult`].
-impl TryFrom<Result<(InstructionResult, Option<Output>, u128, State)>> for GasEstimationCallResult {
-    type Error = BlockchainError;
-
-    fn try_from(res: Result<(InstructionResult, Option<Output>, u128, State)>) -> Result<Self> {
-        match res {
-            // Exceptional case: init used too much gas, treated as out of gas error
-            Err(BlockchainError::InvalidTransaction(InvalidTransactionError::GasTooHigh(_))) => {
-                Ok(Self::OutOfGas)
-            }
-            Err(err) => Err(err),
-            Ok((exit, output, gas, _)) => match exit {
-                return_ok!() | InstructionResult::CallOrCreate => Ok(Self::Success(gas)),
-
-                InstructionResult::Revert => Ok(Self::Revert(output.map(|o| o.into_data()))),
-
-                InstructionResult::OutOfGas |
-                InstructionResult::MemoryOOG |
-                InstructionResult::MemoryLimitOOG |
-                InstructionResult::PrecompileOOG |
-                InstructionResult::InvalidOperandOOG => Ok(Self::OutOfGas),
-
-                InstructionResult::OpcodeNotFound |
-                InstructionResult::CallNotAllowedInsideStatic |
-                InstructionResult::StateChangeDuringStaticCall |
-                InstructionResult::InvalidEFOpcode |
-                InstructionResult::InvalidJump |
-                InstructionResult::NotActivated |
-                InstructionResult::StackUnderflow |
-                InstructionResult::StackOverflow |
-                InstructionResult::OutOfOffset |
-                InstructionResult::CreateCollision |
-                InstructionResult::OverflowPayment |
-                InstructionResult::PrecompileError |
-                InstructionResult::NonceOverflow |
-                InstructionResult::CreateContractSizeLimit |
-                InstructionResult::CreateContractStartingWithEF |
-                InstructionResult::CreateInitCodeSizeLimit |
-                InstructionResult::FatalExternalError |
-                InstructionResult::OutOfFunds |
-                InstructionResult::CallTooDeep => Ok(Self::EvmError(exit)),
-
-                // Handle Revm EOF InstructionResults: Not supported yet
-                InstructionResult::ReturnContractInNotInitEOF |
-                InstructionResult::EOFOpcodeDisabledInLegacy |
-                InstructionResult::EOFFunctionStackOverflow |
-                InstructionResult::CreateInitCodeStartingEF00 |
-                InstructionResult::InvalidEOFInitCode |
-                InstructionResult::EofAuxDataOverflow |
-                InstructionResult::EofAuxDataTooSmall => Ok(Self::EvmError(exit)),
-            },
-        }
-    }
+/// The access list of a request, see [EthApi::create_access_list_with_error]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessListResult {
+    /// The accounts and storage slots accessed by the request
+    pub access_list: AccessList,
+    /// The gas estimated for the request with the access list applied, or the gas used by the
+    /// execution if it failed
+    pub gas_used: u128,
+    /// The reason the execution failed, if it did
+    pub error: Option<String>,
 }
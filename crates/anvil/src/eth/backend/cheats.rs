@@ -3,7 +3,11 @@
 use alloy_primitives::{Address, Signature};
 use anvil_core::eth::transaction::impersonated_signature;
 use parking_lot::RwLock;
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Instant,
+};
 
 /// Manages user modifications that may affect the node's behavior
 ///
@@ -23,6 +27,9 @@ impl CheatsManager {
     pub fn impersonate(&self, addr: Address) -> bool {
         trace!(target: "cheats", "Start impersonating {:?}", addr);
         let mut state = self.state.write();
+        // an explicit impersonation is permanent, so it overrides any previously configured expiry
+        state.impersonation_expiries.remove(&addr);
+        state.expired_impersonations.remove(&addr);
         // When somebody **explicitly** impersonates an account we need to store it so we are able
         // to return it from `eth_accounts`. That's why we do not simply call `is_impersonated()`
         // which does not check that list when auto impersonation is enabeld.
@@ -33,10 +40,24 @@ impl CheatsManager {
         state.impersonated_accounts.insert(addr)
     }
 
+    /// Sets the account to impersonate until the given `expiry` is reached
+    ///
+    /// Returns `true` if the account is already impersonated
+    pub fn impersonate_until(&self, addr: Address, expiry: ImpersonationExpiry) -> bool {
+        trace!(target: "cheats", "Start impersonating {:?} until {:?}", addr, expiry);
+        let mut state = self.state.write();
+        state.impersonation_expiries.insert(addr, expiry);
+        state.expired_impersonations.remove(&addr);
+        !state.impersonated_accounts.insert(addr)
+    }
+
     /// Removes the account that from the impersonated set
     pub fn stop_impersonating(&self, addr: &Address) {
         trace!(target: "cheats", "Stop impersonating {:?}", addr);
-        self.state.write().impersonated_accounts.remove(addr);
+        let mut state = self.state.write();
+        state.impersonated_accounts.remove(addr);
+        state.impersonation_expiries.remove(addr);
+        state.expired_impersonations.remove(addr);
     }
 
     /// Returns true if the `addr` is currently impersonated
@@ -44,10 +65,52 @@ impl CheatsManager {
         if self.state.read().auto_impersonate_accounts {
             true
         } else {
+            self.evict_expired_impersonations(None);
             self.state.read().impersonated_accounts.contains(&addr)
         }
     }
 
+    /// Returns true if `addr` was impersonated with an expiry that has since been reached
+    ///
+    /// This is cleared again when the account is impersonated again or explicitly stopped.
+    pub fn is_impersonation_expired(&self, addr: Address) -> bool {
+        self.evict_expired_impersonations(None);
+        self.state.read().expired_impersonations.contains(&addr)
+    }
+
+    /// Ends all impersonations that expire with the mined block `number`
+    pub fn on_new_block(&self, number: u64) {
+        self.evict_expired_impersonations(Some(number));
+    }
+
+    /// Moves all impersonations that have expired, either in wall-clock time or at
+    /// `block_number`, to the expired set
+    fn evict_expired_impersonations(&self, block_number: Option<u64>) {
+        let now = Instant::now();
+        let is_expired = |expiry: &ImpersonationExpiry| match *expiry {
+            ImpersonationExpiry::Instant(instant) => instant <= now,
+            ImpersonationExpiry::Block(number) => block_number.is_some_and(|n| number <= n),
+        };
+
+        if !self.state.read().impersonation_expiries.values().any(is_expired) {
+            return
+        }
+
+        let mut state = self.state.write();
+        let expired = state
+            .impersonation_expiries
+            .iter()
+            .filter(|(_, expiry)| is_expired(expiry))
+            .map(|(addr, _)| *addr)
+            .collect::<Vec<_>>();
+        for addr in expired {
+            trace!(target: "cheats", "Impersonation of {:?} expired", addr);
+            state.impersonation_expiries.remove(&addr);
+            state.impersonated_accounts.remove(&addr);
+            state.expired_impersonations.insert(addr);
+        }
+    }
+
     /// Returns the signature to use to bypass transaction signing
     pub fn bypass_signature(&self) -> Signature {
         self.state.read().bypass_signature
@@ -60,17 +123,36 @@ impl CheatsManager {
         self.state.write().auto_impersonate_accounts = enabled
     }
 
+    /// Returns true if auto impersonation is enabled
+    pub fn auto_impersonate_accounts(&self) -> bool {
+        self.state.read().auto_impersonate_accounts
+    }
+
     /// Returns all accounts that are currently being impersonated.
     pub fn impersonated_accounts(&self) -> HashSet<Address> {
+        self.evict_expired_impersonations(None);
         self.state.read().impersonated_accounts.clone()
     }
 }
 
+/// When an impersonation set via [CheatsManager::impersonate_until] ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImpersonationExpiry {
+    /// The impersonation ends at this point in wall-clock time
+    Instant(Instant),
+    /// The impersonation ends once the block with this number was mined
+    Block(u64),
+}
+
 /// Container type for all the state variables
 #[derive(Clone, Debug)]
 pub struct CheatsState {
     /// All accounts that are currently impersonated
     pub impersonated_accounts: HashSet<Address>,
+    /// Expiries of impersonated accounts that are only impersonated temporarily
+    pub impersonation_expiries: HashMap<Address, ImpersonationExpiry>,
+    /// Accounts whose temporary impersonation has expired
+    pub expired_impersonations: HashSet<Address>,
     /// The signature used for the `eth_sendUnsignedTransaction` cheat code
     pub bypass_signature: Signature,
     /// If set to true will make the `is_impersonated` function always return true
@@ -81,6 +163,8 @@ impl Default for CheatsState {
     fn default() -> Self {
         Self {
             impersonated_accounts: Default::default(),
+            impersonation_expiries: Default::default(),
+            expired_impersonations: Default::default(),
             bypass_signature: impersonated_signature(),
             auto_impersonate_accounts: false,
         }
@@ -4,7 +4,10 @@ use crate::revm::primitives::AccountInfo;
 use alloy_consensus::Header;
 use alloy_primitives::{keccak256, Address, Bytes, B256, U256, U64};
 use alloy_rpc_types::BlockId;
-use anvil_core::eth::{block::Block, transaction::TypedTransaction};
+use anvil_core::eth::{
+    block::Block,
+    transaction::{TransactionInfo, TypedReceipt, TypedTransaction},
+};
 use foundry_common::errors::FsPathError;
 use foundry_evm::{
     backend::{DatabaseError, DatabaseResult, MemDb, RevertSnapshotAction, StateSnapshot},
@@ -16,7 +19,11 @@ use foundry_evm::{
     },
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fmt, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::Path,
+};
 
 /// Helper trait get access to the full state data of the database
 #[auto_impl::auto_impl(Box)]
@@ -110,6 +117,14 @@ pub trait Db:
     /// Sets the balance of the given address
     fn set_storage_at(&mut self, address: Address, slot: U256, val: U256) -> DatabaseResult<()>;
 
+    /// Replaces the entire storage of the given address, slots that are not in `storage` will read
+    /// as zero afterwards
+    fn replace_account_storage(
+        &mut self,
+        address: Address,
+        storage: HashMap<U256, U256>,
+    ) -> DatabaseResult<()>;
+
     /// inserts a blockhash for the given number
     fn insert_block_hash(&mut self, number: U256, hash: B256);
 
@@ -166,6 +181,15 @@ pub trait Db:
         None
     }
 
+    /// Returns all accounts whose state differs from the state they were loaded with
+    ///
+    /// Without an upstream state every stored account is local.
+    fn locally_modified_accounts(&self) -> Vec<Address> {
+        self.maybe_as_full_db()
+            .map(|accounts| accounts.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
     /// Returns the current, standalone state of the Db
     fn current_state(&self) -> StateDb;
 }
@@ -183,6 +207,14 @@ impl<T: DatabaseRef<Error = DatabaseError> + Send + Sync + Clone + fmt::Debug> D
         self.insert_account_storage(address, slot, val)
     }
 
+    fn replace_account_storage(
+        &mut self,
+        address: Address,
+        storage: HashMap<U256, U256>,
+    ) -> DatabaseResult<()> {
+        Self::replace_account_storage(self, address, storage)
+    }
+
     fn insert_block_hash(&mut self, number: U256, hash: B256) {
         self.block_hashes.insert(number, hash);
     }
@@ -324,6 +356,14 @@ pub struct SerializableState {
     pub best_block_number: Option<U64>,
     #[serde(default)]
     pub blocks: Vec<SerializableBlock>,
+    /// The mined transactions of the `blocks`, with their receipts and execution info
+    #[serde(default)]
+    pub transactions: Vec<SerializableTransaction>,
+    /// The cheat code state, such as impersonated accounts.
+    ///
+    /// Note: This is an Option for backwards compatibility with state files that predate it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cheats: Option<SerializableCheats>,
 }
 
 impl SerializableState {
@@ -343,6 +383,42 @@ impl SerializableState {
     }
 }
 
+/// Manifest of a state dump that is split across multiple chunk files.
+///
+/// Each chunk is a gzipped [SerializableState] that only contains accounts.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SerializableStateManifest {
+    /// The file names of the chunks, relative to the manifest
+    pub chunks: Vec<String>,
+    /// The remaining chain data, without any accounts
+    pub state: SerializableState,
+}
+
+impl SerializableStateManifest {
+    /// The file name of the manifest within the dump directory
+    pub const FILE_NAME: &'static str = "manifest.json";
+}
+
+/// The serializable subset of the [CheatsState](super::cheats::CheatsState)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableCheats {
+    /// All explicitly impersonated accounts
+    #[serde(default)]
+    pub impersonated_accounts: BTreeSet<Address>,
+    /// Whether every account is impersonated
+    #[serde(default)]
+    pub auto_impersonate: bool,
+}
+
+/// The complete state of an account
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FullAccount {
+    pub balance: U256,
+    pub nonce: u64,
+    pub code: Bytes,
+    pub storage: BTreeMap<U256, U256>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SerializableAccountRecord {
     pub nonce: u64,
@@ -377,3 +453,11 @@ impl From<SerializableBlock> for Block {
         }
     }
 }
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableTransaction {
+    pub info: TransactionInfo,
+    pub receipt: TypedReceipt,
+    pub block_hash: B256,
+    pub block_number: u64,
+}
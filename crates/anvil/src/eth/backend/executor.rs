@@ -5,12 +5,15 @@ use crate::{
         pool::transactions::PoolTransaction,
     },
     inject_precompiles,
-    mem::inspector::Inspector,
-    PrecompileFactory,
+    mem::inspector::{CustomInspector, Inspector},
 };
 use alloy_consensus::{Header, Receipt, ReceiptWithBloom};
-use alloy_eips::eip2718::Encodable2718;
-use alloy_primitives::{Bloom, BloomInput, Log, B256};
+use alloy_eips::{
+    eip2718::Encodable2718,
+    eip2935::{HISTORY_STORAGE_ADDRESS, HISTORY_STORAGE_CODE},
+    eip4895::Withdrawal,
+};
+use alloy_primitives::{Address, Bloom, BloomInput, Log, B256, U256};
 use anvil_core::eth::{
     block::{Block, BlockInfo, PartialHeader},
     transaction::{
@@ -22,15 +25,22 @@ use foundry_evm::{
     backend::DatabaseError,
     revm::{
         interpreter::InstructionResult,
+        precompile::Precompile,
         primitives::{
             BlockEnv, CfgEnvWithHandlerCfg, EVMError, EnvWithHandlerCfg, ExecutionResult, Output,
-            SpecId,
+            ResultAndState, SpecId, KECCAK_EMPTY,
         },
     },
     traces::CallTraceNode,
 };
-use revm::primitives::MAX_BLOB_GAS_PER_BLOCK;
-use std::sync::Arc;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    sync::Arc,
+};
+
+/// The number of recent block hashes that are kept in the EIP-2935 history storage contract
+pub const HISTORY_SERVE_WINDOW: u64 = 8192;
 
 /// Represents an executed transaction (transacted on the DB)
 #[derive(Debug)]
@@ -39,9 +49,15 @@ pub struct ExecutedTransaction {
     exit_reason: InstructionResult,
     out: Option<Output>,
     gas_used: u128,
+    /// the gas refund of the transaction before it's capped per EIP-3529
+    gas_refunded: u64,
     logs: Vec<Log>,
+    /// logs emitted before the transaction reverted, only recorded if enabled
+    pre_revert_logs: Vec<Log>,
     traces: Vec<CallTraceNode>,
     nonce: u64,
+    /// all accounts that were modified by the transaction
+    touched_accounts: Vec<Address>,
 }
 
 // == impl ExecutedTransaction ==
@@ -76,15 +92,19 @@ impl ExecutedTransaction {
 }
 
 /// Represents the outcome of mining a new block
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct ExecutedTransactions {
     /// The block created after executing the `included` transactions
     pub block: BlockInfo,
     /// All transactions included in the
     pub included: Vec<Arc<PoolTransaction>>,
     /// All transactions that were invalid at the point of their execution and were not included in
-    /// the block
-    pub invalid: Vec<Arc<PoolTransaction>>,
+    /// the block, with the reason they were invalid
+    pub invalid: Vec<(Arc<PoolTransaction>, InvalidTransactionError)>,
+    /// All accounts that were modified by the `included` transactions and withdrawals
+    pub touched_accounts: HashSet<Address>,
+    /// All withdrawals that were processed in the block
+    pub withdrawals: Vec<Withdrawal>,
 }
 
 /// An executor for a series of transactions
@@ -103,9 +123,30 @@ pub struct TransactionExecutor<'a, Db: ?Sized, Validator: TransactionValidator>
     pub gas_used: u128,
     /// Cumulative blob gas used by all executed transactions
     pub blob_gas_used: u128,
+    /// Number of executed transactions
+    pub transaction_count: usize,
     pub enable_steps_tracing: bool,
-    /// Precompiles to inject to the EVM.
-    pub precompile_factory: Option<Arc<dyn PrecompileFactory>>,
+    /// Whether to record the logs that reverted transactions emitted before reverting
+    pub keep_reverted_logs: bool,
+    /// Whether to compute the state root after executing all transactions, if the `db` supports
+    /// it
+    pub compute_state_root: bool,
+    /// The maximum number of transactions to execute, if any
+    pub max_transactions: Option<usize>,
+    /// Precompiles to inject to the EVM, overriding any existing precompile at the same address.
+    pub precompiles: Vec<(Address, Precompile)>,
+    /// Nonces to restore after every transaction of the sender
+    pub pinned_nonces: HashMap<Address, u64>,
+    /// Senders that are exempt from EIP-3607
+    pub senders_with_code: HashSet<Address>,
+    /// Withdrawals to process after all transactions, only applied post-Shanghai
+    pub withdrawals: Vec<Withdrawal>,
+    /// Custom inspector that observes the execution of all transactions
+    pub custom_inspector: Option<Arc<dyn CustomInspector>>,
+    /// Whether transactions with a fee cap below the base fee are executed
+    pub accept_underpriced: bool,
+    /// The maximum blob gas of the block
+    pub max_blob_gas_per_block: u64,
 }
 
 impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'a, DB, Validator> {
@@ -118,18 +159,33 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
         let mut cumulative_gas_used: u128 = 0;
         let mut invalid = Vec::new();
         let mut included = Vec::new();
+        let mut touched_accounts = HashSet::new();
         let gas_limit = self.block_env.gas_limit.to::<u128>();
         let parent_hash = self.parent_hash;
         let block_number = self.block_env.number.to::<u64>();
         let difficulty = self.block_env.difficulty;
         let beneficiary = self.block_env.coinbase;
         let timestamp = self.block_env.timestamp.to::<u64>();
+        let mix_hash = self.block_env.prevrandao.unwrap_or_default();
         let base_fee = if self.cfg_env.handler_cfg.spec_id.is_enabled_in(SpecId::LONDON) {
             Some(self.block_env.basefee.to::<u128>())
         } else {
             None
         };
 
+        // post Prague the parent hash is stored in the history storage contract before any
+        // transaction is executed, see EIP-2935
+        if self.cfg_env.handler_cfg.spec_id >= SpecId::PRAGUE && block_number > 0 {
+            match self.store_parent_hash(block_number - 1, parent_hash) {
+                Ok(()) => {
+                    touched_accounts.insert(HISTORY_STORAGE_ADDRESS);
+                }
+                Err(err) => {
+                    warn!(target: "backend", ?err, "Failed to store parent hash in history contract");
+                }
+            }
+        }
+
         let is_cancun = self.cfg_env.handler_cfg.spec_id >= SpecId::CANCUN;
         let excess_blob_gas = if is_cancun { self.block_env.get_blob_excess_gas() } else { None };
         let mut cumulative_blob_gas_used = if is_cancun { Some(0u128) } else { None };
@@ -148,9 +204,13 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
                     trace!(target: "backend",  blob_gas = %tx.pending_transaction.transaction.blob_gas().unwrap_or_default(), ?tx,  "block blob gas limit exhausting, skipping transaction");
                     continue
                 }
-                TransactionExecutionOutcome::Invalid(tx, _) => {
+                TransactionExecutionOutcome::TransactionLimitReached(tx) => {
+                    trace!(target: "backend", ?tx,  "block transaction limit reached, skipping transaction");
+                    continue
+                }
+                TransactionExecutionOutcome::Invalid(tx, err) => {
                     trace!(target: "backend", ?tx,  "skipping invalid transaction");
-                    invalid.push(tx);
+                    invalid.push((tx, err));
                     continue
                 }
                 TransactionExecutionOutcome::DatabaseError(_, err) => {
@@ -172,6 +232,7 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
                     Some(cumulative_blob_gas_used.unwrap_or(0u128).saturating_add(tx_blob_gas));
             }
             let receipt = tx.create_receipt(&mut cumulative_gas_used);
+            touched_accounts.extend(tx.touched_accounts.iter().copied());
 
             let ExecutedTransaction { transaction, logs, out, traces, exit_reason: exit, .. } = tx;
             build_logs_bloom(logs.clone(), &mut bloom);
@@ -197,6 +258,8 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
                 out: out.map(Output::into_data),
                 nonce: tx.nonce,
                 gas_used: tx.gas_used,
+                gas_refunded: tx.gas_refunded,
+                pre_revert_logs: tx.pre_revert_logs,
             };
 
             transaction_infos.push(info);
@@ -204,6 +267,25 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
             transactions.push(transaction.pending_transaction.transaction.clone());
         }
 
+        let mut withdrawals = if self.cfg_env.handler_cfg.spec_id >= SpecId::SHANGHAI {
+            std::mem::take(&mut self.withdrawals)
+        } else {
+            Vec::new()
+        };
+        // withdrawals that can't be credited are not included in the block
+        withdrawals.retain(|withdrawal| match self.credit_withdrawal(withdrawal) {
+            Ok(()) => {
+                touched_accounts.insert(withdrawal.address);
+                true
+            }
+            Err(err) => {
+                warn!(target: "backend", ?err, ?withdrawal, "Failed to process withdrawal, dropping it");
+                false
+            }
+        });
+        let withdrawals_root = (!withdrawals.is_empty())
+            .then(|| trie::ordered_trie_root(withdrawals.iter().map(alloy_rlp::encode)));
+
         let ommers: Vec<Header> = Vec::new();
         let receipts_root =
             trie::ordered_trie_root(receipts.iter().map(Encodable2718::encoded_2718));
@@ -211,7 +293,11 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
         let partial_header = PartialHeader {
             parent_hash,
             beneficiary,
-            state_root: self.db.maybe_state_root().unwrap_or_default(),
+            state_root: if self.compute_state_root {
+                self.db.maybe_state_root().unwrap_or_default()
+            } else {
+                Default::default()
+            },
             receipts_root,
             logs_bloom: bloom,
             difficulty,
@@ -220,7 +306,8 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
             gas_used: cumulative_gas_used,
             timestamp,
             extra_data: Default::default(),
-            mix_hash: Default::default(),
+            mix_hash,
+            withdrawals_root,
             nonce: Default::default(),
             base_fee,
             parent_beacon_block_root: Default::default(),
@@ -230,7 +317,29 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
 
         let block = Block::new(partial_header, transactions.clone(), ommers);
         let block = BlockInfo { block, transactions: transaction_infos, receipts };
-        ExecutedTransactions { block, included, invalid }
+        ExecutedTransactions { block, included, invalid, touched_accounts, withdrawals }
+    }
+
+    /// Stores the hash of the parent block in the EIP-2935 history storage contract, deploys the
+    /// contract if it doesn't exist yet
+    fn store_parent_hash(&mut self, number: u64, hash: B256) -> Result<(), DatabaseError> {
+        let has_code = self
+            .db
+            .basic(HISTORY_STORAGE_ADDRESS)?
+            .is_some_and(|info| info.code_hash != KECCAK_EMPTY);
+        if !has_code {
+            self.db.set_code(HISTORY_STORAGE_ADDRESS, HISTORY_STORAGE_CODE.clone())?;
+        }
+        let slot = U256::from(number % HISTORY_SERVE_WINDOW);
+        self.db.set_storage_at(HISTORY_STORAGE_ADDRESS, slot, U256::from_be_bytes(hash.0))
+    }
+
+    /// Credits the withdrawn amount to the recipient of the withdrawal
+    fn credit_withdrawal(&mut self, withdrawal: &Withdrawal) -> Result<(), DatabaseError> {
+        let mut info = self.db.basic(withdrawal.address)?.unwrap_or_default();
+        info.balance = info.balance.saturating_add(withdrawal.amount_wei());
+        self.db.insert_account(withdrawal.address, info);
+        Ok(())
     }
 
     fn env_for(&self, tx: &PendingTransaction) -> EnvWithHandlerCfg {
@@ -240,7 +349,14 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
                 Some(alloy_rlp::encode(&tx.transaction.transaction).into());
         }
 
-        EnvWithHandlerCfg::new_with_cfg_env(self.cfg_env.clone(), self.block_env.clone(), tx_env)
+        let mut cfg_env = self.cfg_env.clone();
+        if self.senders_with_code.contains(tx.sender()) {
+            cfg_env.disable_eip3607 = true;
+        }
+        if self.accept_underpriced {
+            cfg_env.disable_base_fee = true;
+        }
+        EnvWithHandlerCfg::new_with_cfg_env(cfg_env, self.block_env.clone(), tx_env)
     }
 }
 
@@ -255,6 +371,8 @@ pub enum TransactionExecutionOutcome {
     Exhausted(Arc<PoolTransaction>),
     /// Execution skipped because it exceeded the blob gas limit
     BlobGasExhausted(Arc<PoolTransaction>),
+    /// Execution skipped because the block already contains the maximum number of transactions
+    TransactionLimitReached(Arc<PoolTransaction>),
     /// When an error occurred during execution
     DatabaseError(Arc<PoolTransaction>, DatabaseError),
 }
@@ -266,6 +384,9 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
 
     fn next(&mut self) -> Option<Self::Item> {
         let transaction = self.pending.next()?;
+        if self.max_transactions.is_some_and(|max| self.transaction_count >= max) {
+            return Some(TransactionExecutionOutcome::TransactionLimitReached(transaction))
+        }
         let sender = *transaction.pending_transaction.sender();
         let account = match self.db.basic(sender).map(|acc| acc.unwrap_or_default()) {
             Ok(account) => account,
@@ -283,7 +404,7 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
         let max_blob_gas = self.blob_gas_used.saturating_add(
             transaction.pending_transaction.transaction.transaction.blob_gas().unwrap_or(0u128),
         );
-        if max_blob_gas > MAX_BLOB_GAS_PER_BLOCK as u128 {
+        if max_blob_gas > self.max_blob_gas_per_block as u128 {
             return Some(TransactionExecutionOutcome::BlobGasExhausted(transaction))
         }
 
@@ -300,22 +421,37 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
         let nonce = account.nonce;
 
         // records all call and step traces
-        let mut inspector = Inspector::default().with_tracing();
+        let mut inspector = Inspector::default()
+            .with_tracing()
+            .with_custom_inspector(self.custom_inspector.clone());
         if self.enable_steps_tracing {
             inspector = inspector.with_steps_tracing();
         }
+        if self.keep_reverted_logs {
+            inspector = inspector.with_emitted_logs();
+        }
 
-        let exec_result = {
+        let (exec_result, touched_accounts) = {
             let mut evm =
                 foundry_evm::utils::new_evm_with_inspector(&mut *self.db, env, &mut inspector);
-            if let Some(factory) = &self.precompile_factory {
-                inject_precompiles(&mut evm, factory.precompiles());
+            if !self.precompiles.is_empty() {
+                inject_precompiles(&mut evm, self.precompiles.clone());
             }
 
             trace!(target: "backend", "[{:?}] executing", transaction.hash());
-            // transact and commit the transaction
-            match evm.transact_commit() {
-                Ok(exec_result) => exec_result,
+            let res = evm.transact();
+            drop(evm);
+            match res {
+                // commit the transaction and keep track of the modified accounts
+                Ok(ResultAndState { result, state }) => {
+                    let touched_accounts = state
+                        .iter()
+                        .filter(|(_, account)| account.is_touched())
+                        .map(|(address, _)| *address)
+                        .collect::<Vec<_>>();
+                    self.db.commit(state);
+                    (result, touched_accounts)
+                }
                 Err(err) => {
                     warn!(target: "backend", "[{:?}] failed to execute: {:?}", transaction.hash(), err);
                     match err {
@@ -340,14 +476,20 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
         };
         inspector.print_logs();
 
-        let (exit_reason, gas_used, out, logs) = match exec_result {
+        if let Some(pinned) = self.pinned_nonces.get(&sender) {
+            if let Err(err) = self.db.set_nonce(sender, *pinned) {
+                return Some(TransactionExecutionOutcome::DatabaseError(transaction, err))
+            }
+        }
+
+        let (exit_reason, gas_used, gas_refunded, out, logs) = match exec_result {
             ExecutionResult::Success { reason, gas_used, logs, output, .. } => {
-                (reason.into(), gas_used, Some(output), Some(logs))
+                (reason.into(), gas_used, inspector.gas_refunded, Some(output), Some(logs))
             }
             ExecutionResult::Revert { gas_used, output } => {
-                (InstructionResult::Revert, gas_used, Some(Output::Call(output)), None)
+                (InstructionResult::Revert, gas_used, 0, Some(Output::Call(output)), None)
             }
-            ExecutionResult::Halt { reason, gas_used } => (reason.into(), gas_used, None, None),
+            ExecutionResult::Halt { reason, gas_used } => (reason.into(), gas_used, 0, None, None),
         };
 
         if exit_reason == InstructionResult::OutOfGas {
@@ -359,6 +501,7 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
 
         // Track the total gas used for total gas per block checks
         self.gas_used = self.gas_used.saturating_add(gas_used as u128);
+        self.transaction_count += 1;
 
         // Track the total blob gas used for total blob gas per blob checks
         if let Some(blob_gas) = transaction.pending_transaction.transaction.transaction.blob_gas() {
@@ -367,20 +510,65 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
 
         trace!(target: "backend::executor", "transacted [{:?}], result: {:?} gas {}", transaction.hash(), exit_reason, gas_used);
 
+        // logs of a successful transaction are already part of the receipt
+        let pre_revert_logs =
+            if logs.is_none() { inspector.emitted_logs.unwrap_or_default() } else { Vec::new() };
+
         let tx = ExecutedTransaction {
             transaction,
             exit_reason,
             out,
             gas_used: gas_used as u128,
+            gas_refunded,
             logs: logs.unwrap_or_default(),
+            pre_revert_logs,
             traces: inspector.tracer.map(|t| t.into_traces().into_nodes()).unwrap_or_default(),
             nonce,
+            touched_accounts,
         };
 
         Some(TransactionExecutionOutcome::Executed(tx))
     }
 }
 
+/// Sorts the transactions by their effective gas price against the block's `base_fee`, highest
+/// first
+///
+/// Transactions of the same sender keep their relative order so that nonces are still executed in
+/// order, ties are resolved by the original order.
+pub fn order_by_effective_gas_price(
+    transactions: Vec<Arc<PoolTransaction>>,
+    base_fee: u128,
+) -> Vec<Arc<PoolTransaction>> {
+    let effective_gas_price = |tx: &PoolTransaction| {
+        tx.pending_transaction.transaction.effective_gas_price(Some(base_fee))
+    };
+
+    let mut ordered = Vec::with_capacity(transactions.len());
+    let mut by_sender: HashMap<Address, VecDeque<(usize, Arc<PoolTransaction>)>> = HashMap::new();
+    for (idx, tx) in transactions.into_iter().enumerate() {
+        by_sender.entry(*tx.pending_transaction.sender()).or_default().push_back((idx, tx));
+    }
+
+    // the next transaction of every sender, ordered by price and then by original position
+    let mut next = by_sender
+        .iter()
+        .filter_map(|(sender, txs)| txs.front().map(|(idx, tx)| (tx, idx, sender)))
+        .map(|(tx, idx, sender)| (effective_gas_price(tx), Reverse(*idx), *sender))
+        .collect::<BinaryHeap<_>>();
+
+    while let Some((_, _, sender)) = next.pop() {
+        let Some(txs) = by_sender.get_mut(&sender) else { continue };
+        let Some((_, tx)) = txs.pop_front() else { continue };
+        ordered.push(tx);
+        if let Some((idx, tx)) = txs.front() {
+            next.push((effective_gas_price(tx), Reverse(*idx), sender));
+        }
+    }
+
+    ordered
+}
+
 /// Inserts all logs into the bloom
 fn build_logs_bloom(logs: Vec<Log>, bloom: &mut Bloom) {
     for log in logs {
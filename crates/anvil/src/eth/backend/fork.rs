@@ -9,7 +9,7 @@ use alloy_provider::{
 use alloy_rpc_types::{
     request::TransactionRequest,
     trace::{
-        geth::{GethDebugTracingOptions, GethTrace},
+        geth::{GethDebugTracingOptions, GethTrace, TraceResult},
         parity::LocalizedTransactionTrace as Trace,
     },
     AccessListWithGasUsed, Block, BlockId, BlockNumberOrTag as BlockNumber, BlockTransactions,
@@ -356,6 +356,21 @@ impl ClientFork {
         Ok(trace)
     }
 
+    pub async fn debug_trace_block(
+        &self,
+        block_id: BlockId,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>, TransportError> {
+        match block_id {
+            BlockId::Hash(hash) => {
+                self.provider().debug_trace_block_by_hash(hash.block_hash, opts).await
+            }
+            BlockId::Number(number) => {
+                self.provider().debug_trace_block_by_number(number, opts).await
+            }
+        }
+    }
+
     pub async fn trace_block(&self, number: u64) -> Result<Vec<Trace>, TransportError> {
         if let Some(traces) = self.storage_read().block_traces.get(&number).cloned() {
             return Ok(traces);
@@ -481,6 +496,21 @@ impl ClientFork {
         self.fetch_full_block(block_number).await
     }
 
+    /// Returns the number of the forked chain's block the given `tag` resolves to, e.g.
+    /// `finalized`
+    ///
+    /// Blocks after the fork block are not part of the fork, so this never exceeds
+    /// [Self::block_number].
+    pub async fn block_number_by_tag(
+        &self,
+        tag: BlockNumber,
+    ) -> Result<Option<u64>, TransportError> {
+        let block = self.provider().get_block(tag.into(), false.into()).await?;
+        Ok(block
+            .and_then(|block| block.header.number)
+            .map(|number| number.min(self.block_number())))
+    }
+
     async fn fetch_full_block(
         &self,
         block_id: impl Into<BlockId>,
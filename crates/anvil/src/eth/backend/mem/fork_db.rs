@@ -3,7 +3,7 @@ use crate::{
         Db, MaybeForkedDatabase, MaybeFullDatabase, SerializableAccountRecord, SerializableBlock,
         SerializableState, StateDb,
     },
-    revm::primitives::AccountInfo,
+    revm::primitives::{AccountInfo, HashMap},
 };
 use alloy_primitives::{Address, B256, U256, U64};
 use alloy_rpc_types::BlockId;
@@ -28,10 +28,42 @@ impl Db for ForkedDatabase {
         self.database_mut().set_storage_at(address, slot, val)
     }
 
+    fn replace_account_storage(
+        &mut self,
+        address: Address,
+        storage: HashMap<U256, U256>,
+    ) -> DatabaseResult<()> {
+        // this ensures the account is loaded first
+        let _ = Database::basic(self, address)?;
+        self.database_mut().replace_account_storage(address, storage)
+    }
+
     fn insert_block_hash(&mut self, number: U256, hash: B256) {
         self.inner().block_hashes().write().insert(number, hash);
     }
 
+    fn locally_modified_accounts(&self) -> Vec<Address> {
+        // everything fetched from the fork is also cached in the `BlockchainDb`, so any account
+        // that differs from its cached remote state was modified locally
+        let remote_accounts = self.inner().accounts().read();
+        let remote_storage = self.inner().storage().read();
+        self.database()
+            .accounts
+            .iter()
+            .filter(|(address, account)| {
+                if remote_accounts.get(*address) != Some(&account.info) {
+                    return true
+                }
+                let remote_storage = remote_storage.get(*address);
+                account
+                    .storage
+                    .iter()
+                    .any(|(slot, value)| remote_storage.and_then(|s| s.get(slot)) != Some(value))
+            })
+            .map(|(address, _)| *address)
+            .collect()
+    }
+
     fn dump_state(
         &self,
         at: BlockEnv,
@@ -66,6 +98,8 @@ impl Db for ForkedDatabase {
             accounts,
             best_block_number: Some(best_number),
             blocks,
+            transactions: Vec::new(),
+            cheats: None,
         }))
     }
 
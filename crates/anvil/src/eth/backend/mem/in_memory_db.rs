@@ -29,6 +29,14 @@ impl Db for MemDb {
         self.inner.insert_account_storage(address, slot, val)
     }
 
+    fn replace_account_storage(
+        &mut self,
+        address: Address,
+        storage: HashMap<U256, U256>,
+    ) -> DatabaseResult<()> {
+        self.inner.replace_account_storage(address, storage)
+    }
+
     fn insert_block_hash(&mut self, number: U256, hash: B256) {
         self.inner.block_hashes.insert(number, hash);
     }
@@ -67,6 +75,8 @@ impl Db for MemDb {
             accounts,
             best_block_number: Some(best_number),
             blocks,
+            transactions: Vec::new(),
+            cheats: None,
         }))
     }
 
@@ -8,7 +8,8 @@ use foundry_evm::{
     inspectors::{LogCollector, TracingInspector},
     revm::{
         interpreter::{
-            CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, Interpreter,
+            CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs,
+            InstructionResult, Interpreter, InterpreterResult,
         },
         primitives::U256,
         EvmContext,
@@ -16,6 +17,36 @@ use foundry_evm::{
     traces::TracingInspectorConfig,
     InspectorExt,
 };
+use std::{fmt, sync::Arc, time::Instant};
+
+/// A custom inspector that runs alongside the [Inspector], see
+/// [Backend::set_custom_inspector](super::Backend::set_custom_inspector)
+///
+/// Unlike a [`revm::Inspector`] this is independent of the database type, it only observes the
+/// execution and can't modify it. Implementations that collect data, like profilers or coverage
+/// collectors, need to use interior mutability.
+pub trait CustomInspector: fmt::Debug + Send + Sync {
+    /// Called before every instruction is executed
+    fn step(&self, _interp: &Interpreter) {}
+
+    /// Called after every instruction was executed
+    fn step_end(&self, _interp: &Interpreter) {}
+
+    /// Called for every emitted log
+    fn log(&self, _log: &Log) {}
+
+    /// Called when a call frame is entered
+    fn call(&self, _inputs: &CallInputs) {}
+
+    /// Called when a call frame is exited
+    fn call_end(&self, _inputs: &CallInputs, _outcome: &CallOutcome) {}
+
+    /// Called when a create frame is entered
+    fn create(&self, _inputs: &CreateInputs) {}
+
+    /// Called when a create frame is exited
+    fn create_end(&self, _inputs: &CreateInputs, _outcome: &CreateOutcome) {}
+}
 
 /// The [`revm::Inspector`] used when transacting in the evm
 #[derive(Clone, Debug, Default)]
@@ -23,6 +54,17 @@ pub struct Inspector {
     pub tracer: Option<TracingInspector>,
     /// collects all `console.sol` logs
     pub log_collector: LogCollector,
+    /// records all emitted logs, including those that are discarded if the call reverts
+    pub emitted_logs: Option<Vec<Log>>,
+    /// the point in time after which execution is halted, see [Self::with_deadline]
+    pub deadline: Option<Instant>,
+    /// whether execution was halted because the deadline passed
+    pub timed_out: bool,
+    /// the custom inspector that observes the execution, see [Self::with_custom_inspector]
+    pub custom: Option<Arc<dyn CustomInspector>>,
+    /// the gas refund of the last exited frame before it's capped per EIP-3529, which is the
+    /// outermost frame once the execution finished
+    pub gas_refunded: u64,
 }
 
 impl Inspector {
@@ -40,8 +82,29 @@ impl Inspector {
     }
 
     /// Enables steps recording for `Tracer`.
+    ///
+    /// Storage changes are recorded as well so that struct logs of mined transactions can include
+    /// the touched storage slots.
     pub fn with_steps_tracing(mut self) -> Self {
-        self.tracer = Some(TracingInspector::new(TracingInspectorConfig::all()));
+        self.tracer = Some(TracingInspector::new(TracingInspectorConfig::all().with_state_diffs()));
+        self
+    }
+
+    /// Enables recording of all emitted logs, see [Self::emitted_logs]
+    pub fn with_emitted_logs(mut self) -> Self {
+        self.emitted_logs = Some(Vec::new());
+        self
+    }
+
+    /// Runs the given custom inspector alongside
+    pub fn with_custom_inspector(mut self, custom: Option<Arc<dyn CustomInspector>>) -> Self {
+        self.custom = custom;
+        self
+    }
+
+    /// Halts execution once the given `deadline` has passed, see [Self::timed_out]
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
         self
     }
 }
@@ -57,21 +120,42 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
         call_inspectors!([&mut self.tracer], |inspector| {
             inspector.step(interp, ecx);
         });
+        if let Some(custom) = &self.custom {
+            custom.step(interp);
+        }
+
+        // once timed out, every remaining frame is halted on its next step
+        if self.timed_out || self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.timed_out = true;
+            interp.instruction_result = InstructionResult::OutOfGas;
+        }
     }
 
     fn step_end(&mut self, interp: &mut Interpreter, ecx: &mut EvmContext<DB>) {
         call_inspectors!([&mut self.tracer], |inspector| {
             inspector.step_end(interp, ecx);
         });
+        if let Some(custom) = &self.custom {
+            custom.step_end(interp);
+        }
     }
 
     fn log(&mut self, ecx: &mut EvmContext<DB>, log: &Log) {
         call_inspectors!([&mut self.tracer, Some(&mut self.log_collector)], |inspector| {
             inspector.log(ecx, log);
         });
+        if let Some(logs) = &mut self.emitted_logs {
+            logs.push(log.clone());
+        }
+        if let Some(custom) = &self.custom {
+            custom.log(log);
+        }
     }
 
     fn call(&mut self, ecx: &mut EvmContext<DB>, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        if let Some(custom) = &self.custom {
+            custom.call(inputs);
+        }
         call_inspectors!(
             #[ret]
             [&mut self.tracer, Some(&mut self.log_collector)],
@@ -86,9 +170,14 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
         inputs: &CallInputs,
         outcome: CallOutcome,
     ) -> CallOutcome {
-        if let Some(tracer) = &mut self.tracer {
-            return tracer.call_end(ecx, inputs, outcome);
+        let outcome = match &mut self.tracer {
+            Some(tracer) => tracer.call_end(ecx, inputs, outcome),
+            None => outcome,
+        };
+        if let Some(custom) = &self.custom {
+            custom.call_end(inputs, &outcome);
         }
+        self.gas_refunded = frame_gas_refunded(&outcome.result);
 
         outcome
     }
@@ -98,6 +187,9 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
         ecx: &mut EvmContext<DB>,
         inputs: &mut CreateInputs,
     ) -> Option<CreateOutcome> {
+        if let Some(custom) = &self.custom {
+            custom.create(inputs);
+        }
         if let Some(tracer) = &mut self.tracer {
             if let Some(out) = tracer.create(ecx, inputs) {
                 return Some(out);
@@ -112,9 +204,14 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
         inputs: &CreateInputs,
         outcome: CreateOutcome,
     ) -> CreateOutcome {
-        if let Some(tracer) = &mut self.tracer {
-            return tracer.create_end(ecx, inputs, outcome);
+        let outcome = match &mut self.tracer {
+            Some(tracer) => tracer.create_end(ecx, inputs, outcome),
+            None => outcome,
+        };
+        if let Some(custom) = &self.custom {
+            custom.create_end(inputs, &outcome);
         }
+        self.gas_refunded = frame_gas_refunded(&outcome.result);
 
         outcome
     }
@@ -163,3 +260,12 @@ pub fn print_logs(logs: &[Log]) {
         node_info!("{}", log);
     }
 }
+
+/// Returns the gas refund of an exited frame, refunds are only granted if the frame succeeded
+fn frame_gas_refunded(result: &InterpreterResult) -> u64 {
+    if result.is_ok() {
+        result.gas.refunded().max(0) as u64
+    } else {
+        0
+    }
+}
@@ -5,90 +5,122 @@ use crate::{
     config::PruneStateHistoryConfig,
     eth::{
         backend::{
-            cheats::CheatsManager,
-            db::{Db, MaybeFullDatabase, SerializableState},
-            executor::{ExecutedTransactions, TransactionExecutor},
+            cheats::{CheatsManager, ImpersonationExpiry},
+            db::{
+                Db, FullAccount, MaybeFullDatabase, SerializableCheats, SerializableState,
+                SerializableStateManifest,
+            },
+            executor::{order_by_effective_gas_price, ExecutedTransactions, TransactionExecutor},
             fork::ClientFork,
             genesis::GenesisConfig,
             mem::{
-                state::{storage_root, trie_accounts},
+                state::{build_root, storage_root, trie_accounts},
                 storage::MinedTransactionReceipt,
             },
-            notifications::{NewBlockNotification, NewBlockNotifications},
+            notifications::{
+                NewBlockNotification, NewBlockNotifications, NewBlockOutcomeNotification,
+                NewBlockOutcomeNotifications, NewPendingTransactionNotifications,
+            },
             time::{utc_from_secs, TimeManager},
             validate::TransactionValidator,
         },
-        error::{BlockchainError, ErrDetail, InvalidTransactionError},
-        fees::{FeeDetails, FeeManager, MIN_SUGGESTED_PRIORITY_FEE},
+        error::{BlockchainError, ErrDetail, FeeHistoryError, InvalidTransactionError},
+        fees::{BlobParams, FeeDetails, FeeManager, MIN_SUGGESTED_PRIORITY_FEE},
         macros::node_info,
-        pool::transactions::PoolTransaction,
+        pool::transactions::{PoolTransaction, TransactionPriority},
         util::get_precompiles_for,
     },
     inject_precompiles,
     mem::{
-        inspector::Inspector,
-        storage::{BlockchainStorage, InMemoryBlockStates, MinedBlockOutcome},
+        in_memory_db::MemDb,
+        inspector::{CustomInspector, Inspector},
+        storage::{
+            BlockchainStorage, DryRunOutcome, InMemoryBlockStates, MinedBlockOutcome,
+            PendingBlockGasUsage,
+        },
     },
     revm::{db::DatabaseRef, primitives::AccountInfo},
-    NodeConfig, PrecompileFactory,
+    MockPrecompile, MockPrecompileHandler, NodeConfig, PrecompileFactory,
+};
+use alloy_consensus::{Header, Receipt, ReceiptWithBloom, TxEip4844Variant};
+use alloy_eips::{
+    eip2718::Encodable2718,
+    eip4844::{Blob, BlobTransactionSidecar},
+    eip4895::Withdrawal,
+};
+use alloy_genesis::GenesisAccount;
+use alloy_primitives::{
+    hex, keccak256, Address, Bytes, Selector, TxHash, TxKind, B256, I256, U256, U64,
 };
-use alloy_consensus::{Header, Receipt, ReceiptWithBloom};
-use alloy_eips::eip4844::MAX_BLOBS_PER_BLOCK;
-use alloy_primitives::{keccak256, Address, Bytes, TxHash, TxKind, B256, U256, U64};
 use alloy_rpc_types::{
     anvil::Forking,
     request::TransactionRequest,
     serde_helpers::JsonStorageKey,
     state::StateOverride,
     trace::{
-        geth::{DefaultFrame, GethDebugTracingOptions, GethDefaultTracingOptions, GethTrace},
+        geth::{
+            DefaultFrame, GethDebugTracingOptions, GethDefaultTracingOptions, GethTrace,
+            TraceResult,
+        },
         parity::LocalizedTransactionTrace,
     },
-    AccessList, Block as AlloyBlock, BlockId, BlockNumberOrTag as BlockNumber,
-    EIP1186AccountProofResponse as AccountProof, EIP1186StorageProof as StorageProof, Filter,
-    FilteredParams, Header as AlloyHeader, Index, Log, Transaction, TransactionReceipt,
+    AccessList, Block as AlloyBlock, BlockId, BlockNumberOrTag as BlockNumber, BlockOverrides,
+    EIP1186AccountProofResponse as AccountProof, EIP1186StorageProof as StorageProof, FeeHistory,
+    Filter, FilteredParams, Header as AlloyHeader, Index, Log, Transaction, TransactionReceipt,
 };
-use alloy_serde::WithOtherFields;
+use alloy_serde::{OtherFields, WithOtherFields};
+use alloy_sol_types::SolCall;
 use alloy_trie::{proof::ProofRetainer, HashBuilder, Nibbles};
-use anvil_core::eth::{
-    block::{Block, BlockInfo},
-    transaction::{
-        DepositReceipt, MaybeImpersonatedTransaction, PendingTransaction, ReceiptResponse,
-        TransactionInfo, TypedReceipt, TypedTransaction,
+use anvil_core::{
+    eth::{
+        block::{Block, BlockInfo},
+        transaction::{
+            transaction_request_to_typed, DepositReceipt, MaybeImpersonatedTransaction,
+            PendingTransaction, ReceiptResponse, TransactionInfo, TypedReceipt, TypedTransaction,
+            TypedTransactionRequest,
+        },
+        utils::meets_eip155,
     },
-    utils::meets_eip155,
+    types::{StorageRange, StorageRangeEntry},
 };
 use anvil_rpc::error::RpcError;
 use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use foundry_common::ens::{namehash, reverse_address, EnsRegistry, EnsResolver};
 use foundry_evm::{
     backend::{DatabaseError, DatabaseResult, RevertSnapshotAction},
-    constants::DEFAULT_CREATE2_DEPLOYER_RUNTIME_CODE,
+    constants::{DEFAULT_CREATE2_DEPLOYER, DEFAULT_CREATE2_DEPLOYER_RUNTIME_CODE},
     decode::RevertDecoder,
     inspectors::AccessListInspector,
     revm::{
         db::CacheDB,
-        interpreter::InstructionResult,
+        interpreter::{return_ok, InstructionResult},
+        precompile::Precompile,
         primitives::{
             BlockEnv, CfgEnvWithHandlerCfg, EnvWithHandlerCfg, ExecutionResult, Output, SpecId,
             TxEnv, KECCAK_EMPTY,
         },
     },
+    traces::CallTraceNode,
     utils::new_evm_with_inspector_ref,
     InspectorExt,
 };
-use futures::channel::mpsc::{unbounded, UnboundedSender};
+use futures::{
+    channel::mpsc::{unbounded, UnboundedSender},
+    StreamExt,
+};
 use parking_lot::{Mutex, RwLock};
 use revm::{
     db::WrapDatabaseRef,
-    primitives::{
-        calc_blob_gasprice, BlobExcessGasAndPrice, HashMap, OptimismFields, ResultAndState,
-    },
+    optimism::L1BlockInfo,
+    primitives::{BlobExcessGasAndPrice, HashMap, OptimismFields, ResultAndState},
+    DatabaseCommit,
 };
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashSet},
     io::{Read, Write},
+    path::{Path, PathBuf},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use storage::{Blockchain, MinedTransaction};
 use tokio::sync::RwLock as AsyncRwLock;
@@ -104,9 +136,27 @@ pub mod storage;
 pub const MIN_TRANSACTION_GAS: u128 = 21000;
 // Gas per transaction creating a contract.
 pub const MIN_CREATE_GAS: u128 = 53000;
+// Maximum number of blocks that are scanned for event signatures at once.
+pub const MAX_EVENT_SIGNATURES_RANGE: u64 = 10_000;
+// Maximum number of blocks that are re-executed at once to find the transactions touching a slot.
+pub const MAX_SLOT_HISTORY_RANGE: u64 = 1_000;
+// Code prefix of accounts that delegate to the code of another account, see EIP-7702.
+pub const EIP7702_DELEGATION_DESIGNATOR: [u8; 3] = [0xef, 0x01, 0x00];
 
 pub type State = foundry_evm::utils::StateChangeset;
 
+/// The sorted account trie leaves of a block, see [trie_accounts], and the block's hash
+type BlockTrieLeaves = (B256, Arc<Vec<(Nibbles, Vec<u8>)>>);
+
+/// Callback that is invoked with the outcome of every mined block, see
+/// [Backend::set_on_block_mined]
+pub type BlockMinedCallback = Arc<dyn Fn(&MinedBlockOutcome) + Send + Sync>;
+
+/// Custom validator that is invoked with every transaction added to the pool after the built-in
+/// validation, see [Backend::set_transaction_validator]
+pub type TransactionValidatorCallback =
+    Arc<dyn Fn(&PendingTransaction, &AccountInfo) -> Result<(), String> + Send + Sync>;
+
 /// A block request, which includes the Pool Transactions if it's Pending
 #[derive(Debug)]
 pub enum BlockRequest {
@@ -123,6 +173,90 @@ impl BlockRequest {
     }
 }
 
+/// A lightweight summary of the current head of the chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChainTip {
+    /// The number of the best block
+    pub number: u64,
+    /// The hash of the best block
+    pub hash: B256,
+    /// The parent hash of the best block
+    pub parent_hash: B256,
+    /// The timestamp of the best block
+    pub timestamp: u64,
+    /// The base fee of the best block
+    pub base_fee: u128,
+    /// The gas used by the best block
+    pub gas_used: u128,
+}
+
+/// How the `prevrandao` value of newly mined blocks is chosen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PrevRandao {
+    /// A new random value for every block
+    #[default]
+    Random,
+    /// The same value for every block
+    Fixed(B256),
+    /// A value derived from the seed and the block number: `keccak256(seed ++ number)`
+    Seeded(B256),
+}
+
+impl PrevRandao {
+    /// Returns the `prevrandao` value for the block with the given number
+    pub fn for_block(&self, number: U256) -> B256 {
+        match self {
+            Self::Random => B256::random(),
+            Self::Fixed(value) => *value,
+            Self::Seeded(seed) => {
+                let mut preimage = [0u8; 64];
+                preimage[..32].copy_from_slice(seed.as_slice());
+                preimage[32..].copy_from_slice(&number.to_be_bytes::<32>());
+                keccak256(preimage)
+            }
+        }
+    }
+}
+
+/// The outcome of a call with the revert data decoded, see [Backend::call_and_decode]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallResult {
+    /// The call succeeded
+    Success {
+        /// The data returned by the call
+        output: Bytes,
+        gas_used: u128,
+    },
+    /// The call reverted
+    Revert {
+        /// The raw revert data
+        output: Bytes,
+        /// The decoded revert reason, e.g. the message of `Error(string)`
+        reason: Option<String>,
+        /// The selector of the error, if the revert data contains one
+        selector: Option<Selector>,
+        gas_used: u128,
+    },
+    /// The call halted, e.g. because it ran out of gas
+    Halt { reason: InstructionResult, gas_used: u128 },
+}
+
+impl CallResult {
+    /// Returns true if the call succeeded
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Success { .. })
+    }
+
+    /// Returns the gas used by the call
+    pub fn gas_used(&self) -> u128 {
+        match self {
+            Self::Success { gas_used, .. } |
+            Self::Revert { gas_used, .. } |
+            Self::Halt { gas_used, .. } => *gas_used,
+        }
+    }
+}
+
 /// Gives access to the [revm::Database]
 #[derive(Clone)]
 pub struct Backend {
@@ -163,18 +297,77 @@ pub struct Backend {
     genesis: GenesisConfig,
     /// listeners for new blocks that get notified when a new block was imported
     new_block_listeners: Arc<Mutex<Vec<UnboundedSender<NewBlockNotification>>>>,
+    /// listeners for new blocks that also get notified about the outcome of mining the block
+    new_block_outcome_listeners: Arc<Mutex<Vec<UnboundedSender<NewBlockOutcomeNotification>>>>,
+    /// listeners that get notified when a new transaction was added to the pool
+    new_pending_transaction_listeners: Arc<Mutex<Vec<UnboundedSender<Arc<PoolTransaction>>>>>,
+    /// callbacks that are invoked at the end of mining every block
+    block_mined_callbacks: Arc<Mutex<Vec<BlockMinedCallback>>>,
     /// keeps track of active snapshots at a specific block
     active_snapshots: Arc<Mutex<HashMap<U256, (u64, B256)>>>,
+    /// names of snapshots created via [Backend::create_named_snapshot]
+    named_snapshots: Arc<Mutex<HashMap<String, U256>>>,
+    /// blocks in which an account was active (sender, recipient or created contract), in
+    /// ascending order, so that the last active block can be restored on rollback
+    account_activity: Arc<Mutex<HashMap<Address, Vec<u64>>>>,
+    /// how the `prevrandao` of new blocks is chosen
+    prevrandao: Arc<RwLock<PrevRandao>>,
+    /// The coinbases new blocks rotate through, starting at the given block number, see
+    /// [Self::set_coinbase_schedule]
+    coinbase_schedule: Arc<RwLock<(Vec<Address>, U256)>>,
+    /// Whether new blocks only depend on their transactions, see [Self::set_deterministic_mining]
+    deterministic_mining: Arc<RwLock<bool>>,
+    /// the difficulty of new blocks before the merge, if set
+    block_difficulty: Arc<RwLock<Option<U256>>>,
+    /// the gas limit that the gas limit of new blocks moves towards, if enabled
+    gas_limit_target: Arc<RwLock<Option<u128>>>,
     enable_steps_tracing: bool,
+    /// whether receipts of reverted transactions include the logs emitted before the revert
+    keep_reverted_logs: Arc<RwLock<bool>>,
     /// How to keep history state
     prune_state_history_config: PruneStateHistoryConfig,
     /// max number of blocks with transactions in memory
     transaction_block_keeper: Option<usize>,
+    /// whether the states of blocks pruned by the `transaction_block_keeper` are removed as well
+    prune_states_with_transactions: bool,
+    /// The spec ids of the scheduled hardforks, keyed by their activation timestamp
+    hardfork_activations: BTreeMap<u64, SpecId>,
+    /// The spec id of blocks before the first scheduled hardfork activation
+    initial_spec_id: SpecId,
     node_config: Arc<AsyncRwLock<NodeConfig>>,
     /// Slots in an epoch
     slots_in_an_epoch: u64,
+    /// Whether to mine transactions sorted by their effective gas price
+    order_by_effective_gas_price: bool,
+    /// Whether to compute the state root of mined blocks
+    compute_state_root: bool,
+    /// The maximum number of transactions to include in a block
+    max_transactions_per_block: Option<usize>,
+    /// The wall-clock time after which calls are aborted
+    call_timeout: Option<Duration>,
     /// Precompiles to inject to the EVM.
     precompile_factory: Option<Arc<dyn PrecompileFactory>>,
+    /// Precompiles mocked via [Self::mock_precompile], keyed by their address
+    mocked_precompiles: Arc<RwLock<HashMap<Address, MockPrecompileHandler>>>,
+    /// Nonces pinned via [Self::pin_nonce]
+    pinned_nonces: Arc<RwLock<HashMap<Address, u64>>>,
+    /// Senders that are exempt from EIP-3607, see [Self::allow_sender_with_code]
+    senders_with_code: Arc<RwLock<HashSet<Address>>>,
+    /// The sorted account trie leaves of the historic block that was last proven via
+    /// [Self::prove_account_at], keyed by the block's hash
+    account_trie_cache: Arc<Mutex<Option<BlockTrieLeaves>>>,
+    /// Custom validator set via [Self::set_transaction_validator]
+    transaction_validator: Arc<RwLock<Option<TransactionValidatorCallback>>>,
+    /// Accounts that were modified directly, e.g. via [Self::set_balance], since the last mined
+    /// block
+    pending_touched_accounts: Arc<Mutex<HashSet<Address>>>,
+    /// Withdrawals queued via [Self::queue_withdrawals] for the next mined block
+    queued_withdrawals: Arc<Mutex<Vec<Withdrawal>>>,
+    /// Custom inspector set via [Self::set_custom_inspector]
+    custom_inspector: Arc<RwLock<Option<Arc<dyn CustomInspector>>>>,
+    /// Whether transactions with a fee cap below the base fee are accepted, see
+    /// [Self::set_accept_underpriced]
+    accept_underpriced: Arc<RwLock<bool>>,
 }
 
 impl Backend {
@@ -210,7 +403,38 @@ impl Backend {
             genesis.timestamp
         };
 
-        let states = if prune_state_history_config.is_config_enabled() {
+        let (
+            slots_in_an_epoch,
+            order_by_effective_gas_price,
+            compute_state_root,
+            max_transactions_per_block,
+            call_timeout,
+            prune_states_with_transactions,
+            hardfork_activations,
+            precompile_factory,
+            archive_mode,
+        ) = {
+            let cfg = node_config.read().await;
+            (
+                cfg.slots_in_an_epoch,
+                cfg.order_by_effective_gas_price,
+                cfg.compute_state_root,
+                cfg.max_transactions_per_block,
+                cfg.call_timeout,
+                cfg.prune_states_with_transactions && !cfg.archive_mode,
+                cfg.hardfork_activations
+                    .iter()
+                    .map(|(timestamp, hardfork)| (*timestamp, SpecId::from(*hardfork)))
+                    .collect(),
+                cfg.precompile_factory.clone(),
+                cfg.archive_mode,
+            )
+        };
+
+        let states = if archive_mode {
+            // keep every state, older states are moved to disk
+            InMemoryBlockStates::default().archive()
+        } else if prune_state_history_config.is_config_enabled() {
             // if prune state history is enabled, configure the state cache only for memory
             prune_state_history_config
                 .max_memory_history
@@ -221,10 +445,7 @@ impl Backend {
             Default::default()
         };
 
-        let (slots_in_an_epoch, precompile_factory) = {
-            let cfg = node_config.read().await;
-            (cfg.slots_in_an_epoch, cfg.precompile_factory.clone())
-        };
+        let initial_spec_id = env.read().handler_cfg.spec_id;
 
         let backend = Self {
             db,
@@ -235,15 +456,42 @@ impl Backend {
             time: TimeManager::new(start_timestamp),
             cheats: Default::default(),
             new_block_listeners: Default::default(),
+            new_block_outcome_listeners: Default::default(),
+            new_pending_transaction_listeners: Default::default(),
+            block_mined_callbacks: Default::default(),
             fees,
             genesis,
             active_snapshots: Arc::new(Mutex::new(Default::default())),
+            named_snapshots: Default::default(),
+            account_activity: Default::default(),
+            prevrandao: Default::default(),
+            coinbase_schedule: Default::default(),
+            deterministic_mining: Default::default(),
+            block_difficulty: Default::default(),
+            gas_limit_target: Default::default(),
             enable_steps_tracing,
+            keep_reverted_logs: Default::default(),
             prune_state_history_config,
             transaction_block_keeper,
+            prune_states_with_transactions,
+            hardfork_activations,
+            initial_spec_id,
             node_config,
             slots_in_an_epoch,
+            order_by_effective_gas_price,
+            compute_state_root,
+            max_transactions_per_block,
+            call_timeout,
             precompile_factory,
+            mocked_precompiles: Default::default(),
+            pinned_nonces: Default::default(),
+            senders_with_code: Default::default(),
+            account_trie_cache: Default::default(),
+            transaction_validator: Default::default(),
+            pending_touched_accounts: Default::default(),
+            queued_withdrawals: Default::default(),
+            custom_inspector: Default::default(),
+            accept_underpriced: Default::default(),
         };
 
         if let Some(interval_block_time) = automine_block_time {
@@ -257,9 +505,27 @@ impl Backend {
 
     /// Writes the CREATE2 deployer code directly to the database at the address provided.
     pub async fn set_create2_deployer(&self, address: Address) -> DatabaseResult<()> {
-        self.set_code(address, Bytes::from_static(DEFAULT_CREATE2_DEPLOYER_RUNTIME_CODE)).await?;
+        self.set_create2_deployer_code(
+            address,
+            Bytes::from_static(DEFAULT_CREATE2_DEPLOYER_RUNTIME_CODE),
+        )
+        .await
+    }
 
-        Ok(())
+    /// Writes the given runtime code of a custom CREATE2 deployer directly to the database at the
+    /// address provided.
+    pub async fn set_create2_deployer_code(
+        &self,
+        address: Address,
+        code: Bytes,
+    ) -> DatabaseResult<()> {
+        self.set_code(address, code).await
+    }
+
+    /// Returns whether a CREATE2 deployer is installed at the given address, i.e. whether there's
+    /// code at the address.
+    pub async fn has_create2_deployer(&self, address: Address) -> Result<bool, BlockchainError> {
+        Ok(!self.get_code(address, None).await?.is_empty())
     }
 
     /// Updates memory limits that should be more strict when auto-mine is enabled
@@ -275,20 +541,23 @@ impl Backend {
 
         if self.fork.read().is_some() {
             // fetch all account first
-            let mut genesis_accounts_futures = Vec::with_capacity(self.genesis.accounts.len());
-            for address in self.genesis.accounts.iter().copied() {
-                let db = Arc::clone(&self.db);
-
-                // The forking Database backend can handle concurrent requests, we can fetch all dev
-                // accounts concurrently by spawning the job to a new task
-                genesis_accounts_futures.push(tokio::task::spawn(async move {
-                    let db = db.read().await;
-                    let info = db.basic_ref(address)?.unwrap_or_default();
-                    Ok::<_, DatabaseError>((address, info))
-                }));
-            }
-
-            let genesis_accounts = futures::future::join_all(genesis_accounts_futures).await;
+            let concurrency = self.node_config.read().await.fork_genesis_accounts_concurrency;
+            let genesis_accounts: Vec<_> = futures::stream::iter(self.genesis.accounts.clone())
+                .map(|address| {
+                    let db = Arc::clone(&self.db);
+
+                    // The forking Database backend can handle concurrent requests, we can fetch
+                    // the dev accounts concurrently by spawning the job to a new task, but only
+                    // `concurrency` at a time so we don't run into the rate limits of the endpoint
+                    tokio::task::spawn(async move {
+                        let db = db.read().await;
+                        let info = db.basic_ref(address)?.unwrap_or_default();
+                        Ok::<_, DatabaseError>((address, info))
+                    })
+                })
+                .buffered(concurrency.max(1))
+                .collect()
+                .await;
 
             let mut db = self.db.write().await;
 
@@ -328,7 +597,7 @@ impl Backend {
     /// Returns `true` if the account is already impersonated
     pub async fn impersonate(&self, addr: Address) -> DatabaseResult<bool> {
         if self.cheats.impersonated_accounts().contains(&addr) {
-            return Ok(true);
+            return Ok(self.cheats.impersonate(addr));
         }
         // Ensure EIP-3607 is disabled
         let mut env = self.env.write();
@@ -336,6 +605,65 @@ impl Backend {
         Ok(self.cheats.impersonate(addr))
     }
 
+    /// Enables or disables EIP-3607, which rejects transactions from senders with code.
+    ///
+    /// This is disabled by default, and impersonating an account disables it again.
+    pub fn set_eip3607_enabled(&self, enabled: bool) {
+        self.env.write().cfg.disable_eip3607 = !enabled;
+    }
+
+    /// Allows or disallows the given account to send transactions even though it has code.
+    ///
+    /// Unlike impersonating, this keeps EIP-3607 enabled for all other accounts, which models
+    /// accounts that are both contracts and senders.
+    pub fn allow_sender_with_code(&self, address: Address, allow: bool) {
+        let mut senders = self.senders_with_code.write();
+        if allow {
+            senders.insert(address);
+        } else {
+            senders.remove(&address);
+        }
+    }
+
+    /// Returns whether the given account may send transactions even though it has code, see
+    /// [Self::allow_sender_with_code]
+    pub fn is_sender_with_code_allowed(&self, address: Address) -> bool {
+        self.senders_with_code.read().contains(&address)
+    }
+
+    /// Sets whether transactions with a fee cap below the current base fee are accepted and mined.
+    ///
+    /// This is useful for replaying historical transactions on a fork, which may pay less than the
+    /// current base fee. Note that the base fee check is skipped for every transaction, so blocks
+    /// can include transactions that would never be included on a real network.
+    pub fn set_accept_underpriced(&self, accept: bool) {
+        *self.accept_underpriced.write() = accept;
+    }
+
+    /// Returns whether transactions with a fee cap below the base fee are accepted, see
+    /// [Self::set_accept_underpriced]
+    pub fn accepts_underpriced(&self) -> bool {
+        *self.accept_underpriced.read()
+    }
+
+    /// Sets the account to impersonate for the given wall-clock `duration`
+    ///
+    /// Returns `true` if the account is already impersonated
+    pub async fn impersonate_for(&self, addr: Address, duration: Duration) -> DatabaseResult<bool> {
+        self.env.write().cfg.disable_eip3607 = true;
+        let expiry = ImpersonationExpiry::Instant(Instant::now() + duration);
+        Ok(self.cheats.impersonate_until(addr, expiry))
+    }
+
+    /// Sets the account to impersonate for the next `blocks` mined blocks
+    ///
+    /// Returns `true` if the account is already impersonated
+    pub async fn impersonate_for_blocks(&self, addr: Address, blocks: u64) -> DatabaseResult<bool> {
+        self.env.write().cfg.disable_eip3607 = true;
+        let expiry = ImpersonationExpiry::Block(self.best_number().saturating_add(blocks));
+        Ok(self.cheats.impersonate_until(addr, expiry))
+    }
+
     /// Removes the account that from the impersonated set
     ///
     /// If the impersonated `addr` is a contract then we also reset the code here
@@ -349,6 +677,18 @@ impl Backend {
         self.cheats.set_auto_impersonate_account(enabled);
     }
 
+    /// Returns all explicitly impersonated accounts, sorted by address
+    pub fn impersonated_accounts(&self) -> Vec<Address> {
+        let mut accounts = self.cheats.impersonated_accounts().into_iter().collect::<Vec<_>>();
+        accounts.sort_unstable();
+        accounts
+    }
+
+    /// Returns true if every account is impersonated automatically
+    pub fn is_auto_impersonating(&self) -> bool {
+        self.cheats.auto_impersonate_accounts()
+    }
+
     /// Returns the configured fork, if any
     pub fn get_fork(&self) -> Option<ClientFork> {
         self.fork.read().clone()
@@ -364,6 +704,42 @@ impl Backend {
         Ok(self.db.read().await.basic_ref(address)?.unwrap_or_default())
     }
 
+    /// Fetches the given accounts, including their code, and storage slots from the forked
+    /// endpoint so that subsequent accesses are served from the fork's cache.
+    ///
+    /// The accounts are fetched concurrently, at most `fork_genesis_accounts_concurrency` at a
+    /// time. This is a no-op if not in forking mode.
+    pub async fn prefetch_accounts(
+        &self,
+        accounts: Vec<(Address, Vec<U256>)>,
+    ) -> DatabaseResult<()> {
+        if !self.is_fork() {
+            return Ok(())
+        }
+
+        let concurrency = self.node_config.read().await.fork_genesis_accounts_concurrency;
+        let results: Vec<_> = futures::stream::iter(accounts)
+            .map(|(address, slots)| {
+                let db = Arc::clone(&self.db);
+                tokio::task::spawn(async move {
+                    let db = db.read().await;
+                    db.basic_ref(address)?;
+                    for slot in slots {
+                        db.storage_ref(address, slot)?;
+                    }
+                    Ok::<_, DatabaseError>(())
+                })
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+
+        for res in results {
+            res.map_err(DatabaseError::display)??;
+        }
+        Ok(())
+    }
+
     /// Whether we're forked off some remote client
     pub fn is_fork(&self) -> bool {
         self.fork.read().is_some()
@@ -453,6 +829,9 @@ impl Backend {
                 fork.total_difficulty(),
             );
             self.states.write().clear();
+            self.account_trie_cache.lock().take();
+            self.account_activity.lock().clear();
+            self.pending_touched_accounts.lock().clear();
 
             // insert back all genesis accounts, by reusing cached `AccountInfo`s we don't need to
             // fetch the data via RPC again
@@ -482,6 +861,21 @@ impl Backend {
         &self.time
     }
 
+    /// Sets the exact timestamp of the next mined block.
+    ///
+    /// The timestamp is only used once, subsequent blocks continue from it with the configured
+    /// interval or offset. Fails if the timestamp isn't strictly greater than the parent block's.
+    pub fn set_next_block_timestamp(&self, timestamp: u64) -> Result<(), BlockchainError> {
+        if let Some(parent) = self.best_block() {
+            if timestamp <= parent.header.timestamp {
+                return Err(BlockchainError::TimestampError(format!(
+                    "{timestamp} is lower than or equal to previous block's timestamp"
+                )))
+            }
+        }
+        self.time.set_next_block_timestamp(timestamp)
+    }
+
     /// Returns the `CheatsManager` responsible for executing cheatcodes
     pub fn cheats(&self) -> &CheatsManager {
         &self.cheats
@@ -507,6 +901,117 @@ impl Backend {
         self.blockchain.storage.read().best_number.try_into().unwrap_or(u64::MAX)
     }
 
+    /// Returns the current best block of the chain, as it's stored internally
+    ///
+    /// This is `None` if the block at `best_hash` is not stored locally, e.g. right after forking.
+    pub fn best_block(&self) -> Option<Block> {
+        let storage = self.blockchain.storage.read();
+        storage.blocks.get(&storage.best_hash).cloned()
+    }
+
+    /// Returns the number of blocks that were mined since the given account was last active.
+    ///
+    /// An account is active in a block if it sent or received a transaction or was created by
+    /// one. Returns `None` if the account was never active.
+    pub fn blocks_since_last_activity(&self, address: Address) -> Option<u64> {
+        let last_active = *self.account_activity.lock().get(&address)?.last()?;
+        Some(self.best_number().saturating_sub(last_active))
+    }
+
+    /// Returns the number of transactions per second mined over the last `window_blocks` blocks.
+    ///
+    /// The time span is measured from the parent of the first block in the window to the best
+    /// block, so a window of a single block measures the throughput of the best block alone. The
+    /// window is capped at the number of locally mined blocks.
+    pub fn mining_throughput(&self, window_blocks: u64) -> Result<f64, BlockchainError> {
+        let best = self.best_number();
+        let first_local = self.get_fork().map(|fork| fork.block_number()).unwrap_or_default();
+        let window = window_blocks.min(best.saturating_sub(first_local));
+        if window == 0 {
+            return Ok(0.0)
+        }
+
+        let storage = self.blockchain.storage.read();
+        let block_at = |number: u64| {
+            storage
+                .hashes
+                .get(&U64::from(number))
+                .and_then(|hash| storage.blocks.get(hash))
+                .ok_or(BlockchainError::BlockNotFound)
+        };
+
+        let start = block_at(best - window)?.header.timestamp;
+        let mut end = start;
+        let mut transactions = 0;
+        for number in best - window + 1..=best {
+            let block = block_at(number)?;
+            transactions += block.transactions.len();
+            end = block.header.timestamp;
+        }
+
+        let span = end.saturating_sub(start);
+        if span == 0 {
+            return Err(BlockchainError::Message(format!(
+                "cannot compute throughput, the last {window} blocks were mined at the same timestamp"
+            )))
+        }
+        Ok(transactions as f64 / span as f64)
+    }
+
+    /// Returns a summary of the current best block without building the full RPC block
+    ///
+    /// If the best block is not stored locally, e.g. right after forking, only the number, hash
+    /// and current base fee are known and the remaining fields are zero.
+    pub fn chain_tip(&self) -> ChainTip {
+        let storage = self.blockchain.storage.read();
+        let header = storage.blocks.get(&storage.best_hash).map(|block| &block.header);
+        ChainTip {
+            number: storage.best_number.to::<u64>(),
+            hash: storage.best_hash,
+            parent_hash: header.map(|h| h.parent_hash).unwrap_or_default(),
+            timestamp: header.map(|h| h.timestamp).unwrap_or_default(),
+            base_fee: header
+                .and_then(|h| h.base_fee_per_gas)
+                .unwrap_or_else(|| self.fees.base_fee()),
+            gas_used: header.map(|h| h.gas_used).unwrap_or_default(),
+        }
+    }
+
+    /// Verifies that the `parent_hash` of every block in `from..=to` matches the hash of the
+    /// block before it
+    ///
+    /// Returns [BlockchainError::BrokenHashChain] with the number of the first block whose parent
+    /// hash doesn't match.
+    pub fn verify_hash_chain(&self, from: u64, to: u64) -> Result<(), BlockchainError> {
+        let best = self.best_number();
+        if to > best {
+            return Err(BlockchainError::BlockOutOfRange(best, to));
+        }
+        if from > to {
+            return Err(RpcError::invalid_params("`from` must not be greater than `to`").into());
+        }
+
+        let storage = self.blockchain.storage.read();
+        let header = |number: u64| {
+            storage
+                .hashes
+                .get(&U64::from(number))
+                .and_then(|hash| storage.blocks.get(hash))
+                .map(|block| &block.header)
+                .ok_or(BlockchainError::BlockNotFound)
+        };
+
+        let mut parent_hash = header(from)?.hash_slow();
+        for number in (from + 1)..=to {
+            let header = header(number)?;
+            if header.parent_hash != parent_hash {
+                return Err(BlockchainError::BrokenHashChain(number));
+            }
+            parent_hash = header.hash_slow();
+        }
+        Ok(())
+    }
+
     /// Sets the block number
     pub fn set_block_number(&self, number: U256) {
         let mut env = self.env.write();
@@ -514,8 +1019,12 @@ impl Backend {
     }
 
     /// Returns the client coinbase address.
+    ///
+    /// If a schedule is set via [Self::set_coinbase_schedule], this is the coinbase of the current
+    /// block.
     pub fn coinbase(&self) -> Address {
-        self.env.read().block.coinbase
+        let env = self.env.read();
+        self.scheduled_coinbase(env.block.number).unwrap_or(env.block.coinbase)
     }
 
     /// Returns the client coinbase address.
@@ -527,6 +1036,19 @@ impl Backend {
         self.env.write().cfg.chain_id = chain_id;
     }
 
+    /// Returns the configured contract code size limit, see [Self::set_code_size_limit]
+    pub fn code_size_limit(&self) -> Option<usize> {
+        self.env.read().cfg.limit_contract_code_size
+    }
+
+    /// Sets the maximum size of deployed contract code, used for calls and new blocks.
+    ///
+    /// The init code limit is twice this value. `None` means the spec default, which is the
+    /// EIP-170 limit of 24576 bytes.
+    pub fn set_code_size_limit(&self, limit: Option<usize>) {
+        self.env.write().cfg.limit_contract_code_size = limit;
+    }
+
     /// Returns balance of the given account.
     pub async fn current_balance(&self, address: Address) -> DatabaseResult<U256> {
         Ok(self.get_account(address).await?.balance)
@@ -542,21 +1064,240 @@ impl Backend {
         self.env.write().block.coinbase = address;
     }
 
+    /// Sets the coinbases that new blocks rotate through, round-robin starting with the next
+    /// block.
+    ///
+    /// An empty schedule restores the single coinbase set via [Self::set_coinbase].
+    pub fn set_coinbase_schedule(&self, coinbases: Vec<Address>) {
+        let start = self.env.read().block.number.saturating_add(U256::from(1));
+        *self.coinbase_schedule.write() = (coinbases, start);
+    }
+
+    /// Returns the coinbase of the block with the given number according to the schedule set via
+    /// [Self::set_coinbase_schedule], if any
+    fn scheduled_coinbase(&self, number: U256) -> Option<Address> {
+        let schedule = self.coinbase_schedule.read();
+        let (coinbases, start) = &*schedule;
+        if coinbases.is_empty() || number < *start {
+            return None
+        }
+        let index = (number - *start) % U256::from(coinbases.len());
+        Some(coinbases[index.to::<usize>()])
+    }
+
+    /// Queues withdrawals that are processed in the next mined block.
+    ///
+    /// The withdrawn amounts are credited to the recipients after all transactions of the block
+    /// were executed. Withdrawals remain queued until a block is mined with Shanghai enabled.
+    pub fn queue_withdrawals(&self, withdrawals: Vec<Withdrawal>) {
+        self.queued_withdrawals.lock().extend(withdrawals);
+    }
+
+    /// Sets a fixed `prevrandao` for all new blocks, `None` restores a random value per block
+    pub fn set_prevrandao(&self, prevrandao: Option<B256>) {
+        self.set_prevrandao_mode(prevrandao.map(PrevRandao::Fixed).unwrap_or_default());
+    }
+
+    /// Sets how the `prevrandao` of new blocks is chosen
+    pub fn set_prevrandao_mode(&self, mode: PrevRandao) {
+        *self.prevrandao.write() = mode;
+    }
+
+    /// Returns how the `prevrandao` of new blocks is chosen
+    pub fn prevrandao_mode(&self) -> PrevRandao {
+        *self.prevrandao.read()
+    }
+
+    /// Sets whether new blocks are mined deterministically, so that two nodes with the same
+    /// genesis that are fed the same transactions produce identical block hashes.
+    ///
+    /// If enabled:
+    ///  - the `prevrandao` (`mixHash`) is derived from the block number, unless it was set via
+    ///    [Self::set_prevrandao_mode]
+    ///  - the timestamp is the parent's timestamp plus the block timestamp interval, or plus one
+    ///    second if there's no interval, instead of the wall clock time
+    pub fn set_deterministic_mining(&self, enabled: bool) {
+        *self.deterministic_mining.write() = enabled;
+        self.time.set_deterministic(enabled);
+    }
+
+    /// Returns whether new blocks are mined deterministically
+    pub fn is_deterministic_mining(&self) -> bool {
+        *self.deterministic_mining.read()
+    }
+
+    /// Returns the `prevrandao` of the new block with the given number
+    fn next_prevrandao(&self, number: U256) -> B256 {
+        match *self.prevrandao.read() {
+            PrevRandao::Random if self.is_deterministic_mining() => {
+                PrevRandao::Seeded(B256::ZERO).for_block(number)
+            }
+            mode => mode.for_block(number),
+        }
+    }
+
+    /// Sets whether receipts of reverted transactions mined from now on include the logs that were
+    /// emitted before the revert.
+    ///
+    /// These logs are not part of the consensus receipt, they are returned in the non-standard
+    /// `preRevertLogs` field of the rpc receipt instead. Disabled by default.
+    pub fn set_keep_reverted_logs(&self, enabled: bool) {
+        *self.keep_reverted_logs.write() = enabled;
+    }
+
+    /// Returns whether the logs of reverted transactions are kept, see
+    /// [Self::set_keep_reverted_logs]
+    pub fn keep_reverted_logs(&self) -> bool {
+        *self.keep_reverted_logs.read()
+    }
+
+    /// Returns all accounts that were modified locally, sorted by address.
+    ///
+    /// In forking mode these are only the accounts whose state differs from the forked state, which
+    /// excludes accounts that were only read from the fork. Otherwise this returns all accounts.
+    pub async fn locally_modified_accounts(&self) -> Vec<Address> {
+        let mut accounts = self.db.read().await.locally_modified_accounts();
+        accounts.sort_unstable();
+        accounts
+    }
+
+    /// Records accounts that were modified directly, they're attributed to the next mined block
+    fn touch_accounts(&self, accounts: impl IntoIterator<Item = Address>) {
+        self.pending_touched_accounts.lock().extend(accounts);
+    }
+
+    /// Returns all accounts that were modified after the given block, sorted by address.
+    ///
+    /// These are the accounts modified by transactions of later blocks, and accounts modified
+    /// directly, e.g. via [Self::set_balance], since the given block. In forking mode only
+    /// changes after the forked block are known.
+    pub fn accounts_changed_since(&self, number: u64) -> Result<Vec<Address>, BlockchainError> {
+        let best_number = self.best_number();
+        if number > best_number {
+            return Err(BlockchainError::BlockNotFound)
+        }
+        if self.get_fork().is_some_and(|fork| number < fork.block_number()) {
+            return Err(BlockchainError::DataUnavailable)
+        }
+
+        let mut accounts = self.pending_touched_accounts.lock().clone();
+        let storage = self.blockchain.storage.read();
+        for n in (number + 1)..=best_number {
+            if let Some(touched) = storage
+                .hashes
+                .get(&U64::from(n))
+                .and_then(|hash| storage.touched_accounts.get(hash))
+            {
+                accounts.extend(touched.iter().copied());
+            }
+        }
+
+        let mut accounts: Vec<_> = accounts.into_iter().collect();
+        accounts.sort_unstable();
+        Ok(accounts)
+    }
+
+    /// Sets the difficulty of new blocks, which is also added to the total difficulty.
+    ///
+    /// This only applies before the merge, afterwards the difficulty is always zero and replaced
+    /// by `prevrandao`.
+    pub fn set_block_difficulty(&self, difficulty: U256) {
+        *self.block_difficulty.write() = Some(difficulty);
+    }
+
+    /// Returns the difficulty set via [Self::set_block_difficulty], if it applies to the current
+    /// spec
+    pub fn block_difficulty(&self) -> Option<U256> {
+        if self.is_eip3675() {
+            None
+        } else {
+            *self.block_difficulty.read()
+        }
+    }
+
     /// Sets the nonce of the given address
     pub async fn set_nonce(&self, address: Address, nonce: U256) -> DatabaseResult<()> {
+        self.touch_accounts([address]);
         self.db.write().await.set_nonce(address, nonce.try_into().unwrap_or(u64::MAX))
     }
 
+    /// Sets the nonce of the given address and pins it, so that it is reset to `nonce` after
+    /// every mined transaction of the address.
+    ///
+    /// This allows sending several transactions with the same nonce, which is not possible on a
+    /// real network.
+    pub async fn pin_nonce(&self, address: Address, nonce: u64) -> DatabaseResult<()> {
+        self.pinned_nonces.write().insert(address, nonce);
+        self.touch_accounts([address]);
+        self.db.write().await.set_nonce(address, nonce)
+    }
+
+    /// Removes the pinned nonce of the given address, see [Self::pin_nonce]
+    pub fn unpin_nonce(&self, address: Address) {
+        self.pinned_nonces.write().remove(&address);
+    }
+
     /// Sets the balance of the given address
     pub async fn set_balance(&self, address: Address, balance: U256) -> DatabaseResult<()> {
+        self.touch_accounts([address]);
         self.db.write().await.set_balance(address, balance)
     }
 
     /// Sets the code of the given address
     pub async fn set_code(&self, address: Address, code: Bytes) -> DatabaseResult<()> {
+        self.touch_accounts([address]);
         self.db.write().await.set_code(address, code.0.into())
     }
 
+    /// Sets the code at `address` to the runtime code read from the file at `path`
+    ///
+    /// The file may contain the code either as a hex string, with or without `0x` prefix, or as
+    /// raw bytes.
+    pub async fn set_genesis_code_from_file(
+        &self,
+        address: Address,
+        path: &Path,
+    ) -> Result<(), BlockchainError> {
+        let content = foundry_common::fs::read(path)
+            .map_err(|err| BlockchainError::Message(err.to_string()))?;
+        let code = std::str::from_utf8(&content)
+            .ok()
+            .and_then(|content| hex::decode(content.trim()).ok())
+            .unwrap_or(content);
+        self.set_code(address, code.into()).await?;
+        Ok(())
+    }
+
+    /// Sets the nonces of all given addresses while holding the database lock only once
+    pub async fn set_nonces(&self, entries: Vec<(Address, U256)>) -> DatabaseResult<()> {
+        self.touch_accounts(entries.iter().map(|(address, _)| *address));
+        let mut db = self.db.write().await;
+        for (address, nonce) in entries {
+            db.set_nonce(address, nonce.try_into().unwrap_or(u64::MAX))?;
+        }
+        Ok(())
+    }
+
+    /// Sets the balances of all given addresses while holding the database lock only once
+    pub async fn set_balances(&self, entries: Vec<(Address, U256)>) -> DatabaseResult<()> {
+        self.touch_accounts(entries.iter().map(|(address, _)| *address));
+        let mut db = self.db.write().await;
+        for (address, balance) in entries {
+            db.set_balance(address, balance)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the codes of all given addresses while holding the database lock only once
+    pub async fn set_codes(&self, entries: Vec<(Address, Bytes)>) -> DatabaseResult<()> {
+        self.touch_accounts(entries.iter().map(|(address, _)| *address));
+        let mut db = self.db.write().await;
+        for (address, code) in entries {
+            db.set_code(address, code.0.into())?;
+        }
+        Ok(())
+    }
+
     /// Sets the value for the given slot of the given address
     pub async fn set_storage_at(
         &self,
@@ -564,14 +1305,118 @@ impl Backend {
         slot: U256,
         val: B256,
     ) -> DatabaseResult<()> {
+        self.touch_accounts([address]);
         self.db.write().await.set_storage_at(address, slot, U256::from_be_bytes(val.0))
     }
 
+    /// Sets the values of all given slots of the given address
+    ///
+    /// If `clear_existing` is set, all slots that are not in `storage` will read as zero
+    /// afterwards.
+    pub async fn set_account_storage(
+        &self,
+        address: Address,
+        storage: HashMap<U256, B256>,
+        clear_existing: bool,
+    ) -> DatabaseResult<()> {
+        self.touch_accounts([address]);
+        let storage = storage.into_iter().map(|(slot, val)| (slot, U256::from_be_bytes(val.0)));
+        let mut db = self.db.write().await;
+        if clear_existing {
+            return db.replace_account_storage(address, storage.collect())
+        }
+        for (slot, val) in storage {
+            db.set_storage_at(address, slot, val)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the entire state of the given address with `account`
+    ///
+    /// Unlike setting the individual fields, this also clears all storage slots that are not part
+    /// of `account`.
+    pub async fn replace_account(
+        &self,
+        address: Address,
+        account: FullAccount,
+    ) -> DatabaseResult<()> {
+        let FullAccount { balance, nonce, code, storage } = account;
+        self.touch_accounts([address]);
+        let mut db = self.db.write().await;
+        db.insert_account(address, AccountInfo { balance, nonce, ..Default::default() });
+        db.set_code(address, code.0.into())?;
+        db.replace_account_storage(address, storage.into_iter().collect())
+    }
+
+    /// Returns the number of the oldest block whose state is still available, e.g. for
+    /// [Self::storage_at] or proofs.
+    ///
+    /// States of older blocks were pruned, in forking mode states of blocks before the forked
+    /// block are fetched from the remote endpoint. If every state is retained, e.g. in archive
+    /// mode, this is the genesis block.
+    pub fn oldest_historical_block(&self) -> u64 {
+        let best_number = self.best_number();
+        let states = self.states.read();
+        let storage = self.blockchain.storage.read();
+        let oldest = states
+            .hashes_oldest_first()
+            .find_map(|hash| storage.blocks.get(hash).map(|block| block.header.number))
+            .map_or(best_number, |number| number.min(best_number));
+        oldest
+    }
+
+    /// Returns the number and hash of all canonical blocks in `from..=to`
+    ///
+    /// `to` is clamped to the best block number, so clients can cheaply verify their view of the
+    /// chain, e.g. to detect reorgs, without fetching every block.
+    pub fn canonical_chain(&self, from: u64, to: u64) -> Vec<(u64, B256)> {
+        let storage = self.blockchain.storage.read();
+        let to = to.min(storage.best_number.to::<u64>());
+        let mut chain = Vec::with_capacity(to.saturating_sub(from).saturating_add(1) as usize);
+        for number in from..=to {
+            match storage.hashes.get(&U64::from(number)) {
+                Some(hash) => chain.push((number, *hash)),
+                None => warn!(target: "backend", "missing hash of canonical block={}", number),
+            }
+        }
+        chain
+    }
+
     /// Returns the configured specid
     pub fn spec_id(&self) -> SpecId {
         self.env.read().handler_cfg.spec_id
     }
 
+    /// Returns the spec id of a block with the given timestamp
+    ///
+    /// This is the most recently activated hardfork of the configured schedule, or the spec id the
+    /// node was started with if no scheduled hardfork is active at `timestamp` yet.
+    pub fn spec_id_at(&self, timestamp: u64) -> SpecId {
+        self.hardfork_activations
+            .range(..=timestamp)
+            .next_back()
+            .map_or(self.initial_spec_id, |(_, spec_id)| *spec_id)
+    }
+
+    /// Returns the block env and spec id the transactions of the given block are executed with
+    fn block_env_for(&self, block: &Block) -> (BlockEnv, SpecId) {
+        let header = &block.header;
+        let block_env = BlockEnv {
+            number: U256::from(header.number),
+            coinbase: header.beneficiary,
+            timestamp: U256::from(header.timestamp),
+            difficulty: header.difficulty,
+            prevrandao: Some(header.mix_hash),
+            basefee: U256::from(header.base_fee_per_gas.unwrap_or_default()),
+            gas_limit: U256::from(header.gas_limit),
+            blob_excess_gas_and_price: header.excess_blob_gas.map(|excess| BlobExcessGasAndPrice {
+                excess_blob_gas: excess as u64,
+                blob_gasprice: self.fees.calc_blob_gasprice(excess as u64),
+            }),
+        };
+        (block_env, self.spec_id_at(header.timestamp))
+    }
+
     /// Returns true for post London
     pub fn is_eip1559(&self) -> bool {
         (self.spec_id() as u8) >= (SpecId::LONDON as u8)
@@ -638,6 +1483,33 @@ impl Backend {
         self.env.write().block.gas_limit = U256::from(gas_limit);
     }
 
+    /// Sets the gas limit new blocks move towards, `None` keeps the gas limit fixed.
+    ///
+    /// With a target, the gas limit of every block differs from its parent's by less than 1/1024
+    /// of the parent's gas limit, as enforced by the protocol. By default the gas limit is fixed.
+    pub fn set_gas_limit_target(&self, target: Option<u64>) {
+        *self.gas_limit_target.write() = target.map(|target| target as u128);
+    }
+
+    /// Enables or disables moving the gas limit of new blocks towards `target`.
+    ///
+    /// See [Self::set_gas_limit_target]
+    pub fn set_gas_limit_targeting(&self, enabled: bool, target: u64) {
+        self.set_gas_limit_target(enabled.then_some(target));
+    }
+
+    /// Returns the gas limit of a block with the given parent gas limit, moved towards the target
+    /// set via [Self::set_gas_limit_target] if any
+    fn next_gas_limit(&self, parent_gas_limit: u128) -> u128 {
+        let Some(target) = *self.gas_limit_target.read() else { return parent_gas_limit };
+        let max_delta = (parent_gas_limit / 1024).saturating_sub(1);
+        if parent_gas_limit < target {
+            parent_gas_limit.saturating_add(max_delta).min(target)
+        } else {
+            parent_gas_limit.saturating_sub(max_delta).max(target)
+        }
+    }
+
     /// Returns the current base fee
     pub fn base_fee(&self) -> u128 {
         self.fees.base_fee()
@@ -657,6 +1529,27 @@ impl Backend {
         self.fees.set_gas_price(price)
     }
 
+    /// Sets a custom gas target for the base fee calculation of the next blocks
+    pub fn set_gas_target(&self, gas_target: Option<u128>) {
+        self.fees.set_gas_target(gas_target)
+    }
+
+    /// Sets the minimum base fee of the next blocks, `None` removes the floor
+    pub fn set_base_fee_floor(&self, floor: Option<u128>) {
+        self.fees.set_base_fee_floor(floor)
+    }
+
+    /// Returns the blob schedule of the next blocks
+    pub fn blob_params(&self) -> BlobParams {
+        self.fees.blob_params()
+    }
+
+    /// Overrides the blob schedule of the next blocks, e.g. to simulate a rollup with a
+    /// non-standard max blob count, target or blob base fee update fraction
+    pub fn set_blob_params(&self, params: BlobParams) {
+        self.fees.set_blob_params(params)
+    }
+
     pub fn elasticity(&self) -> f64 {
         self.fees.elasticity()
     }
@@ -669,6 +1562,13 @@ impl Backend {
         self.blockchain.storage.read().total_difficulty
     }
 
+    /// Returns the combined RLP-encoded size of all locally stored blocks, in bytes
+    ///
+    /// Note: in forking mode this only includes the blocks mined after the fork
+    pub fn total_chain_size(&self) -> u64 {
+        self.blockchain.storage.read().total_size
+    }
+
     /// Creates a new `evm_snapshot` at the current height
     ///
     /// Returns the id of the snapshot created
@@ -681,9 +1581,55 @@ impl Backend {
         id
     }
 
+    /// Creates a new snapshot at the current height that can be reverted to by `name`
+    ///
+    /// If a snapshot with this name already exists, the name is moved to the new snapshot.
+    ///
+    /// Returns the id of the snapshot created
+    pub async fn create_named_snapshot(&self, name: String) -> U256 {
+        let id = self.create_snapshot().await;
+        trace!(target: "backend", "naming snapshot {} {:?}", id, name);
+        self.named_snapshots.lock().insert(name, id);
+        id
+    }
+
+    /// Reverts the state to the snapshot with the given `name`.
+    pub async fn revert_named_snapshot(&self, name: &str) -> Result<bool, BlockchainError> {
+        let id = { self.named_snapshots.lock().get(name).copied() };
+        match id {
+            Some(id) => self.revert_snapshot(id).await,
+            None => Ok(false),
+        }
+    }
+
     /// Reverts the state to the snapshot identified by the given `id`.
     pub async fn revert_snapshot(&self, id: U256) -> Result<bool, BlockchainError> {
-        let block = { self.active_snapshots.lock().remove(&id) };
+        self.do_revert_snapshot(id, RevertSnapshotAction::RevertRemove).await
+    }
+
+    /// Reverts the state to the snapshot identified by the given `id`, but keeps the snapshot so
+    /// that it can be reverted to again.
+    pub async fn revert_snapshot_keep(&self, id: U256) -> Result<bool, BlockchainError> {
+        self.do_revert_snapshot(id, RevertSnapshotAction::RevertKeep).await
+    }
+
+    async fn do_revert_snapshot(
+        &self,
+        id: U256,
+        action: RevertSnapshotAction,
+    ) -> Result<bool, BlockchainError> {
+        let block = {
+            let mut active_snapshots = self.active_snapshots.lock();
+            if action.is_keep() {
+                active_snapshots.get(&id).copied()
+            } else {
+                active_snapshots.remove(&id)
+            }
+        };
+        // reverting also discards all snapshots that were taken afterwards
+        self.named_snapshots
+            .lock()
+            .retain(|_, snapshot| *snapshot < id || (action.is_keep() && *snapshot == id));
         if let Some((num, hash)) = block {
             let best_block_hash = {
                 // revert the storage that's newer than the snapshot
@@ -695,10 +1641,13 @@ impl Backend {
                     let n = U64::from(n);
                     if let Some(hash) = storage.hashes.remove(&n) {
                         if let Some(block) = storage.blocks.remove(&hash) {
+                            storage.total_size -= alloy_rlp::Encodable::length(&block) as u64;
                             for tx in block.transactions {
                                 let _ = storage.transactions.remove(&tx.hash());
                             }
                         }
+                        storage.touched_accounts.remove(&hash);
+                        storage.withdrawals.remove(&hash);
                     }
                 }
 
@@ -706,6 +1655,7 @@ impl Backend {
                 storage.best_hash = hash;
                 hash
             };
+            self.forget_activity_after(num);
             let block =
                 self.block_by_hash(best_block_hash).await?.ok_or(BlockchainError::BlockNotFound)?;
 
@@ -726,23 +1676,297 @@ impl Backend {
                 ..Default::default()
             };
         }
-        Ok(self.db.write().await.revert(id, RevertSnapshotAction::RevertRemove))
+        Ok(self.db.write().await.revert(id, action))
+    }
+
+    /// Forgets any account activity in blocks after the given block number
+    fn forget_activity_after(&self, number: u64) {
+        self.account_activity.lock().retain(|_, blocks| {
+            while blocks.last().is_some_and(|block| *block > number) {
+                blocks.pop();
+            }
+            !blocks.is_empty()
+        });
+    }
+
+    /// Rewinds the chain by `depth` blocks, see [Self::rollback]
+    pub async fn rewind(&self, depth: u64) -> Result<(), BlockchainError> {
+        if depth == 0 {
+            return Ok(())
+        }
+        let best_number = self.best_number();
+        let number = best_number.checked_sub(depth).ok_or_else(|| {
+            BlockchainError::Message(format!(
+                "cannot rewind {depth} blocks, the current block is {best_number}"
+            ))
+        })?;
+        let block = self.get_block(number).ok_or(BlockchainError::BlockNotFound)?;
+        self.rollback(block).await
+    }
+
+    /// Rolls the chain back to the given block, which becomes the new best block.
+    ///
+    /// All blocks after `common_block` are removed and the state is reset to the state at
+    /// `common_block`, which must still be retained in the state history.
+    pub async fn rollback(&self, common_block: Block) -> Result<(), BlockchainError> {
+        let number = common_block.header.number;
+        let hash = common_block.header.hash_slow();
+
+        let common_state = {
+            let mut states = self.states.write();
+            let state = states.get(&hash).ok_or(BlockchainError::HistoricalStatePruned(number))?;
+            state.maybe_as_full_db().ok_or(BlockchainError::DataUnavailable)?.clone()
+        };
+
+        // the hashes of the retained blocks, which are served by `BLOCKHASH`, and the ones of the
+        // removed blocks
+        let (block_hashes, removed): (Vec<_>, Vec<_>) = self
+            .blockchain
+            .storage
+            .read()
+            .hashes
+            .iter()
+            .map(|(n, block_hash)| (n.to::<u64>(), *block_hash))
+            .partition(|(n, _)| *n <= number);
+
+        {
+            let mut db = self.db.write().await;
+            db.clear();
+            for (address, account) in common_state {
+                db.insert_account(address, account.info);
+                for (slot, value) in account.storage {
+                    db.set_storage_at(address, slot, value)?;
+                }
+            }
+            for (n, block_hash) in block_hashes {
+                db.insert_block_hash(U256::from(n), block_hash);
+            }
+        }
+
+        {
+            let mut states = self.states.write();
+            for (_, block_hash) in &removed {
+                states.remove(block_hash);
+            }
+        }
+
+        self.blockchain.storage.write().unwind_to(number, hash);
+        self.forget_activity_after(number);
+
+        // reset the fees to the ones of the block after the common block
+        let header = &common_block.header;
+        let next_block_base_fee = self.fees.get_next_block_base_fee_per_gas(
+            header.gas_used,
+            header.gas_limit,
+            header.base_fee_per_gas.unwrap_or_default(),
+        );
+        let next_block_excess_blob_gas = self.fees.get_next_block_blob_excess_gas(
+            header.excess_blob_gas.unwrap_or_default(),
+            header.blob_gas_used.unwrap_or_default(),
+        );
+        self.fees.set_base_fee(next_block_base_fee);
+        self.fees
+            .set_blob_excess_gas_and_price(BlobExcessGasAndPrice::new(next_block_excess_blob_gas));
+
+        self.time.reset(header.timestamp);
+        let mut env = self.env.write();
+        env.block = BlockEnv {
+            number: U256::from(number),
+            timestamp: U256::from(header.timestamp),
+            difficulty: header.difficulty,
+            // ensures prevrandao is set
+            prevrandao: Some(header.mix_hash),
+            gas_limit: U256::from(header.gas_limit),
+            // Keep previous `coinbase` value
+            coinbase: env.block.coinbase,
+            basefee: U256::from(header.base_fee_per_gas.unwrap_or_default()),
+            blob_excess_gas_and_price: header
+                .excess_blob_gas
+                .map(|excess_blob_gas| BlobExcessGasAndPrice::new(excess_blob_gas as u64)),
+        };
+
+        Ok(())
+    }
+
+    /// Reorgs the last `depth` blocks by rebuilding them from their previously mined
+    /// transactions.
+    ///
+    /// Transactions in `exclude` are dropped from the rebuilt blocks. `inject` adds new
+    /// transactions keyed by the offset of the rebuilt block, where `0` is the first block after
+    /// the common block, at the given position within that block. Injected transactions must
+    /// have a valid nonce against the rewound state, while previously mined transactions that no
+    /// longer line up with their sender's nonce, e.g. because an earlier transaction of the sender
+    /// was excluded, are dropped.
+    ///
+    /// Returns the outcomes of the rebuilt blocks.
+    pub async fn reorg(
+        &self,
+        depth: u64,
+        exclude: &HashSet<TxHash>,
+        mut inject: BTreeMap<u64, Vec<(usize, Arc<PoolTransaction>)>>,
+    ) -> Result<Vec<MinedBlockOutcome>, BlockchainError> {
+        let best_number = self.best_number();
+        let common_number = best_number.checked_sub(depth).ok_or_else(|| {
+            BlockchainError::Message(format!(
+                "cannot reorg {depth} blocks, the current block is {best_number}"
+            ))
+        })?;
+        if let Some(offset) = inject.keys().find(|offset| **offset >= depth) {
+            return Err(BlockchainError::Message(format!(
+                "cannot inject transactions at block offset {offset}, only {depth} blocks are reorged"
+            )))
+        }
+
+        // collect the surviving transactions of the reorged blocks and splice in the injected ones
+        let mut blocks = Vec::with_capacity(depth as usize);
+        for (offset, number) in (common_number + 1..=best_number).enumerate() {
+            let block = self.get_block(number).ok_or(BlockchainError::BlockNotFound)?;
+            let mut transactions = Vec::with_capacity(block.transactions.len());
+            for tx in block.transactions {
+                let pending_transaction = match tx.impersonated_sender {
+                    Some(sender) => PendingTransaction::with_impersonated(tx.transaction, sender),
+                    None => PendingTransaction::new(tx.transaction)?,
+                };
+                if exclude.contains(pending_transaction.hash()) {
+                    continue
+                }
+                let tx = PoolTransaction {
+                    pending_transaction,
+                    requires: vec![],
+                    provides: vec![],
+                    priority: TransactionPriority(0),
+                };
+                transactions.push((false, Arc::new(tx)));
+            }
+            for (position, tx) in inject.remove(&(offset as u64)).unwrap_or_default() {
+                transactions.insert(position.min(transactions.len()), (true, tx));
+            }
+            blocks.push(transactions);
+        }
+
+        // validate the nonces against the state of the common block before touching the chain
+        let mut nonces: HashMap<Address, u64> = HashMap::default();
+        let mut rebuilt = Vec::with_capacity(blocks.len());
+        for transactions in blocks {
+            let mut included = Vec::with_capacity(transactions.len());
+            for (injected, tx) in transactions {
+                let sender = *tx.pending_transaction.sender();
+                let expected = match nonces.get(&sender) {
+                    Some(nonce) => *nonce,
+                    None => self.get_nonce(sender, BlockRequest::Number(common_number)).await?,
+                };
+                let nonce = tx.pending_transaction.nonce();
+                if nonce != expected {
+                    if !injected {
+                        continue
+                    }
+                    return Err(if nonce < expected {
+                        InvalidTransactionError::NonceTooLow
+                    } else {
+                        InvalidTransactionError::NonceTooHigh
+                    }
+                    .into())
+                }
+                nonces.insert(sender, expected + 1);
+                included.push(tx);
+            }
+            rebuilt.push(included);
+        }
+
+        self.rewind(depth).await?;
+
+        let mut outcomes = Vec::with_capacity(rebuilt.len());
+        for transactions in rebuilt {
+            outcomes.push(self.mine_block(transactions).await);
+        }
+        Ok(outcomes)
     }
 
     pub fn list_snapshots(&self) -> BTreeMap<U256, (u64, B256)> {
         self.active_snapshots.lock().clone().into_iter().collect()
     }
 
+    /// Returns the ids of all named snapshots by their name
+    pub fn list_named_snapshots(&self) -> BTreeMap<String, U256> {
+        self.named_snapshots.lock().clone().into_iter().collect()
+    }
+
     /// Get the current state.
     pub async fn serialized_state(&self) -> Result<SerializableState, BlockchainError> {
         let at = self.env.read().block.clone();
         let best_number = self.blockchain.storage.read().best_number;
         let blocks = self.blockchain.storage.read().serialized_blocks();
         let state = self.db.read().await.dump_state(at, best_number, blocks)?;
-        state.ok_or_else(|| {
+        let mut state = state.ok_or_else(|| {
             RpcError::invalid_params("Dumping state not supported with the current configuration")
-                .into()
-        })
+        })?;
+        state.transactions = self.blockchain.storage.read().serialized_transactions();
+        state.cheats = Some(SerializableCheats {
+            impersonated_accounts: self.cheats.impersonated_accounts().into_iter().collect(),
+            auto_impersonate: self.cheats.auto_impersonate_accounts(),
+        });
+        Ok(state)
+    }
+
+    /// Returns the current state in the shape of a genesis `alloc`, e.g. to bootstrap another
+    /// node with it
+    ///
+    /// In forking mode this only includes the accounts that are held in memory, i.e. accounts that
+    /// were already fetched from the forked client or modified since.
+    pub async fn export_alloc(&self) -> Result<BTreeMap<Address, GenesisAccount>, BlockchainError> {
+        let to_genesis_account =
+            |nonce: u64, balance: U256, code: Bytes, storage: BTreeMap<U256, U256>| {
+                let storage: BTreeMap<_, _> = storage
+                    .into_iter()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(|(slot, value)| (B256::from(slot), B256::from(value)))
+                    .collect();
+                GenesisAccount {
+                    nonce: Some(nonce),
+                    balance,
+                    code: (!code.is_empty()).then_some(code),
+                    storage: (!storage.is_empty()).then_some(storage),
+                    private_key: None,
+                }
+            };
+
+        let db = self.db.read().await;
+        let Some(accounts) = db.maybe_as_full_db() else {
+            let at = self.env.read().block.clone();
+            let best_number = self.blockchain.storage.read().best_number;
+            let state =
+                db.dump_state(at, best_number, vec![])?.ok_or(BlockchainError::DataUnavailable)?;
+            return Ok(state
+                .accounts
+                .into_iter()
+                .map(|(address, account)| {
+                    let genesis_account = to_genesis_account(
+                        account.nonce,
+                        account.balance,
+                        account.code,
+                        account.storage,
+                    );
+                    (address, genesis_account)
+                })
+                .collect())
+        };
+
+        accounts
+            .iter()
+            .map(|(address, account)| {
+                let code = match &account.info.code {
+                    Some(code) => code.clone(),
+                    None => db.code_by_hash_ref(account.info.code_hash)?,
+                };
+                let genesis_account = to_genesis_account(
+                    account.info.nonce,
+                    account.info.balance,
+                    code.original_bytes(),
+                    account.storage.iter().map(|(slot, value)| (*slot, *value)).collect(),
+                );
+                Ok((*address, genesis_account))
+            })
+            .collect()
     }
 
     /// Write all chain data to serialized bytes buffer
@@ -775,6 +1999,16 @@ impl Backend {
         }
 
         self.blockchain.storage.write().load_blocks(state.blocks.clone());
+        self.blockchain.storage.write().load_transactions(state.transactions.clone());
+
+        if let Some(cheats) = state.cheats {
+            for addr in cheats.impersonated_accounts {
+                self.impersonate(addr).await?;
+            }
+            if cheats.auto_impersonate {
+                self.auto_impersonate_account(true).await;
+            }
+        }
 
         Ok(true)
     }
@@ -798,6 +2032,135 @@ impl Backend {
         self.load_state(state).await
     }
 
+    /// Re-executes all transactions of the given state's blocks, starting from the genesis state,
+    /// and returns the resulting state root.
+    ///
+    /// Comparing this to the state root after [Self::load_state] verifies that the dumped state is
+    /// consistent with its transaction history. This fails with
+    /// [BlockchainError::DataUnavailable] in forking mode, where the genesis state is stored
+    /// upstream.
+    pub async fn replay_from_dump(
+        &self,
+        state: SerializableState,
+    ) -> Result<B256, BlockchainError> {
+        if self.is_fork() {
+            return Err(BlockchainError::DataUnavailable);
+        }
+
+        let db: AsyncRwLock<Box<dyn Db>> = AsyncRwLock::new(Box::<MemDb>::default());
+        {
+            let mut db = db.write().await;
+            for (account, info) in self.genesis.account_infos() {
+                db.insert_account(account, info);
+            }
+            if !self.node_config.read().await.disable_default_create2_deployer {
+                db.set_code(
+                    DEFAULT_CREATE2_DEPLOYER,
+                    Bytes::from_static(DEFAULT_CREATE2_DEPLOYER_RUNTIME_CODE).0.into(),
+                )?;
+            }
+            self.genesis.apply_genesis_json_alloc(db)?;
+        }
+        let mut db = db.into_inner();
+
+        let mut blocks: Vec<Block> = state.blocks.into_iter().map(Into::into).collect();
+        blocks.sort_by_key(|block| block.header.number);
+
+        let mut env = self.env.read().clone();
+        for block in blocks {
+            db.insert_block_hash(U256::from(block.header.number), block.header.hash_slow());
+            (env.block, env.handler_cfg.spec_id) = self.block_env_for(&block);
+
+            for tx in block.transactions {
+                let pending =
+                    PendingTransaction::with_impersonated(tx.transaction.clone(), tx.recover()?);
+                env.tx = pending.to_revm_tx_env();
+                if env.handler_cfg.is_optimism {
+                    env.tx.optimism.enveloped_tx = Some(alloy_rlp::encode(&tx.transaction).into());
+                }
+
+                let mut evm =
+                    self.new_evm_with_inspector_ref(&*db, env.clone(), Inspector::default());
+                let ResultAndState { state, .. } = evm.transact()?;
+                drop(evm);
+                db.commit(state);
+            }
+        }
+
+        let accounts = db.maybe_as_full_db().ok_or(BlockchainError::DataUnavailable)?;
+        Ok(build_root(trie_accounts(accounts)))
+    }
+
+    /// Writes the state to `dir`, split into numbered gzip files of at most `accounts_per_chunk`
+    /// accounts each, and a [SerializableStateManifest] that lists the chunks and holds the
+    /// remaining chain data.
+    ///
+    /// Accounts are chunked in address order, so the chunk boundaries are deterministic. Returns
+    /// the paths of the written chunks.
+    pub async fn dump_state_chunked(
+        &self,
+        dir: &Path,
+        accounts_per_chunk: usize,
+    ) -> Result<Vec<PathBuf>, BlockchainError> {
+        if accounts_per_chunk == 0 {
+            return Err(BlockchainError::Message("accounts per chunk must be non-zero".to_string()))
+        }
+        let mut state = self.serialized_state().await?;
+        let mut accounts = std::mem::take(&mut state.accounts).into_iter();
+
+        foundry_common::fs::create_dir_all(dir)
+            .map_err(|err| BlockchainError::Message(err.to_string()))?;
+        let mut chunks = Vec::new();
+        let mut paths = Vec::new();
+        loop {
+            let accounts: BTreeMap<_, _> = accounts.by_ref().take(accounts_per_chunk).collect();
+            if accounts.is_empty() {
+                break
+            }
+            let chunk = SerializableState { accounts, ..Default::default() };
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder
+                .write_all(&serde_json::to_vec(&chunk).unwrap_or_default())
+                .map_err(|_| BlockchainError::DataUnavailable)?;
+
+            let name = format!("state-{:05}.json.gz", chunks.len());
+            let path = dir.join(&name);
+            foundry_common::fs::write(&path, encoder.finish().unwrap_or_default())
+                .map_err(|err| BlockchainError::Message(err.to_string()))?;
+            chunks.push(name);
+            paths.push(path);
+        }
+
+        let manifest = SerializableStateManifest { chunks, state };
+        foundry_common::fs::write_json_file(
+            &dir.join(SerializableStateManifest::FILE_NAME),
+            &manifest,
+        )
+        .map_err(|err| BlockchainError::Message(err.to_string()))?;
+        Ok(paths)
+    }
+
+    /// Loads a state from `dir` that was written by [Self::dump_state_chunked].
+    pub async fn load_state_chunked(&self, dir: &Path) -> Result<bool, BlockchainError> {
+        let SerializableStateManifest { chunks, mut state } =
+            foundry_common::fs::read_json_file(&dir.join(SerializableStateManifest::FILE_NAME))
+                .map_err(|err| BlockchainError::Message(err.to_string()))?;
+
+        for chunk in chunks {
+            let buf = foundry_common::fs::read(dir.join(chunk))
+                .map_err(|err| BlockchainError::Message(err.to_string()))?;
+            let mut decoded_data = Vec::new();
+            GzDecoder::new(&buf[..])
+                .read_to_end(&mut decoded_data)
+                .map_err(|_| BlockchainError::FailedToDecodeStateDump)?;
+            let chunk: SerializableState = serde_json::from_slice(&decoded_data)
+                .map_err(|_| BlockchainError::FailedToDecodeStateDump)?;
+            state.accounts.extend(chunk.accounts);
+        }
+
+        self.load_state(state).await
+    }
+
     /// Returns the environment for the next block
     fn next_env(&self) -> EnvWithHandlerCfg {
         let mut env = self.env.read().clone();
@@ -805,6 +2168,14 @@ impl Backend {
         env.block.number = env.block.number.saturating_add(U256::from(1));
         env.block.basefee = U256::from(self.base_fee());
         env.block.timestamp = U256::from(self.time.current_call_timestamp());
+        env.block.gas_limit = U256::from(self.next_gas_limit(env.block.gas_limit.to()));
+        env.handler_cfg.spec_id = self.spec_id_at(env.block.timestamp.to());
+        if let Some(difficulty) = self.block_difficulty() {
+            env.block.difficulty = difficulty;
+        }
+        if let Some(coinbase) = self.scheduled_coinbase(env.block.number) {
+            env.block.coinbase = coinbase;
+        }
         env
     }
 
@@ -820,12 +2191,35 @@ impl Backend {
         I: InspectorExt<WrapDatabaseRef<DB>>,
     {
         let mut evm = new_evm_with_inspector_ref(db, env, inspector);
-        if let Some(factory) = &self.precompile_factory {
-            inject_precompiles(&mut evm, factory.precompiles());
+        let precompiles = self.injected_precompiles();
+        if !precompiles.is_empty() {
+            inject_precompiles(&mut evm, precompiles);
         }
         evm
     }
 
+    /// Returns the precompiles to inject to the EVM: those of the configured
+    /// [PrecompileFactory], followed by the mocked precompiles which take precedence.
+    fn injected_precompiles(&self) -> Vec<(Address, Precompile)> {
+        let mut precompiles =
+            self.precompile_factory.as_ref().map(|f| f.precompiles()).unwrap_or_default();
+        precompiles.extend(
+            self.mocked_precompiles
+                .read()
+                .iter()
+                .map(|(address, handler)| (*address, MockPrecompile(handler.clone()).into())),
+        );
+        precompiles
+    }
+
+    /// Installs `handler` as the precompile at `address`, overriding any existing precompile.
+    ///
+    /// The handler is called with the call input for every call to `address`, which allows
+    /// forcing specific precompile outputs, e.g. a failing pairing check.
+    pub fn mock_precompile(&self, address: Address, handler: MockPrecompileHandler) {
+        self.mocked_precompiles.write().insert(address, handler);
+    }
+
     /// executes the transactions without writing to the underlying database
     pub async fn inspect_tx(
         &self,
@@ -879,6 +2273,19 @@ impl Backend {
     ) -> T
     where
         F: FnOnce(Box<dyn MaybeFullDatabase + '_>, BlockInfo) -> T,
+    {
+        self.with_pending_execution(pool_transactions, |db, executed| f(db, executed.block)).await
+    }
+
+    /// Executes `pool_transactions` on top of the current state like [Self::with_pending_block],
+    /// but passes all results of the execution to `f`
+    async fn with_pending_execution<F, T>(
+        &self,
+        pool_transactions: Vec<Arc<PoolTransaction>>,
+        f: F,
+    ) -> T
+    where
+        F: FnOnce(Box<dyn MaybeFullDatabase + '_>, ExecutedTransactions) -> T,
     {
         let db = self.db.read().await;
         let env = self.next_env();
@@ -897,13 +2304,118 @@ impl Backend {
             parent_hash: storage.best_hash,
             gas_used: 0,
             blob_gas_used: 0,
+            transaction_count: 0,
             enable_steps_tracing: self.enable_steps_tracing,
-            precompile_factory: self.precompile_factory.clone(),
+            keep_reverted_logs: self.keep_reverted_logs(),
+            compute_state_root: self.compute_state_root,
+            max_transactions: self.max_transactions_per_block,
+            precompiles: self.injected_precompiles(),
+            pinned_nonces: self.pinned_nonces.read().clone(),
+            senders_with_code: self.senders_with_code.read().clone(),
+            withdrawals: self.queued_withdrawals.lock().clone(),
+            custom_inspector: self.custom_inspector.read().clone(),
+            accept_underpriced: self.accepts_underpriced(),
+            max_blob_gas_per_block: self.fees.blob_params().max_blob_gas_per_block(),
         };
 
         // create a new pending block
         let executed = executor.execute();
-        f(Box::new(cache_db), executed.block)
+        f(Box::new(cache_db), executed)
+    }
+
+    /// Simulates mining a block with `pool_transactions` without committing it
+    ///
+    /// Returns which transactions would be included and which would be rejected as invalid, with
+    /// the reason. Transactions that don't fit into the block are neither. None of the state is
+    /// modified.
+    pub async fn dry_run_mine(
+        &self,
+        pool_transactions: Vec<Arc<PoolTransaction>>,
+    ) -> Result<DryRunOutcome, BlockchainError> {
+        self.with_pending_execution(pool_transactions, |_, executed| {
+            let ExecutedTransactions { block, included, invalid, .. } = executed;
+            Ok(DryRunOutcome {
+                included: included.iter().map(|tx| tx.hash()).collect(),
+                invalid: invalid.into_iter().map(|(tx, err)| (tx.hash(), err)).collect(),
+                gas_used: block
+                    .block
+                    .header
+                    .gas_used
+                    .try_into()
+                    .map_err(|_| BlockchainError::UintConversion("gas exceeds u64"))?,
+            })
+        })
+        .await
+    }
+
+    /// Creates the pending block like [Self::with_pending_block], but with the transaction in
+    /// `pool_transactions` that has the same sender and nonce as `replacement` swapped for it
+    ///
+    /// If there's no such transaction, `replacement` is appended instead. This can be used to
+    /// preview the effect of replacing a transaction before actually submitting it.
+    pub async fn with_pending_block_replacing<F, T>(
+        &self,
+        mut pool_transactions: Vec<Arc<PoolTransaction>>,
+        replacement: Arc<PoolTransaction>,
+        f: F,
+    ) -> T
+    where
+        F: FnOnce(Box<dyn MaybeFullDatabase + '_>, BlockInfo) -> T,
+    {
+        let sender = *replacement.pending_transaction.sender();
+        let nonce = replacement.pending_transaction.nonce();
+        if let Some(tx) = pool_transactions.iter_mut().find(|tx| {
+            *tx.pending_transaction.sender() == sender && tx.pending_transaction.nonce() == nonce
+        }) {
+            *tx = replacement;
+        } else {
+            pool_transactions.push(replacement);
+        }
+        self.with_pending_block(pool_transactions, f).await
+    }
+
+    /// Returns how much gas is still available in the pending block built from
+    /// `pool_transactions`, i.e. its gas limit minus the gas used by the transactions
+    pub async fn pending_block_headroom(
+        &self,
+        pool_transactions: Vec<Arc<PoolTransaction>>,
+    ) -> Result<u64, BlockchainError> {
+        self.with_pending_block(pool_transactions, |_, info| {
+            let header = &info.block.header;
+            header
+                .gas_limit
+                .saturating_sub(header.gas_used)
+                .try_into()
+                .map_err(|_| BlockchainError::UintConversion("gas headroom exceeds u64"))
+        })
+        .await
+    }
+
+    /// Returns the gas used by the pending block built from `pool_transactions` and which of the
+    /// transactions it includes
+    ///
+    /// Transactions that are invalid at the point of their execution, or that don't fit into the
+    /// block, are not included.
+    pub async fn pending_block_gas_used(
+        &self,
+        pool_transactions: Vec<Arc<PoolTransaction>>,
+    ) -> Result<PendingBlockGasUsage, BlockchainError> {
+        let hashes = pool_transactions.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+        self.with_pending_block(pool_transactions, |_, info| {
+            let header = &info.block.header;
+            let to_u64 = |gas: u128| {
+                u64::try_from(gas).map_err(|_| BlockchainError::UintConversion("gas exceeds u64"))
+            };
+            let included =
+                info.transactions.iter().map(|tx| tx.transaction_hash).collect::<HashSet<_>>();
+            Ok(PendingBlockGasUsage {
+                gas_limit: to_u64(header.gas_limit)?,
+                gas_used: to_u64(header.gas_used)?,
+                blob_gas_used: to_u64(header.blob_gas_used.unwrap_or_default())?,
+                included: hashes.into_iter().map(|hash| (hash, included.contains(&hash))).collect(),
+            })
+        })
+        .await
     }
 
     /// Mines a new block and stores it.
@@ -917,6 +2429,27 @@ impl Backend {
         self.do_mine_block(pool_transactions).await
     }
 
+    /// Mines a single block without any pool transactions and returns its outcome
+    ///
+    /// The backend doesn't own the transaction pool, use [`EthApi::mine_one`] to also include the
+    /// ready transactions of the pool.
+    ///
+    /// [`EthApi::mine_one`]: crate::eth::EthApi::mine_one
+    pub async fn mine_one(&self) -> MinedBlockOutcome {
+        self.do_mine_block(Vec::new()).await
+    }
+
+    /// Mines `count` blocks without any pool transactions and returns their outcomes
+    ///
+    /// The timestamps of the blocks advance with the configured block timestamp interval, if any.
+    pub async fn mine_n(&self, count: u64) -> Vec<MinedBlockOutcome> {
+        let mut outcomes = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            outcomes.push(self.mine_one().await);
+        }
+        outcomes
+    }
+
     async fn do_mine_block(
         &self,
         pool_transactions: Vec<Arc<PoolTransaction>>,
@@ -925,7 +2458,6 @@ impl Backend {
 
         let (outcome, header, block_hash) = {
             let current_base_fee = self.base_fee();
-            let current_excess_blob_gas_and_price = self.excess_blob_gas_and_price();
 
             let mut env = self.env.read().clone();
 
@@ -938,20 +2470,46 @@ impl Backend {
             // increase block number for this block
             env.block.number = env.block.number.saturating_add(U256::from(1));
             env.block.basefee = U256::from(current_base_fee);
-            env.block.blob_excess_gas_and_price = current_excess_blob_gas_and_price;
             env.block.timestamp = U256::from(self.time.next_timestamp());
+            env.block.gas_limit = U256::from(self.next_gas_limit(env.block.gas_limit.to()));
+            // the block's timestamp may activate a scheduled hardfork
+            env.handler_cfg.spec_id = self.spec_id_at(env.block.timestamp.to());
+            self.fees.set_spec_id(env.handler_cfg.spec_id);
+            env.block.blob_excess_gas_and_price = self.excess_blob_gas_and_price();
+            if let Some(difficulty) = self.block_difficulty() {
+                env.block.difficulty = difficulty;
+            }
 
-            // pick a random value for prevrandao
-            env.block.prevrandao = Some(B256::random());
+            // random unless overridden via `set_prevrandao` or deterministic mining
+            env.block.prevrandao = Some(self.next_prevrandao(env.block.number));
+
+            // the configured coinbase is restored after mining, so the schedule can be reset
+            let coinbase = env.block.coinbase;
+            env.block.coinbase = self.scheduled_coinbase(env.block.number).unwrap_or(coinbase);
 
             let best_hash = self.blockchain.storage.read().best_hash;
 
+            let pool_transactions = if self.order_by_effective_gas_price {
+                order_by_effective_gas_price(pool_transactions, current_base_fee)
+            } else {
+                pool_transactions
+            };
+
             if self.prune_state_history_config.is_state_history_supported() {
                 let db = self.db.read().await.current_state();
                 // store current state before executing all transactions
                 self.states.write().insert(best_hash, db);
+                // the state of `best_hash` may differ from the one it was last stored with, e.g.
+                // after reverting to a snapshot
+                self.invalidate_account_trie_cache(best_hash);
             }
 
+            let withdrawals = if env.handler_cfg.spec_id >= SpecId::SHANGHAI {
+                std::mem::take(&mut *self.queued_withdrawals.lock())
+            } else {
+                Vec::new()
+            };
+
             let (executed_tx, block_hash) = {
                 let mut db = self.db.write().await;
                 let executor = TransactionExecutor {
@@ -963,8 +2521,18 @@ impl Backend {
                     parent_hash: best_hash,
                     gas_used: 0,
                     blob_gas_used: 0,
+                    transaction_count: 0,
                     enable_steps_tracing: self.enable_steps_tracing,
-                    precompile_factory: self.precompile_factory.clone(),
+                    keep_reverted_logs: self.keep_reverted_logs(),
+                    compute_state_root: self.compute_state_root,
+                    max_transactions: self.max_transactions_per_block,
+                    precompiles: self.injected_precompiles(),
+                    pinned_nonces: self.pinned_nonces.read().clone(),
+                    senders_with_code: self.senders_with_code.read().clone(),
+                    withdrawals,
+                    custom_inspector: self.custom_inspector.read().clone(),
+                    accept_underpriced: self.accepts_underpriced(),
+                    max_blob_gas_per_block: self.fees.blob_params().max_blob_gas_per_block(),
                 };
                 let executed_tx = executor.execute();
 
@@ -976,7 +2544,13 @@ impl Backend {
             };
 
             // create the new block with the current timestamp
-            let ExecutedTransactions { block, included, invalid } = executed_tx;
+            let ExecutedTransactions {
+                block,
+                included,
+                invalid,
+                mut touched_accounts,
+                withdrawals,
+            } = executed_tx;
             let BlockInfo { block, transactions, receipts } = block;
 
             let mut storage = self.blockchain.storage.write();
@@ -1001,12 +2575,33 @@ impl Backend {
                     storage.total_difficulty.saturating_add(header.difficulty);
             }
 
+            storage.total_size += alloy_rlp::Encodable::length(&block) as u64;
             storage.blocks.insert(block_hash, block);
             storage.hashes.insert(block_number, block_hash);
 
+            // accounts modified since the previous block are attributed to this block
+            touched_accounts.extend(self.pending_touched_accounts.lock().drain());
+            let mut touched_accounts: Vec<_> = touched_accounts.into_iter().collect();
+            touched_accounts.sort_unstable();
+            storage.touched_accounts.insert(block_hash, touched_accounts);
+            if !withdrawals.is_empty() {
+                storage.withdrawals.insert(block_hash, withdrawals);
+            }
+
             node_info!("");
+            let mut account_activity = self.account_activity.lock();
             // insert all transactions
             for (info, receipt) in transactions.into_iter().zip(receipts) {
+                // track the accounts that were active in this block
+                for address in
+                    std::iter::once(info.from).chain(info.to).chain(info.contract_address)
+                {
+                    let blocks = account_activity.entry(address).or_default();
+                    if blocks.last() != Some(&block_number.to::<u64>()) {
+                        blocks.push(block_number.to::<u64>());
+                    }
+                }
+
                 // log some tx info
                 node_info!("    Transaction: {:?}", info.transaction_hash);
                 if let Some(contract) = &info.contract_address {
@@ -1031,20 +2626,28 @@ impl Backend {
                 storage.transactions.insert(mined_tx.info.transaction_hash, mined_tx);
             }
 
+            drop(account_activity);
+
             // remove old transactions that exceed the transaction block keeper
             if let Some(transaction_block_keeper) = self.transaction_block_keeper {
                 if storage.blocks.len() > transaction_block_keeper {
                     let to_clear = block_number
                         .to::<u64>()
                         .saturating_sub(transaction_block_keeper.try_into().unwrap());
-                    storage.remove_block_transactions_by_number(to_clear)
+                    storage.remove_block_transactions_by_number(to_clear);
+                    if self.prune_states_with_transactions {
+                        if let Some(hash) = storage.hashes.get(&U64::from(to_clear)) {
+                            self.states.write().remove(hash);
+                        }
+                    }
                 }
             }
 
-            // we intentionally set the difficulty to `0` for newer blocks
-            env.block.difficulty = U256::from(0);
+            // we intentionally set the difficulty to `0` for newer blocks, unless configured
+            env.block.difficulty = self.block_difficulty().unwrap_or_default();
 
             // update env with new values
+            env.block.coinbase = coinbase;
             *self.env.write() = env;
 
             let timestamp = utc_from_secs(header.timestamp);
@@ -1053,7 +2656,8 @@ impl Backend {
             node_info!("    Block Hash: {:?}", block_hash);
             node_info!("    Block Time: {:?}\n", timestamp.to_rfc2822());
 
-            let outcome = MinedBlockOutcome { block_number, included, invalid };
+            let invalid = invalid.into_iter().map(|(tx, _)| tx).collect();
+            let outcome = MinedBlockOutcome { block_number, block_hash, included, invalid };
 
             (outcome, header, block_hash)
         };
@@ -1072,8 +2676,16 @@ impl Backend {
         self.fees
             .set_blob_excess_gas_and_price(BlobExcessGasAndPrice::new(next_block_excess_blob_gas));
 
+        // end all impersonations that were only valid up until this block
+        self.cheats.on_new_block(outcome.block_number.to::<u64>());
+
         // notify all listeners
-        self.notify_on_new_block(header, block_hash);
+        self.notify_on_new_block(header, block_hash, &outcome);
+
+        let callbacks = self.block_mined_callbacks.lock().clone();
+        for callback in callbacks {
+            callback(&outcome);
+        }
 
         outcome
     }
@@ -1104,6 +2716,77 @@ impl Backend {
         }).await?
     }
 
+    /// Executes the call like [Self::call] and decodes its outcome.
+    ///
+    /// If the call reverted, the revert data is decoded via [RevertDecoder], the raw revert data is
+    /// retained as well.
+    pub async fn call_and_decode(
+        &self,
+        request: WithOtherFields<TransactionRequest>,
+        fee_details: FeeDetails,
+        block_request: Option<BlockRequest>,
+        overrides: Option<StateOverride>,
+    ) -> Result<CallResult, BlockchainError> {
+        let (exit, out, gas_used, _) =
+            self.call(request, fee_details, block_request, overrides).await?;
+        let output = out.map(Output::into_data).unwrap_or_default();
+        let result = match exit {
+            return_ok!() => CallResult::Success { output, gas_used },
+            InstructionResult::Revert => {
+                let reason = RevertDecoder::new().maybe_decode(&output, None);
+                let selector = output.get(..4).map(Selector::from_slice);
+                CallResult::Revert { output, reason, selector, gas_used }
+            }
+            reason => CallResult::Halt { reason, gas_used },
+        };
+        Ok(result)
+    }
+
+    /// Performs an ENS reverse lookup of `address` against the given ENS `registry` at the current
+    /// state.
+    ///
+    /// Returns `None` if the reverse node has no resolver or the lookup reverts.
+    pub async fn resolve_reverse(
+        &self,
+        address: Address,
+        registry: Address,
+    ) -> Result<Option<String>, BlockchainError> {
+        let node = namehash(&reverse_address(&address));
+        self.with_database_at(None, |state, block| {
+            let call = |to: Address, input: Vec<u8>| -> Result<Option<Bytes>, BlockchainError> {
+                let request = WithOtherFields::new(TransactionRequest {
+                    to: Some(TxKind::Call(to)),
+                    input: Bytes::from(input).into(),
+                    ..Default::default()
+                });
+                let (exit, out, _, _) =
+                    self.call_with_state(&state, request, FeeDetails::zero(), block.clone())?;
+                match (exit, out) {
+                    (InstructionResult::Return | InstructionResult::Stop, Some(out)) => {
+                        Ok(Some(out.into_data()))
+                    }
+                    _ => Ok(None),
+                }
+            };
+
+            let Some(out) = call(registry, EnsRegistry::resolverCall { node }.abi_encode())? else {
+                return Ok(None)
+            };
+            let Ok(resolver) = EnsRegistry::resolverCall::abi_decode_returns(&out, true) else {
+                return Ok(None)
+            };
+            if resolver._0 == Address::ZERO {
+                return Ok(None)
+            }
+
+            let Some(out) = call(resolver._0, EnsResolver::nameCall { node }.abi_encode())? else {
+                return Ok(None)
+            };
+            Ok(EnsResolver::nameCall::abi_decode_returns(&out, true).ok().map(|name| name._0))
+        })
+        .await?
+    }
+
     fn build_call_env(
         &self,
         request: WithOtherFields<TransactionRequest>,
@@ -1138,10 +2821,14 @@ impl Backend {
 
         let gas_limit = gas.unwrap_or(block_env.gas_limit.to());
         let mut env = self.env.read().clone();
+        env.handler_cfg.spec_id = self.spec_id_at(block_env.timestamp.to());
         env.block = block_env;
         // we want to disable this in eth_call, since this is common practice used by other node
         // impls and providers <https://github.com/foundry-rs/foundry/issues/4388>
         env.cfg.disable_block_gas_limit = true;
+        if from.is_some_and(|from| self.is_sender_with_code_allowed(from)) {
+            env.cfg.disable_eip3607 = true;
+        }
 
         if let Some(base) = max_fee_per_gas {
             env.block.basefee = U256::from(base);
@@ -1180,6 +2867,23 @@ impl Backend {
         env
     }
 
+    /// Applies the configured call timeout and custom inspector, if any, to the given inspector
+    fn call_inspector(&self, inspector: Inspector) -> Inspector {
+        let inspector = inspector.with_custom_inspector(self.custom_inspector.read().clone());
+        match self.call_timeout {
+            Some(timeout) => inspector.with_deadline(Instant::now() + timeout),
+            None => inspector,
+        }
+    }
+
+    /// Returns an error if the call was halted because it exceeded the configured call timeout
+    fn ensure_call_not_timed_out(&self, inspector: &Inspector) -> Result<(), BlockchainError> {
+        if inspector.timed_out {
+            return Err(BlockchainError::ExecutionTimeout(self.call_timeout.unwrap_or_default()))
+        }
+        Ok(())
+    }
+
     pub fn call_with_state<D>(
         &self,
         state: D,
@@ -1190,11 +2894,13 @@ impl Backend {
     where
         D: DatabaseRef<Error = DatabaseError>,
     {
-        let mut inspector = Inspector::default();
+        let mut inspector = self.call_inspector(Inspector::default());
 
         let env = self.build_call_env(request, fee_details, block_env);
         let mut evm = self.new_evm_with_inspector_ref(state, env, &mut inspector);
         let ResultAndState { result, state } = evm.transact()?;
+        drop(evm);
+        self.ensure_call_not_timed_out(&inspector)?;
         let (exit_reason, gas_used, out) = match result {
             ExecutionResult::Success { reason, gas_used, output, .. } => {
                 (reason.into(), gas_used, Some(output))
@@ -1204,7 +2910,6 @@ impl Backend {
             }
             ExecutionResult::Halt { reason, gas_used } => (reason.into(), gas_used, None),
         };
-        drop(evm);
         inspector.print_logs();
         Ok((exit_reason, out, gas_used as u128, state))
     }
@@ -1215,14 +2920,23 @@ impl Backend {
         fee_details: FeeDetails,
         block_request: Option<BlockRequest>,
         opts: GethDefaultTracingOptions,
+        overrides: Option<StateOverride>,
     ) -> Result<DefaultFrame, BlockchainError> {
-        self.with_database_at(block_request, |state, block| {
-            let mut inspector = Inspector::default().with_steps_tracing();
+        self.with_database_at(block_request, |mut state, block| {
+            // overrides are applied on top of the requested state, for pending requests this is the
+            // state after executing the pending transactions
+            if let Some(overrides) = overrides {
+                state =
+                    Box::new(state::apply_state_override(overrides.into_iter().collect(), state)?);
+            }
+            let mut inspector = self.call_inspector(Inspector::default().with_steps_tracing());
             let block_number = block.number;
 
             let env = self.build_call_env(request, fee_details, block);
             let mut evm = self.new_evm_with_inspector_ref(state, env, &mut inspector);
             let ResultAndState { result, state: _ } = evm.transact()?;
+            drop(evm);
+            self.ensure_call_not_timed_out(&inspector)?;
 
             let (exit_reason, gas_used, out) = match result {
                 ExecutionResult::Success { reason, gas_used, output, .. } => {
@@ -1234,16 +2948,69 @@ impl Backend {
                 ExecutionResult::Halt { reason, gas_used } => (reason.into(), gas_used, None),
             };
 
-            drop(evm);
             let tracer = inspector.tracer.expect("tracer disappeared");
             let return_value = out.as_ref().map(|o| o.data().clone()).unwrap_or_default();
-            let res = tracer.into_geth_builder().geth_traces(gas_used, return_value, opts);
+            let limit = opts.limit.filter(|limit| *limit > 0);
+            let mut res = tracer.into_geth_builder().geth_traces(gas_used, return_value, opts);
+            if let Some(limit) = limit {
+                res.struct_logs.truncate(limit as usize);
+            }
             trace!(target: "backend", ?exit_reason, ?out, %gas_used, %block_number, "trace call");
             Ok(res)
         })
         .await?
     }
 
+    /// Traces the given calls sequentially, each call is executed on top of the state changes of
+    /// the previous calls.
+    ///
+    /// The state and block overrides are applied once before the first call.
+    pub async fn trace_bundle(
+        &self,
+        requests: Vec<WithOtherFields<TransactionRequest>>,
+        opts: GethDebugTracingOptions,
+        block_request: Option<BlockRequest>,
+        overrides: Option<StateOverride>,
+        block_overrides: Option<BlockOverrides>,
+    ) -> Result<Vec<GethTrace>, BlockchainError> {
+        self.with_database_at(block_request, |mut state, mut block| {
+            if let Some(overrides) = overrides {
+                state =
+                    Box::new(state::apply_state_override(overrides.into_iter().collect(), state)?);
+            }
+            if let Some(overrides) = block_overrides {
+                apply_block_overrides(overrides, &mut block);
+            }
+
+            let mut cache_db = CacheDB::new(state);
+            let mut traces = Vec::with_capacity(requests.len());
+            for request in requests {
+                let fee_details = FeeDetails::new(
+                    request.gas_price,
+                    request.max_fee_per_gas,
+                    request.max_priority_fee_per_gas,
+                    request.max_fee_per_blob_gas,
+                )?
+                .or_zero_fees();
+                let mut inspector = self.call_inspector(Inspector::default().with_steps_tracing());
+                let env = self.build_call_env(request, fee_details, block.clone());
+                let mut evm = self.new_evm_with_inspector_ref(&cache_db, env, &mut inspector);
+                let ResultAndState { result, state } = evm.transact()?;
+                drop(evm);
+                self.ensure_call_not_timed_out(&inspector)?;
+                cache_db.commit(state);
+
+                let gas_used = result.gas_used();
+                let output = result.into_output().unwrap_or_default();
+                let nodes =
+                    inspector.tracer.map(|t| t.into_traces().into_nodes()).unwrap_or_default();
+                traces.push(storage::geth_trace(nodes, gas_used, output, opts.clone())?);
+            }
+            Ok(traces)
+        })
+        .await?
+    }
+
     pub fn build_access_list_with_state<D>(
         &self,
         state: D,
@@ -1286,6 +3053,241 @@ impl Backend {
         Ok((exit_reason, out, gas_used, access_list))
     }
 
+    /// Estimates the gas usage of the `request` at the given block, see
+    /// [Self::estimate_gas_with_state]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `block_number` is greater than the current height
+    pub async fn estimate_gas(
+        &self,
+        request: WithOtherFields<TransactionRequest>,
+        fee_details: FeeDetails,
+        block_request: Option<BlockRequest>,
+        overrides: Option<StateOverride>,
+    ) -> Result<u128, BlockchainError> {
+        self.with_database_at(block_request, |mut state, block| {
+            if let Some(overrides) = overrides {
+                state =
+                    Box::new(state::apply_state_override(overrides.into_iter().collect(), state)?);
+            }
+            self.estimate_gas_with_state(&state, request, fee_details, block)
+        })
+        .await?
+    }
+
+    /// Estimates the gas usage of the `request` at the given block and additionally tries to
+    /// tighten the estimate by attaching a generated access list to the request.
+    ///
+    /// Returns the access list alongside the estimate if executing with it requires less gas,
+    /// otherwise the plain estimate and `None`. Requests that already carry an access list are
+    /// estimated as is.
+    pub async fn estimate_gas_with_access_list(
+        &self,
+        request: WithOtherFields<TransactionRequest>,
+        fee_details: FeeDetails,
+        block_request: Option<BlockRequest>,
+        overrides: Option<StateOverride>,
+    ) -> Result<(u128, Option<AccessList>), BlockchainError> {
+        self.with_database_at(block_request, |mut state, block| {
+            if let Some(overrides) = overrides {
+                state = Box::new(state::apply_state_override(overrides.into_iter().collect(), state)?);
+            }
+            let gas = self.estimate_gas_with_state(
+                &state,
+                request.clone(),
+                fee_details.clone(),
+                block.clone(),
+            )?;
+            if request.access_list.is_some() {
+                return Ok((gas, None))
+            }
+
+            let mut access_list_request = request;
+            access_list_request.gas = Some(gas);
+            let (exit, _, _, access_list) = self.build_access_list_with_state(
+                &state,
+                access_list_request.clone(),
+                fee_details.clone(),
+                block.clone(),
+            )?;
+            if !exit.is_ok() {
+                return Ok((gas, None))
+            }
+
+            access_list_request.gas = None;
+            access_list_request.access_list = Some(access_list.clone());
+            match self.estimate_gas_with_state(&state, access_list_request, fee_details, block) {
+                Ok(refined) if refined < gas => {
+                    trace!(target: "backend", "access list lowered gas estimate from {} to {}", gas, refined);
+                    Ok((refined, Some(access_list)))
+                }
+                _ => Ok((gas, None)),
+            }
+        })
+        .await?
+    }
+
+    /// Returns the gas of the `request` at the given block if it's a plain native token transfer,
+    /// which is estimated without executing it, see [Self::transfer_gas_with_state]
+    pub async fn transfer_gas(
+        &self,
+        request: &WithOtherFields<TransactionRequest>,
+        block_request: Option<BlockRequest>,
+        overrides: Option<StateOverride>,
+    ) -> Result<Option<u128>, BlockchainError> {
+        self.with_database_at(block_request, |mut state, _| {
+            if let Some(overrides) = overrides {
+                state =
+                    Box::new(state::apply_state_override(overrides.into_iter().collect(), state)?);
+            }
+            Ok(self.transfer_gas_with_state(&state, request))
+        })
+        .await?
+    }
+
+    /// Returns [MIN_TRANSACTION_GAS] if the `request` is a plain native token transfer to an
+    /// account without code
+    pub fn transfer_gas_with_state<D>(
+        &self,
+        state: D,
+        request: &WithOtherFields<TransactionRequest>,
+    ) -> Option<u128>
+    where
+        D: DatabaseRef<Error = DatabaseError>,
+    {
+        // We assume it's a transfer if we have no input data.
+        let to = request.to.as_ref().and_then(TxKind::to)?;
+
+        // check certain fields to see if the request could be a simple transfer
+        let maybe_transfer = request.input.input().is_none() &&
+            request.access_list.is_none() &&
+            request.blob_versioned_hashes.is_none();
+        if !maybe_transfer {
+            return None
+        }
+
+        let target_code = self.get_code_with_state(&state, *to).ok()?;
+        target_code.as_ref().is_empty().then_some(MIN_TRANSACTION_GAS)
+    }
+
+    /// Estimates the gas usage of the `request` with the state.
+    ///
+    /// This will execute the transaction request and find the best gas limit via binary search.
+    pub fn estimate_gas_with_state<D>(
+        &self,
+        state: D,
+        mut request: WithOtherFields<TransactionRequest>,
+        fee_details: FeeDetails,
+        block_env: BlockEnv,
+    ) -> Result<u128, BlockchainError>
+    where
+        D: DatabaseRef<Error = DatabaseError>,
+    {
+        // If the request is a simple native token transfer we can optimize
+        if let Some(gas) = self.transfer_gas_with_state(&state, &request) {
+            return Ok(gas);
+        }
+
+        // get the highest possible gas limit, either the request's set value or the currently
+        // configured gas limit
+        let mut highest_gas_limit = request.gas.unwrap_or(block_env.gas_limit.to());
+
+        let gas_price = fee_details.gas_price.unwrap_or_default();
+        // If we have non-zero gas price, cap gas limit by sender balance
+        if gas_price > 0 {
+            if let Some(from) = request.from {
+                let mut available_funds = self.get_balance_with_state(&state, from)?;
+                if let Some(value) = request.value {
+                    if value > available_funds {
+                        return Err(InvalidTransactionError::InsufficientFunds.into());
+                    }
+                    // safe: value < available_funds
+                    available_funds -= value;
+                }
+                // amount of gas the sender can afford with the `gas_price`
+                let allowance =
+                    available_funds.checked_div(U256::from(gas_price)).unwrap_or_default();
+                highest_gas_limit = std::cmp::min(highest_gas_limit, allowance.saturating_to());
+            }
+        }
+
+        let mut call_to_estimate = request.clone();
+        call_to_estimate.gas = Some(highest_gas_limit);
+
+        // execute the call without writing to db
+        let ethres =
+            self.call_with_state(&state, call_to_estimate, fee_details.clone(), block_env.clone());
+
+        let gas_used = match ethres.try_into()? {
+            GasEstimationCallResult::Success(gas) => Ok(gas),
+            GasEstimationCallResult::OutOfGas => {
+                Err(InvalidTransactionError::BasicOutOfGas(highest_gas_limit).into())
+            }
+            GasEstimationCallResult::Revert(output) => {
+                if let Some(reason) =
+                    output.as_ref().and_then(|out| RevertDecoder::new().maybe_decode(out, None))
+                {
+                    trace!(target: "backend", "estimation reverted: {}", reason);
+                }
+                Err(InvalidTransactionError::Revert(output).into())
+            }
+            GasEstimationCallResult::EvmError(err) => {
+                warn!(target: "backend", "estimation failed due to {:?}", err);
+                Err(BlockchainError::EvmError(err))
+            }
+        }?;
+
+        // at this point we know the call succeeded but want to find the _best_ (lowest) gas the
+        // transaction succeeds with. we find this by doing a binary search over the
+        // possible range NOTE: this is the gas the transaction used, which is less than the
+        // transaction requires to succeed
+
+        // Get the starting lowest gas needed depending on the transaction kind.
+        let mut lowest_gas_limit = determine_base_gas_by_kind(&request);
+
+        // pick a point that's close to the estimated gas
+        let mut mid_gas_limit =
+            std::cmp::min(gas_used * 3, (highest_gas_limit + lowest_gas_limit) / 2);
+
+        // Binary search for the ideal gas limit
+        while (highest_gas_limit - lowest_gas_limit) > 1 {
+            request.gas = Some(mid_gas_limit);
+            let ethres = self.call_with_state(
+                &state,
+                request.clone(),
+                fee_details.clone(),
+                block_env.clone(),
+            );
+
+            match ethres.try_into()? {
+                GasEstimationCallResult::Success(_) => {
+                    // If the transaction succeeded, we can set a ceiling for the highest gas limit
+                    // at the current midpoint, as spending any more gas would
+                    // make no sense (as the TX would still succeed).
+                    highest_gas_limit = mid_gas_limit;
+                }
+                GasEstimationCallResult::OutOfGas |
+                GasEstimationCallResult::Revert(_) |
+                GasEstimationCallResult::EvmError(_) => {
+                    // If the transaction failed, we can set a floor for the lowest gas limit at the
+                    // current midpoint, as spending any less gas would make no
+                    // sense (as the TX would still revert due to lack of gas).
+                    //
+                    // We don't care about the reason here, as we known that trasaction is correct
+                    // as it succeeded earlier
+                    lowest_gas_limit = mid_gas_limit;
+                }
+            };
+            // new midpoint
+            mid_gas_limit = (highest_gas_limit + lowest_gas_limit) / 2;
+        }
+
+        trace!(target: "backend", "Estimated Gas for call {:?}", highest_gas_limit);
+
+        Ok(highest_gas_limit)
+    }
+
     /// returns all receipts for the given transactions
     fn get_receipts(&self, tx_hashes: impl IntoIterator<Item = TxHash>) -> Vec<TypedReceipt> {
         let storage = self.blockchain.storage.read();
@@ -1395,8 +3397,19 @@ impl Backend {
             }
         }
 
+        // the header's bloom is a superset of all logs in the block, so blocks that don't match it
+        // definitely don't contain any matching logs
+        let address_filter = FilteredParams::address_filter(&filter.address);
+        let topics_filter = FilteredParams::topics_filter(&filter.topics);
+
         for number in from..=to {
             if let Some(block) = self.get_block(number) {
+                let bloom = block.header.logs_bloom;
+                if !FilteredParams::matches_address(bloom, &address_filter) ||
+                    !FilteredParams::matches_topics(bloom, &topics_filter)
+                {
+                    continue
+                }
                 all_logs.extend(self.mined_logs_for_block(filter.clone(), block));
             }
         }
@@ -1424,6 +3437,50 @@ impl Backend {
         }
     }
 
+    /// Returns every distinct event signature (`topic0`) emitted in the given range of blocks
+    /// together with how often it was emitted
+    ///
+    /// Only locally mined blocks are scanned and the range may span at most
+    /// [MAX_EVENT_SIGNATURES_RANGE] blocks.
+    pub fn event_signatures_in_range(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<HashMap<B256, usize>, BlockchainError> {
+        let best = self.best_number();
+        if to > best {
+            return Err(BlockchainError::BlockOutOfRange(best, to))
+        }
+        if from > to || to - from >= MAX_EVENT_SIGNATURES_RANGE {
+            return Err(RpcError::invalid_params(format!(
+                "invalid block range, at most {MAX_EVENT_SIGNATURES_RANGE} blocks can be queried"
+            ))
+            .into())
+        }
+
+        let mut signatures = HashMap::default();
+        let storage = self.blockchain.storage.read();
+        for number in from..=to {
+            let Some(block) =
+                storage.hashes.get(&U64::from(number)).and_then(|hash| storage.blocks.get(hash))
+            else {
+                continue
+            };
+            // blocks without any logs have an empty bloom
+            if block.header.logs_bloom.is_zero() {
+                continue
+            }
+            for tx in &block.transactions {
+                let Some(tx) = storage.transactions.get(&tx.hash()) else { continue };
+                for topic0 in tx.receipt.logs().iter().filter_map(|log| log.topics().first()) {
+                    *signatures.entry(*topic0).or_default() += 1;
+                }
+            }
+        }
+
+        Ok(signatures)
+    }
+
     pub async fn block_by_hash(&self, hash: B256) -> Result<Option<AlloyBlock>, BlockchainError> {
         trace!(target: "backend", "get block by hash {:?}", hash);
         if let tx @ Some(_) = self.mined_block_by_hash(hash) {
@@ -1437,6 +3494,46 @@ impl Backend {
         Ok(None)
     }
 
+    /// Returns the number of uncles of the given block
+    ///
+    /// Blocks mined by anvil never include uncles, only blocks of the forked chain may have uncles.
+    pub async fn uncle_count(&self, id: impl Into<BlockId>) -> Result<u64, BlockchainError> {
+        let block = match id.into() {
+            BlockId::Hash(hash) => self.block_by_hash(hash.block_hash).await?,
+            BlockId::Number(number) => self.block_by_number(number).await?,
+        };
+        let block = block.ok_or(BlockchainError::BlockNotFound)?;
+        Ok(block.uncles.len() as u64)
+    }
+
+    /// Returns the uncle at the given index of the given block
+    ///
+    /// Returns `None` if the block has no uncle at this index and an error if the block doesn't
+    /// exist.
+    pub async fn uncle_by_index(
+        &self,
+        id: impl Into<BlockId>,
+        index: usize,
+    ) -> Result<Option<AlloyBlock>, BlockchainError> {
+        let id = id.into();
+        let number = self.ensure_block_number(Some(id)).await?;
+        if let Some(fork) = self.get_fork() {
+            if fork.predates_fork_inclusive(number) {
+                let uncle = match id {
+                    BlockId::Hash(hash) => {
+                        fork.uncle_by_block_hash_and_index(hash.block_hash, index).await?
+                    }
+                    BlockId::Number(_) => {
+                        fork.uncle_by_block_number_and_index(number, index).await?
+                    }
+                };
+                return Ok(uncle)
+            }
+        }
+        // blocks mined by anvil never include uncles
+        Ok(None)
+    }
+
     pub async fn block_by_hash_full(
         &self,
         hash: B256,
@@ -1491,6 +3588,7 @@ impl Backend {
         number: BlockNumber,
     ) -> Result<Option<AlloyBlock>, BlockchainError> {
         trace!(target: "backend", "get block by number {:?}", number);
+        let number = self.resolve_fork_block_tag(number).await;
         if let tx @ Some(_) = self.mined_block_by_number(number) {
             return Ok(tx);
         }
@@ -1510,6 +3608,7 @@ impl Backend {
         number: BlockNumber,
     ) -> Result<Option<AlloyBlock>, BlockchainError> {
         trace!(target: "backend", "get block by number {:?}", number);
+        let number = self.resolve_fork_block_tag(number).await;
         if let tx @ Some(_) = self.get_full_block(number) {
             return Ok(tx);
         }
@@ -1524,6 +3623,33 @@ impl Backend {
         Ok(None)
     }
 
+    /// Resolves the `Safe` and `Finalized` tags against the forked chain in fork mode
+    ///
+    /// Locally mined blocks are considered safe/finalized based on `slots_in_an_epoch`, but if that
+    /// points to a block that predates the fork, the forked chain's actual safe/finalized block is
+    /// used instead. If the forked chain doesn't know the tag, this falls back to the local
+    /// computation.
+    async fn resolve_fork_block_tag(&self, number: BlockNumber) -> BlockNumber {
+        if !matches!(number, BlockNumber::Safe | BlockNumber::Finalized) {
+            return number
+        }
+        let Some(fork) = self.get_fork() else { return number };
+
+        let local = self.convert_block_number(Some(number));
+        if !fork.predates_fork_inclusive(local) {
+            return number
+        }
+
+        match fork.block_number_by_tag(number).await {
+            Ok(Some(fork_number)) => BlockNumber::Number(fork_number),
+            Ok(None) => number,
+            Err(err) => {
+                warn!(target: "backend", %err, ?number, "failed to resolve tag on forked chain");
+                number
+            }
+        }
+    }
+
     pub fn get_block(&self, id: impl Into<BlockId>) -> Option<Block> {
         let hash = match id.into() {
             BlockId::Hash(hash) => hash.block_hash,
@@ -1561,6 +3687,21 @@ impl Backend {
         self.blockchain.get_block_by_hash(&hash)
     }
 
+    /// Returns the RLP encoded block with the given id, if it's known locally.
+    pub fn raw_block(&self, id: impl Into<BlockId>) -> Option<Bytes> {
+        let block = self.get_block(id)?;
+        Some(alloy_rlp::encode(&block).into())
+    }
+
+    /// Returns the EIP-2718 encoded transaction with the given hash, if it has been mined locally.
+    pub fn raw_transaction(&self, hash: B256) -> Option<Bytes> {
+        let storage = self.blockchain.storage.read();
+        let MinedTransaction { info, block_hash, .. } = storage.transactions.get(&hash)?;
+        let block = storage.blocks.get(block_hash)?;
+        let tx = block.transactions.get(info.transaction_index as usize)?;
+        Some(tx.transaction.encoded_2718().into())
+    }
+
     pub fn mined_block_by_number(&self, number: BlockNumber) -> Option<AlloyBlock> {
         let block = self.get_block(number)?;
         let mut block = self.convert_block(block);
@@ -1582,6 +3723,16 @@ impl Backend {
         let Block { header, transactions, .. } = block;
 
         let hash = header.hash_slow();
+        let withdrawals = header.withdrawals_root.map(|_| {
+            // the pending block is not stored and includes the queued withdrawals
+            self.blockchain
+                .storage
+                .read()
+                .withdrawals
+                .get(&hash)
+                .cloned()
+                .unwrap_or_else(|| self.queued_withdrawals.lock().clone())
+        });
         let Header {
             parent_hash,
             ommers_hash,
@@ -1600,7 +3751,7 @@ impl Backend {
             mix_hash,
             nonce,
             base_fee_per_gas,
-            withdrawals_root: _,
+            withdrawals_root,
             blob_gas_used,
             excess_blob_gas,
             parent_beacon_block_root,
@@ -1626,7 +3777,7 @@ impl Backend {
                 mix_hash: Some(mix_hash),
                 nonce: Some(nonce),
                 base_fee_per_gas,
-                withdrawals_root: None,
+                withdrawals_root,
                 blob_gas_used,
                 excess_blob_gas,
                 parent_beacon_block_root,
@@ -1637,7 +3788,7 @@ impl Backend {
                 transactions.into_iter().map(|tx| tx.hash()).collect(),
             ),
             uncles: vec![],
-            withdrawals: None,
+            withdrawals,
             other: Default::default(),
         }
     }
@@ -1763,6 +3914,12 @@ impl Backend {
                 }
             }
 
+            // the block exists but its state was pruned from the state history
+            if self.get_block(block_number.to::<u64>()).is_some() {
+                warn!(target: "backend", "Historic state for block={} was pruned", block_number);
+                return Err(BlockchainError::HistoricalStatePruned(block_number.to::<u64>()))
+            }
+
             warn!(target: "backend", "Not historic state found for block={}", block_number);
             return Err(BlockchainError::BlockOutOfRange(
                 self.env.read().block.number.to::<u64>(),
@@ -1789,6 +3946,122 @@ impl Backend {
         .await?
     }
 
+    /// Returns all non-zero storage slots of the given account
+    ///
+    /// This requires the full state, so it fails with [BlockchainError::DataUnavailable] in
+    /// forking mode, where the storage is partially stored upstream, or if the state of the block
+    /// is no longer retained.
+    pub async fn account_storage(
+        &self,
+        address: Address,
+        block_request: Option<BlockRequest>,
+    ) -> Result<HashMap<U256, U256>, BlockchainError> {
+        if self.is_fork() {
+            return Err(BlockchainError::DataUnavailable);
+        }
+
+        self.with_database_at(block_request, |block_db, _| {
+            trace!(target: "backend", "get storage for {:?}", address);
+            let db = block_db.maybe_as_full_db().ok_or(BlockchainError::DataUnavailable)?;
+            Ok(db
+                .get(&address)
+                .map(|account| {
+                    account
+                        .storage
+                        .iter()
+                        .filter(|(_, value)| !value.is_zero())
+                        .map(|(slot, value)| (*slot, *value))
+                        .collect()
+                })
+                .unwrap_or_default())
+        })
+        .await?
+    }
+
+    /// Returns up to `max_results` non-zero storage slots of the given account, ordered by the
+    /// keccak hash of their key and starting at the hashed key `start_key`
+    ///
+    /// The returned `next_key` is the hashed key to start the next page at, or `None` if this is
+    /// the last page. An account that doesn't exist has no storage, so its range is empty.
+    ///
+    /// Like [Self::account_storage], this fails with [BlockchainError::DataUnavailable] in forking
+    /// mode.
+    pub async fn storage_range_at(
+        &self,
+        address: Address,
+        start_key: B256,
+        max_results: usize,
+        block_request: Option<BlockRequest>,
+    ) -> Result<StorageRange, BlockchainError> {
+        let storage = self.account_storage(address, block_request).await?;
+
+        let mut slots = storage
+            .into_iter()
+            .map(|(slot, value)| {
+                let key = B256::from(slot);
+                (keccak256(key), StorageRangeEntry { key, value: B256::from(value) })
+            })
+            .filter(|(hashed, _)| *hashed >= start_key)
+            .collect::<Vec<_>>();
+        slots.sort_unstable_by_key(|(hashed, _)| *hashed);
+
+        let next_key = slots.get(max_results).map(|(hashed, _)| *hashed);
+        slots.truncate(max_results);
+
+        Ok(StorageRange { storage: slots.into_iter().collect(), next_key })
+    }
+
+    /// Returns the storage root of the given account
+    ///
+    /// Note: in forking mode this only covers the storage that is known locally, i.e. slots that
+    /// were already fetched from the forked client or modified since.
+    pub async fn storage_root_at(
+        &self,
+        address: Address,
+        block_request: Option<BlockRequest>,
+    ) -> Result<B256, BlockchainError> {
+        self.with_database_at(block_request, |block_db, _| {
+            trace!(target: "backend", "get storage root for {:?}", address);
+            let db = block_db.maybe_as_full_db().ok_or(BlockchainError::DataUnavailable)?;
+            let account = db.get(&address).cloned().unwrap_or_default();
+            Ok(storage_root(&account.storage))
+        })
+        .await?
+    }
+
+    /// Returns the value of the `mapping` entry for `key`, where the mapping is declared at
+    /// `base_slot`
+    ///
+    /// The entry is stored at `keccak256(key ++ base_slot)`.
+    pub async fn mapping_storage_at(
+        &self,
+        address: Address,
+        base_slot: U256,
+        key: B256,
+        block_request: Option<BlockRequest>,
+    ) -> Result<B256, BlockchainError> {
+        self.nested_mapping_storage_at(address, base_slot, &[key], block_request).await
+    }
+
+    /// Returns the value of a nested `mapping` entry, e.g. `allowance[owner][spender]`, where the
+    /// outermost mapping is declared at `base_slot` and `keys` are ordered from the outermost to
+    /// the innermost mapping
+    pub async fn nested_mapping_storage_at(
+        &self,
+        address: Address,
+        base_slot: U256,
+        keys: &[B256],
+        block_request: Option<BlockRequest>,
+    ) -> Result<B256, BlockchainError> {
+        let slot = keys.iter().fold(base_slot, |slot, key| {
+            let mut preimage = [0u8; 64];
+            preimage[..32].copy_from_slice(key.as_slice());
+            preimage[32..].copy_from_slice(&slot.to_be_bytes::<32>());
+            keccak256(preimage).into()
+        });
+        self.storage_at(address, slot, block_request).await
+    }
+
     /// Returns the code of the address
     ///
     /// If the code is not present and fork mode is enabled then this will try to fetch it from the
@@ -1801,6 +4074,20 @@ impl Backend {
         self.with_database_at(block_request, |db, _| self.get_code_with_state(db, address)).await?
     }
 
+    /// Returns the account the given account delegates its code to, if its code is an EIP-7702
+    /// delegation designator, i.e. `0xef0100 ++ address`
+    pub async fn get_delegation(
+        &self,
+        address: Address,
+        block_request: Option<BlockRequest>,
+    ) -> Result<Option<Address>, BlockchainError> {
+        let code = self.get_code(address, block_request).await?;
+        Ok(code
+            .strip_prefix(&EIP7702_DELEGATION_DESIGNATOR)
+            .filter(|target| target.len() == Address::len_bytes())
+            .map(Address::from_slice))
+    }
+
     pub fn get_code_with_state<D>(
         &self,
         state: D,
@@ -1901,6 +4188,46 @@ impl Backend {
         self.blockchain.storage.read().transactions.get(&hash).cloned()
     }
 
+    /// Returns the gas refund the mined transaction with the given hash accrued
+    ///
+    /// This is the refund before it's capped, the refund deducted from the receipt's `gas_used` is
+    /// at most half, post-London a fifth, of the gas used before the refund, see EIP-3529.
+    pub fn transaction_gas_refund(&self, hash: B256) -> Option<u64> {
+        self.blockchain.storage.read().transactions.get(&hash).map(|tx| tx.info.gas_refunded)
+    }
+
+    /// Returns the blob sidecar of the mined EIP-4844 transaction with the given hash.
+    ///
+    /// The mined transactions are part of the dumped state, so this is also available for blocks
+    /// that were restored via [Self::load_state].
+    pub fn get_blob_sidecar_by_tx_hash(&self, hash: B256) -> Option<BlobTransactionSidecar> {
+        let storage = self.blockchain.storage.read();
+        let MinedTransaction { info, block_hash, .. } = storage.transactions.get(&hash)?;
+        let block = storage.blocks.get(block_hash)?;
+        let tx = block.transactions.get(info.transaction_index as usize)?;
+        let TypedTransaction::EIP4844(tx) = &tx.transaction else { return None };
+        let TxEip4844Variant::TxEip4844WithSidecar(tx) = tx.tx() else { return None };
+        Some(tx.sidecar.clone())
+    }
+
+    /// Returns the blobs of the mined EIP-4844 transaction with the given hash.
+    pub fn get_blob_by_tx_hash(&self, hash: B256) -> Option<Vec<Blob>> {
+        self.get_blob_sidecar_by_tx_hash(hash).map(|sidecar| sidecar.blobs)
+    }
+
+    /// Returns the blob with the given versioned hash from any mined EIP-4844 transaction.
+    pub fn get_blob_by_versioned_hash(&self, hash: B256) -> Option<Blob> {
+        let storage = self.blockchain.storage.read();
+        for tx in storage.blocks.values().flat_map(|block| block.transactions.iter()) {
+            let TypedTransaction::EIP4844(tx) = &tx.transaction else { continue };
+            let TxEip4844Variant::TxEip4844WithSidecar(tx) = tx.tx() else { continue };
+            if let Some(idx) = tx.sidecar.versioned_hashes().position(|h| h == hash) {
+                return tx.sidecar.blobs.get(idx).copied()
+            }
+        }
+        None
+    }
+
     /// Returns the traces for the given block
     pub(crate) fn mined_parity_trace_block(
         &self,
@@ -1940,6 +4267,263 @@ impl Backend {
         self.blockchain.storage.read().transactions.get(&hash).map(|tx| tx.geth_trace(opts))
     }
 
+    /// Re-executes the mined transaction with the given hash against its exact pre-state and
+    /// returns its trace.
+    ///
+    /// The pre-state is derived from the state of the parent block by replaying all transactions
+    /// that precede the transaction in its block.
+    pub async fn replay_transaction(
+        &self,
+        hash: B256,
+        opts: GethDebugTracingOptions,
+    ) -> Result<GethTrace, BlockchainError> {
+        let mut mined = self.mined_transaction(hash).ok_or(BlockchainError::DataUnavailable)?;
+        let block = self.get_block(mined.block_hash).ok_or(BlockchainError::BlockNotFound)?;
+
+        let (_, traces, out) = self
+            .replay_block_transactions(block, Some(hash))
+            .await?
+            .pop()
+            .ok_or(BlockchainError::DataUnavailable)?;
+        mined.info.traces = traces;
+        mined.info.out = out;
+        mined.geth_trace(opts)
+    }
+
+    /// Returns by how much the sender's balance decreased through the mined transaction with the
+    /// given hash, i.e. the value sent plus the gas paid, minus anything the sender received.
+    ///
+    /// The transaction is re-executed against its exact pre-state, see
+    /// [Self::replay_transaction]. The change is negative if the sender gained funds, e.g. through
+    /// the mint of a deposit transaction.
+    pub async fn sender_balance_change(&self, hash: B256) -> Result<I256, BlockchainError> {
+        let mined = self.mined_transaction(hash).ok_or(BlockchainError::DataUnavailable)?;
+        let block = self.get_block(mined.block_hash).ok_or(BlockchainError::BlockNotFound)?;
+        let parent = block.header.number.checked_sub(1).ok_or(BlockchainError::DataUnavailable)?;
+
+        let mut env = self.env.read().clone();
+        (env.block, env.handler_cfg.spec_id) = self.block_env_for(&block);
+
+        let (before, after) = self
+            .with_database_at(Some(BlockRequest::Number(parent)), |state, _| {
+                let mut cache_db = CacheDB::new(state);
+                for tx in block.transactions {
+                    let pending = PendingTransaction::with_impersonated(
+                        tx.transaction.clone(),
+                        tx.recover()?,
+                    );
+                    env.tx = pending.to_revm_tx_env();
+                    if env.handler_cfg.is_optimism {
+                        env.tx.optimism.enveloped_tx =
+                            Some(alloy_rlp::encode(&tx.transaction).into());
+                    }
+
+                    let sender = *pending.sender();
+                    let before = cache_db.basic_ref(sender)?.unwrap_or_default().balance;
+                    let mut evm = self.new_evm_with_inspector_ref(
+                        &cache_db,
+                        env.clone(),
+                        Inspector::default(),
+                    );
+                    let ResultAndState { state, .. } = evm.transact()?;
+                    drop(evm);
+
+                    if tx.hash() == hash {
+                        let after =
+                            state.get(&sender).map_or(before, |account| account.info.balance);
+                        return Ok((before, after))
+                    }
+                    cache_db.commit(state);
+                }
+                Err(BlockchainError::DataUnavailable)
+            })
+            .await??;
+
+        let to_signed = |balance: U256| {
+            I256::try_from(balance).map_err(|_| BlockchainError::UintConversion("balance overflow"))
+        };
+        Ok(to_signed(before)? - to_signed(after)?)
+    }
+
+    /// Returns the geth traces of all transactions in the given block.
+    ///
+    /// Each transaction is re-executed on top of the state left by the previous transactions of
+    /// the block. Blocks that predate the fork are traced by the forked client.
+    pub async fn debug_trace_block(
+        &self,
+        block_id: BlockId,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>, BlockchainError> {
+        let block = self.get_block(block_id);
+        if let Some(fork) = self.get_fork() {
+            let number = match &block {
+                Some(block) => Some(block.header.number),
+                None => match block_id {
+                    BlockId::Number(number) => Some(self.convert_block_number(Some(number))),
+                    BlockId::Hash(_) => None,
+                },
+            };
+            if number.map_or(true, |number| fork.predates_fork_inclusive(number)) {
+                return Ok(fork.debug_trace_block(block_id, opts).await?)
+            }
+        }
+
+        let block = block.ok_or(BlockchainError::BlockNotFound)?;
+        let replayed = self.replay_block_transactions(block, None).await?;
+
+        let storage = self.blockchain.storage.read();
+        replayed
+            .into_iter()
+            .map(|(hash, traces, out)| {
+                let mut mined = storage
+                    .transactions
+                    .get(&hash)
+                    .cloned()
+                    .ok_or(BlockchainError::DataUnavailable)?;
+                mined.info.traces = traces;
+                mined.info.out = out;
+                Ok(match mined.geth_trace(opts.clone()) {
+                    Ok(result) => TraceResult::Success { result, tx_hash: Some(hash) },
+                    Err(err) => TraceResult::Error { error: err.to_string(), tx_hash: Some(hash) },
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the hashes of all transactions mined in `from..=to` that read or wrote the given
+    /// storage `slot` of `address`, in the order they were mined
+    ///
+    /// The transactions are re-executed on top of their parent block's state, so this fails if the
+    /// state of any block in the range is no longer retained. The range may span at most
+    /// [MAX_SLOT_HISTORY_RANGE] blocks.
+    pub async fn transactions_touching_slot(
+        &self,
+        address: Address,
+        slot: U256,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<B256>, BlockchainError> {
+        let best = self.best_number();
+        if to > best {
+            return Err(BlockchainError::BlockOutOfRange(best, to))
+        }
+        if from > to || to - from >= MAX_SLOT_HISTORY_RANGE {
+            return Err(RpcError::invalid_params(format!(
+                "invalid block range, at most {MAX_SLOT_HISTORY_RANGE} blocks can be queried"
+            ))
+            .into())
+        }
+
+        let slot = B256::from(slot);
+        let mut hashes = vec![];
+        for number in from..=to {
+            let block = self.get_block(number).ok_or(BlockchainError::BlockNotFound)?;
+            if block.transactions.is_empty() {
+                continue
+            }
+            let parent = number.checked_sub(1).ok_or(BlockchainError::DataUnavailable)?;
+
+            let mut env = self.env.read().clone();
+            (env.block, env.handler_cfg.spec_id) = self.block_env_for(&block);
+
+            let touching = self
+                .with_database_at(Some(BlockRequest::Number(parent)), |state, _| {
+                    let mut cache_db = CacheDB::new(state);
+                    let mut touching = vec![];
+                    for tx in block.transactions {
+                        let pending = PendingTransaction::with_impersonated(
+                            tx.transaction.clone(),
+                            tx.recover()?,
+                        );
+                        env.tx = pending.to_revm_tx_env();
+                        if env.handler_cfg.is_optimism {
+                            env.tx.optimism.enveloped_tx =
+                                Some(alloy_rlp::encode(&tx.transaction).into());
+                        }
+
+                        let mut inspector = AccessListInspector::new(
+                            Default::default(),
+                            *pending.sender(),
+                            tx.to().unwrap_or_default(),
+                            [],
+                        );
+                        let mut evm =
+                            self.new_evm_with_inspector_ref(&cache_db, env.clone(), &mut inspector);
+                        let ResultAndState { state, .. } = evm.transact()?;
+                        drop(evm);
+                        cache_db.commit(state);
+
+                        if inspector.access_list().0.iter().any(|item| {
+                            item.address == address && item.storage_keys.contains(&slot)
+                        }) {
+                            touching.push(tx.hash());
+                        }
+                    }
+                    Ok::<_, BlockchainError>(touching)
+                })
+                .await??;
+            hashes.extend(touching);
+        }
+        Ok(hashes)
+    }
+
+    /// Re-executes the transactions of the given block on top of the state of its parent block.
+    ///
+    /// If `until` is set, only that transaction is traced and execution stops after it, otherwise
+    /// all transactions are traced. Returns the hash, call traces and output of every traced
+    /// transaction.
+    async fn replay_block_transactions(
+        &self,
+        block: Block,
+        until: Option<B256>,
+    ) -> Result<Vec<(B256, Vec<CallTraceNode>, Option<Bytes>)>, BlockchainError> {
+        if block.transactions.is_empty() {
+            return Ok(vec![])
+        }
+        let parent = block.header.number.checked_sub(1).ok_or(BlockchainError::DataUnavailable)?;
+
+        let mut env = self.env.read().clone();
+        (env.block, env.handler_cfg.spec_id) = self.block_env_for(&block);
+
+        self.with_database_at(Some(BlockRequest::Number(parent)), |state, _| {
+            let mut cache_db = CacheDB::new(state);
+            let mut traced = vec![];
+            for tx in block.transactions {
+                let hash = tx.hash();
+                let pending =
+                    PendingTransaction::with_impersonated(tx.transaction.clone(), tx.recover()?);
+                env.tx = pending.to_revm_tx_env();
+                if env.handler_cfg.is_optimism {
+                    env.tx.optimism.enveloped_tx = Some(alloy_rlp::encode(&tx.transaction).into());
+                }
+
+                let mut inspector = Inspector::default();
+                if until.map_or(true, |until| until == hash) {
+                    inspector = inspector.with_steps_tracing();
+                }
+                let mut evm =
+                    self.new_evm_with_inspector_ref(&cache_db, env.clone(), &mut inspector);
+                let ResultAndState { result, state } = evm.transact()?;
+                drop(evm);
+                cache_db.commit(state);
+
+                if let Some(tracer) = inspector.tracer {
+                    let out = match result {
+                        ExecutionResult::Success { output, .. } => Some(output.into_data()),
+                        ExecutionResult::Revert { output, .. } => Some(output),
+                        ExecutionResult::Halt { .. } => None,
+                    };
+                    traced.push((hash, tracer.into_traces().into_nodes(), out));
+                }
+                if until == Some(hash) {
+                    break
+                }
+            }
+            Ok(traced)
+        })
+        .await?
+    }
+
     /// Returns the traces for the given block
     pub async fn trace_block(
         &self,
@@ -1994,32 +4578,235 @@ impl Backend {
     }
 
     /// Returns all transaction receipts of the block
+    ///
+    /// The transactions are read from storage once and the log indices are accumulated while
+    /// building the receipts in order.
     pub fn mined_block_receipts(&self, id: impl Into<BlockId>) -> Option<Vec<ReceiptResponse>> {
-        let mut receipts = Vec::new();
         let block = self.get_block(id)?;
+        let transactions = {
+            let storage = self.blockchain.storage.read();
+            block
+                .transactions
+                .iter()
+                .map(|tx| storage.transactions.get(&tx.hash()).cloned())
+                .collect::<Option<Vec<_>>>()?
+        };
 
-        for transaction in block.transactions {
-            let receipt = self.mined_transaction_receipt(transaction.hash())?;
-            receipts.push(receipt.inner);
+        let mut receipts = Vec::with_capacity(transactions.len());
+        let mut next_log_index = 0;
+        for tx in transactions {
+            let num_logs = tx.receipt.logs().len();
+            receipts.push(self.build_transaction_receipt(&block, tx, next_log_index).inner);
+            next_log_index += num_logs;
         }
 
         Some(receipts)
     }
 
+    /// Returns the gas-weighted average of the priority fees the transactions of the block
+    /// actually paid
+    ///
+    /// The priority fee of a transaction is its `effective_gas_price - base_fee`, which is weighted
+    /// by the gas the transaction used. Deposit transactions are excluded.
+    pub fn block_avg_priority_fee(&self, block: BlockNumber) -> Result<u128, BlockchainError> {
+        let block = self.get_block(block).ok_or(BlockchainError::BlockNotFound)?;
+        let base_fee = block.header.base_fee_per_gas.unwrap_or_default();
+
+        let mut total_gas_used = U256::ZERO;
+        let mut weighted_priority_fees = U256::ZERO;
+        for tx in block
+            .transactions
+            .iter()
+            .filter(|tx| !matches!(tx.transaction, TypedTransaction::Deposit(_)))
+        {
+            let receipt = self
+                .mined_transaction_receipt(tx.hash())
+                .ok_or(BlockchainError::DataUnavailable)?;
+            let gas_used = U256::from(receipt.inner.gas_used);
+            let priority_fee = receipt.inner.effective_gas_price.saturating_sub(base_fee);
+            total_gas_used += gas_used;
+            weighted_priority_fees += gas_used * U256::from(priority_fee);
+        }
+
+        if total_gas_used.is_zero() {
+            return Ok(0)
+        }
+        Ok((weighted_priority_fees / total_gas_used).saturating_to())
+    }
+
+    /// Returns the total priority fees the transactions of the block paid to its beneficiary, or
+    /// `None` if the block is unknown
+    ///
+    /// This is the sum of `(effective_gas_price - base_fee) * gas_used` over all transactions of
+    /// the block, excluding deposit transactions.
+    pub fn block_priority_fees(&self, id: impl Into<BlockId>) -> Option<U256> {
+        let block = self.get_block(id)?;
+        let base_fee = block.header.base_fee_per_gas.unwrap_or_default();
+
+        block
+            .transactions
+            .iter()
+            .filter(|tx| !matches!(tx.transaction, TypedTransaction::Deposit(_)))
+            .map(|tx| {
+                let receipt = self.mined_transaction_receipt(tx.hash())?;
+                let priority_fee = receipt.inner.effective_gas_price.saturating_sub(base_fee);
+                Some(U256::from(receipt.inner.gas_used) * U256::from(priority_fee))
+            })
+            .sum()
+    }
+
+    /// Computes the `eth_feeHistory` of up to `block_count` stored blocks ending at `newest_block`
+    ///
+    /// The rewards are the effective priority fees at the given `reward_percentiles` of each
+    /// block's gas used, as specified by `eth_feeHistory`. The blob fee fields are only filled
+    /// post Cancun.
+    pub fn fee_history(
+        &self,
+        block_count: u64,
+        newest_block: BlockNumber,
+        reward_percentiles: &[f64],
+    ) -> Result<FeeHistory, BlockchainError> {
+        const MAX_BLOCK_COUNT: u64 = 1024u64;
+
+        if reward_percentiles.iter().any(|p| !(0.0..=100.0).contains(p)) ||
+            reward_percentiles.windows(2).any(|w| w[0] > w[1])
+        {
+            return Err(FeeHistoryError::InvalidRewardPercentiles.into());
+        }
+
+        let best = self.best_number();
+        let highest = self.convert_block_number(Some(newest_block));
+        if highest > best {
+            return Err(BlockchainError::BlockOutOfRange(best, highest));
+        }
+        let block_count = block_count.clamp(1, MAX_BLOCK_COUNT);
+        let lowest = highest.saturating_sub(block_count - 1);
+
+        let is_cancun = self.is_eip4844();
+        let blob_gas_price = |header: &Header| {
+            self.fees.calc_blob_gasprice(header.excess_blob_gas.unwrap_or_default() as u64)
+        };
+
+        let mut history = FeeHistory { oldest_block: lowest, ..Default::default() };
+        let mut rewards = Vec::new();
+        for number in lowest..=highest {
+            let block = self
+                .get_block(BlockNumber::Number(number))
+                .ok_or(FeeHistoryError::BlockNotFound(BlockNumber::Number(number)))?;
+            let header = &block.header;
+            let base_fee = header.base_fee_per_gas.unwrap_or_default();
+
+            history.base_fee_per_gas.push(base_fee);
+            history.gas_used_ratio.push(if header.gas_limit == 0 {
+                0f64
+            } else {
+                header.gas_used as f64 / header.gas_limit as f64
+            });
+            if is_cancun {
+                history.base_fee_per_blob_gas.push(blob_gas_price(header));
+                history.blob_gas_used_ratio.push(
+                    header.blob_gas_used.unwrap_or_default() as f64 /
+                        self.fees.blob_params().max_blob_gas_per_block() as f64,
+                );
+            }
+
+            if reward_percentiles.is_empty() {
+                continue
+            }
+
+            // (gas used, effective priority fee) of every transaction, sorted by the fee
+            let mut transactions = block
+                .transactions
+                .iter()
+                .map(|tx| {
+                    let receipt = self
+                        .mined_transaction_receipt(tx.hash())
+                        .ok_or(BlockchainError::DataUnavailable)?;
+                    Ok((
+                        receipt.inner.gas_used,
+                        receipt.inner.effective_gas_price.saturating_sub(base_fee),
+                    ))
+                })
+                .collect::<Result<Vec<_>, BlockchainError>>()?;
+            transactions.sort_by_key(|(_, reward)| *reward);
+
+            let block_rewards = reward_percentiles
+                .iter()
+                .map(|p| {
+                    let target_gas = (p / 100f64 * header.gas_used as f64) as u128;
+                    let mut sum_gas = 0;
+                    transactions
+                        .iter()
+                        .find(|(gas_used, _)| {
+                            sum_gas += gas_used;
+                            target_gas <= sum_gas
+                        })
+                        .or(transactions.last())
+                        .map_or(0, |(_, reward)| *reward)
+                })
+                .collect();
+            rewards.push(block_rewards);
+        }
+        history.reward = Some(rewards);
+
+        // the base fees include the block after the newest block of the range
+        match self.get_block(BlockNumber::Number(highest + 1)) {
+            Some(next) => {
+                history.base_fee_per_gas.push(next.header.base_fee_per_gas.unwrap_or_default());
+                if is_cancun {
+                    history.base_fee_per_blob_gas.push(blob_gas_price(&next.header));
+                }
+            }
+            None => {
+                history.base_fee_per_gas.push(self.fees.base_fee());
+                if is_cancun {
+                    history.base_fee_per_blob_gas.push(self.fees.base_fee_per_blob_gas());
+                }
+            }
+        }
+
+        Ok(history)
+    }
+
     /// Returns the transaction receipt for the given hash
     pub(crate) fn mined_transaction_receipt(&self, hash: B256) -> Option<MinedTransactionReceipt> {
-        let MinedTransaction { info, receipt: tx_receipt, block_hash, .. } =
-            self.blockchain.get_transaction_by_hash(&hash)?;
+        let tx = self.blockchain.get_transaction_by_hash(&hash)?;
+        let block = self.blockchain.get_block_by_hash(&tx.block_hash)?;
+
+        let index = tx.info.transaction_index as usize;
+        let receipts = self.get_receipts(block.transactions[..index].iter().map(|tx| tx.hash()));
+        let next_log_index = receipts.iter().map(|r| r.logs().len()).sum::<usize>();
+
+        Some(self.build_transaction_receipt(&block, tx, next_log_index))
+    }
+
+    /// Builds the receipt of the given transaction mined in `block`, whose logs start at
+    /// `next_log_index` within the block
+    fn build_transaction_receipt(
+        &self,
+        block: &Block,
+        tx: MinedTransaction,
+        next_log_index: usize,
+    ) -> MinedTransactionReceipt {
+        let MinedTransaction { info, receipt: tx_receipt, block_hash, .. } = tx;
 
         let index = info.transaction_index as usize;
-        let block = self.blockchain.get_block_by_hash(&block_hash)?;
         let transaction = block.transactions[index].clone();
 
         // Cancun specific
         let excess_blob_gas = block.header.excess_blob_gas;
-        let blob_gas_price = calc_blob_gasprice(excess_blob_gas.map_or(0, |g| g as u64));
+        let blob_gas_price = self.fees.calc_blob_gasprice(excess_blob_gas.map_or(0, |g| g as u64));
         let blob_gas_used = transaction.blob_gas();
 
+        // op-stack specific, the L1 data fee of non-deposit transactions
+        let l1_fee_fields = if self.is_optimism() &&
+            !matches!(transaction.transaction, TypedTransaction::Deposit(_))
+        {
+            self.l1_fee_fields(block, &transaction.transaction)
+        } else {
+            None
+        };
+
         let effective_gas_price = match transaction.transaction {
             TypedTransaction::Legacy(t) => t.tx().gas_price,
             TypedTransaction::EIP2930(t) => t.tx().gas_price,
@@ -2036,9 +4823,6 @@ impl Backend {
             TypedTransaction::Deposit(_) => 0_u128,
         };
 
-        let receipts = self.get_receipts(block.transactions.iter().map(|tx| tx.hash()));
-        let next_log_index = receipts[..index].iter().map(|r| r.logs().len()).sum::<usize>();
-
         let receipt = tx_receipt.as_receipt_with_bloom().receipt.clone();
         let receipt = Receipt {
             status: receipt.status,
@@ -2090,7 +4874,62 @@ impl Backend {
             blob_gas_used,
         };
 
-        Some(MinedTransactionReceipt { inner, out: info.out.map(|o| o.0.into()) })
+        let mut inner = WithOtherFields::new(inner);
+        if !info.pre_revert_logs.is_empty() {
+            // non-standard, these logs were discarded by the revert and are not part of the receipt
+            let pre_revert_logs = info
+                .pre_revert_logs
+                .into_iter()
+                .map(|log| alloy_rpc_types::Log {
+                    inner: log,
+                    block_hash: Some(block_hash),
+                    block_number: Some(block.header.number),
+                    block_timestamp: Some(block.header.timestamp),
+                    transaction_hash: Some(info.transaction_hash),
+                    transaction_index: Some(info.transaction_index),
+                    log_index: None,
+                    removed: false,
+                })
+                .collect::<Vec<_>>();
+            inner.other.insert(
+                "preRevertLogs".to_string(),
+                serde_json::to_value(pre_revert_logs).expect("Infallible"),
+            );
+        }
+
+        if let Some(fields) = l1_fee_fields {
+            inner.other.extend(fields);
+        }
+
+        MinedTransactionReceipt { inner, out: info.out.map(|o| o.0.into()) }
+    }
+
+    /// Returns the op-stack L1 fee receipt fields of the given transaction mined in `block`.
+    ///
+    /// The fee parameters are read from the `L1Block` predeploy in the state of the block's parent,
+    /// under the block's hardfork, the same way they are read when the transaction is executed.
+    /// Returns `None` if the parent's state is no longer retained, e.g. because it was pruned
+    /// per the configured state history, in which case the receipt omits these fields.
+    fn l1_fee_fields(&self, block: &Block, transaction: &TypedTransaction) -> Option<OtherFields> {
+        let spec_id = self.spec_id_at(block.header.timestamp);
+        let mut states = self.states.write();
+        let state = states.get(&block.header.parent_hash)?;
+        let l1_block_info = L1BlockInfo::try_fetch(&mut WrapDatabaseRef(state), spec_id).ok()?;
+
+        let enveloped_tx = alloy_rlp::encode(transaction);
+        let l1_fee = l1_block_info.calculate_tx_l1_cost(&enveloped_tx, spec_id);
+        let l1_gas_used = l1_block_info.data_gas(&enveloped_tx, spec_id);
+        let l1_fee_scalar = l1_block_info.l1_base_fee_scalar.saturating_to::<u64>() as f64 / 1e6;
+
+        Some(OtherFields::from_iter([
+            ("l1Fee".to_string(), serde_json::to_value(l1_fee).expect("Infallible")),
+            ("l1GasUsed".to_string(), serde_json::to_value(l1_gas_used).expect("Infallible")),
+            (
+                "l1GasPrice".to_string(),
+                serde_json::to_value(l1_block_info.l1_base_fee).expect("Infallible"),
+            ),
+            ("l1FeeScalar".to_string(), l1_fee_scalar.to_string().into()),
+        ]))
     }
 
     /// Returns the blocks receipts for the given number
@@ -2208,9 +5047,45 @@ impl Backend {
         ))
     }
 
+    /// Computes the root of a secure trie over the given entries.
+    ///
+    /// Keys are hashed with keccak before insertion, like account and storage keys are, and the
+    /// values are inserted as given, so they must already be RLP encoded. If a key appears more
+    /// than once the last value wins.
+    pub fn compute_trie_root(&self, entries: Vec<(B256, Bytes)>) -> B256 {
+        let leaves = entries
+            .into_iter()
+            .map(|(key, value)| (Nibbles::unpack(keccak256(key)), value.to_vec()))
+            .collect::<BTreeMap<_, _>>();
+        build_root(leaves)
+    }
+
+    /// Computes the root of the account trie at the given block.
+    ///
+    /// This requires the full state, so it fails with [BlockchainError::DataUnavailable] in
+    /// forking mode, where the state is partially stored upstream, or if the state of the block
+    /// is no longer retained.
+    pub async fn state_root_at(
+        &self,
+        block_request: Option<BlockRequest>,
+    ) -> Result<B256, BlockchainError> {
+        if self.is_fork() {
+            return Err(BlockchainError::DataUnavailable);
+        }
+
+        self.with_database_at(block_request, |block_db, _| {
+            let db = block_db.maybe_as_full_db().ok_or(BlockchainError::DataUnavailable)?;
+            Ok(build_root(trie_accounts(db)))
+        })
+        .await?
+    }
+
     /// Prove an account's existence or nonexistence in the state trie.
     ///
     /// Returns a merkle proof of the account's trie node, `account_key` == keccak(address)
+    ///
+    /// The account trie leaves of historic blocks are cached, so repeated proofs at the same
+    /// historic block don't recompute the storage roots of all accounts.
     pub async fn prove_account_at(
         &self,
         address: Address,
@@ -2218,17 +5093,35 @@ impl Backend {
         block_request: Option<BlockRequest>,
     ) -> Result<AccountProof, BlockchainError> {
         let block_number = block_request.as_ref().map(|r| r.block_number());
+        // only the state of historic blocks is immutable, the current state can change anytime
+        let historic_hash = match block_request {
+            Some(BlockRequest::Number(number)) if number < self.best_number() => {
+                self.get_block(number).map(|block| block.header.hash_slow())
+            }
+            _ => None,
+        };
 
         self.with_database_at(block_request, |block_db, _| {
             trace!(target: "backend", "get proof for {:?} at {:?}", address, block_number);
             let db = block_db.maybe_as_full_db().ok_or(BlockchainError::DataUnavailable)?;
             let account = db.get(&address).cloned().unwrap_or_default();
 
+            let leaves = match historic_hash {
+                Some(hash) => {
+                    let mut cache = self.account_trie_cache.lock();
+                    match &*cache {
+                        Some((cached, leaves)) if *cached == hash => leaves.clone(),
+                        _ => cache.insert((hash, Arc::new(trie_accounts(db)))).1.clone(),
+                    }
+                }
+                None => Arc::new(trie_accounts(db)),
+            };
+
             let mut builder = HashBuilder::default()
                 .with_proof_retainer(ProofRetainer::new(vec![Nibbles::unpack(keccak256(address))]));
 
-            for (key, account) in trie_accounts(db) {
-                builder.add_leaf(key, &account);
+            for (key, account) in leaves.iter() {
+                builder.add_leaf(key.clone(), account);
             }
 
             let _ = builder.root();
@@ -2259,6 +5152,14 @@ impl Backend {
         .await?
     }
 
+    /// Drops the cached account trie leaves if they belong to the block with the given hash
+    fn invalidate_account_trie_cache(&self, hash: B256) {
+        let mut cache = self.account_trie_cache.lock();
+        if cache.as_ref().is_some_and(|(cached, _)| *cached == hash) {
+            cache.take();
+        }
+    }
+
     /// Returns a new block event stream
     pub fn new_block_notifications(&self) -> NewBlockNotifications {
         let (tx, rx) = unbounded();
@@ -2267,17 +5168,210 @@ impl Backend {
         rx
     }
 
-    /// Notifies all `new_block_listeners` about the new block
-    fn notify_on_new_block(&self, header: Header, hash: B256) {
+    /// Returns a new block event stream that also includes the outcome of mining each block
+    pub fn new_block_outcome_notifications(&self) -> NewBlockOutcomeNotifications {
+        let (tx, rx) = unbounded();
+        self.new_block_outcome_listeners.lock().push(tx);
+        trace!(target: "backed", "added new block outcome listener");
+        rx
+    }
+
+    /// Registers a callback that is invoked with the outcome of every mined block
+    ///
+    /// Callbacks are invoked synchronously at the end of mining a block, in the order they were
+    /// registered, so they run on the mining task and should return quickly.
+    pub fn set_on_block_mined(&self, callback: BlockMinedCallback) {
+        self.block_mined_callbacks.lock().push(callback);
+    }
+
+    /// Sets a custom validator for transactions that are added to the pool, `None` removes it
+    ///
+    /// The validator runs after the built-in validation and rejects the transaction with the
+    /// returned message, which allows simulating custom mempool policies, e.g. a minimum tip.
+    pub fn set_transaction_validator(&self, validator: Option<TransactionValidatorCallback>) {
+        *self.transaction_validator.write() = validator;
+    }
+
+    /// Sets a custom inspector that observes the execution of calls and mined transactions, `None`
+    /// removes it
+    ///
+    /// The inspector runs alongside the built-in tracer and `console.log` collector.
+    pub fn set_custom_inspector(&self, inspector: Option<Arc<dyn CustomInspector>>) {
+        *self.custom_inspector.write() = inspector;
+    }
+
+    /// Notifies all `new_block_listeners` and `new_block_outcome_listeners` about the new block
+    fn notify_on_new_block(&self, header: Header, hash: B256, outcome: &MinedBlockOutcome) {
         // cleanup closed notification streams first, if the channel is closed we can remove the
         // sender half for the set
         self.new_block_listeners.lock().retain(|tx| !tx.is_closed());
+        self.new_block_outcome_listeners.lock().retain(|tx| !tx.is_closed());
 
-        let notification = NewBlockNotification { hash, header: Arc::new(header) };
+        let header = Arc::new(header);
+        let notification = NewBlockNotification { hash, header: header.clone() };
 
         self.new_block_listeners
             .lock()
             .retain(|tx| tx.unbounded_send(notification.clone()).is_ok());
+
+        let mut listeners = self.new_block_outcome_listeners.lock();
+        if !listeners.is_empty() {
+            let notification =
+                NewBlockOutcomeNotification { hash, header, outcome: Arc::new(outcome.clone()) };
+            listeners.retain(|tx| tx.unbounded_send(notification.clone()).is_ok());
+        }
+    }
+
+    /// Returns a new stream of transactions that were added to the pool
+    pub fn new_pending_transaction_notifications(&self) -> NewPendingTransactionNotifications {
+        let (tx, rx) = unbounded();
+        self.new_pending_transaction_listeners.lock().push(tx);
+        trace!(target: "backed", "added new pending transaction listener");
+        rx
+    }
+
+    /// Notifies all `new_pending_transaction_listeners` about the transaction that was added to
+    /// the pool
+    pub fn notify_on_new_pending_transaction(&self, transaction: Arc<PoolTransaction>) {
+        // cleanup closed notification streams first, if the channel is closed we can remove the
+        // sender half for the set
+        self.new_pending_transaction_listeners.lock().retain(|tx| !tx.is_closed());
+
+        self.new_pending_transaction_listeners
+            .lock()
+            .retain(|tx| tx.unbounded_send(transaction.clone()).is_ok());
+    }
+}
+
+/// Applies the block overrides to the block environment.
+///
+/// Note: block hash overrides are not supported.
+fn apply_block_overrides(overrides: BlockOverrides, block: &mut BlockEnv) {
+    let BlockOverrides {
+        number,
+        difficulty,
+        time,
+        gas_limit,
+        coinbase,
+        random,
+        base_fee,
+        block_hash: _,
+    } = overrides;
+    if let Some(number) = number {
+        block.number = number;
+    }
+    if let Some(difficulty) = difficulty {
+        block.difficulty = difficulty;
+    }
+    if let Some(time) = time {
+        block.timestamp = U256::from(time);
+    }
+    if let Some(gas_limit) = gas_limit {
+        block.gas_limit = U256::from(gas_limit);
+    }
+    if let Some(coinbase) = coinbase {
+        block.coinbase = coinbase;
+    }
+    if let Some(random) = random {
+        block.prevrandao = Some(random);
+    }
+    if let Some(base_fee) = base_fee {
+        block.basefee = base_fee;
+    }
+}
+
+/// Determines the minimum gas needed for a transaction depending on the transaction kind.
+fn determine_base_gas_by_kind(request: &WithOtherFields<TransactionRequest>) -> u128 {
+    match transaction_request_to_typed(request.clone()) {
+        Some(request) => match request {
+            TypedTransactionRequest::Legacy(req) => match req.to {
+                TxKind::Call(_) => MIN_TRANSACTION_GAS,
+                TxKind::Create => MIN_CREATE_GAS,
+            },
+            TypedTransactionRequest::EIP1559(req) => match req.to {
+                TxKind::Call(_) => MIN_TRANSACTION_GAS,
+                TxKind::Create => MIN_CREATE_GAS,
+            },
+            TypedTransactionRequest::EIP2930(req) => match req.to {
+                TxKind::Call(_) => MIN_TRANSACTION_GAS,
+                TxKind::Create => MIN_CREATE_GAS,
+            },
+            TypedTransactionRequest::EIP4844(_) => MIN_TRANSACTION_GAS,
+            TypedTransactionRequest::Deposit(req) => match req.kind {
+                TxKind::Call(_) => MIN_TRANSACTION_GAS,
+                TxKind::Create => MIN_CREATE_GAS,
+            },
+        },
+        // Tighten the gas limit upwards if we don't know the transaction type to avoid deployments
+        // failing.
+        _ => MIN_CREATE_GAS,
+    }
+}
+
+/// Keeps result of a call to revm EVM used for gas estimation
+enum GasEstimationCallResult {
+    Success(u128),
+    OutOfGas,
+    Revert(Option<Bytes>),
+    EvmError(InstructionResult),
+}
+
+/// Converts the result of a call to revm EVM into a [`GasEstimationCallResult`].
+impl TryFrom<Result<(InstructionResult, Option<Output>, u128, State), BlockchainError>>
+    for GasEstimationCallResult
+{
+    type Error = BlockchainError;
+
+    fn try_from(
+        res: Result<(InstructionResult, Option<Output>, u128, State), BlockchainError>,
+    ) -> Result<Self, BlockchainError> {
+        match res {
+            // Exceptional case: init used too much gas, treated as out of gas error
+            Err(BlockchainError::InvalidTransaction(InvalidTransactionError::GasTooHigh(_))) => {
+                Ok(Self::OutOfGas)
+            }
+            Err(err) => Err(err),
+            Ok((exit, output, gas, _)) => match exit {
+                return_ok!() | InstructionResult::CallOrCreate => Ok(Self::Success(gas)),
+
+                InstructionResult::Revert => Ok(Self::Revert(output.map(|o| o.into_data()))),
+
+                InstructionResult::OutOfGas |
+                InstructionResult::MemoryOOG |
+                InstructionResult::MemoryLimitOOG |
+                InstructionResult::PrecompileOOG |
+                InstructionResult::InvalidOperandOOG => Ok(Self::OutOfGas),
+
+                InstructionResult::OpcodeNotFound |
+                InstructionResult::CallNotAllowedInsideStatic |
+                InstructionResult::StateChangeDuringStaticCall |
+                InstructionResult::InvalidEFOpcode |
+                InstructionResult::InvalidJump |
+                InstructionResult::NotActivated |
+                InstructionResult::StackUnderflow |
+                InstructionResult::StackOverflow |
+                InstructionResult::OutOfOffset |
+                InstructionResult::CreateCollision |
+                InstructionResult::OverflowPayment |
+                InstructionResult::PrecompileError |
+                InstructionResult::NonceOverflow |
+                InstructionResult::CreateContractSizeLimit |
+                InstructionResult::CreateContractStartingWithEF |
+                InstructionResult::CreateInitCodeSizeLimit |
+                InstructionResult::FatalExternalError |
+                InstructionResult::OutOfFunds |
+                InstructionResult::CallTooDeep => Ok(Self::EvmError(exit)),
+
+                // Handle Revm EOF InstructionResults: Not supported yet
+                InstructionResult::ReturnContractInNotInitEOF |
+                InstructionResult::EOFOpcodeDisabledInLegacy |
+                InstructionResult::EOFFunctionStackOverflow |
+                InstructionResult::CreateInitCodeStartingEF00 |
+                InstructionResult::InvalidEOFInitCode |
+                InstructionResult::EofAuxDataOverflow |
+                InstructionResult::EofAuxDataTooSmall => Ok(Self::EvmError(exit)),
+            },
+        }
     }
 }
 
@@ -2307,7 +5401,15 @@ impl TransactionValidator for Backend {
         let address = *tx.sender();
         let account = self.get_account(address).await?;
         let env = self.next_env();
-        Ok(self.validate_pool_transaction_for(tx, &account, &env)?)
+        self.validate_pool_transaction_for(tx, &account, &env)?;
+        let validator = self.transaction_validator.read().clone();
+        if let Some(validator) = validator {
+            validator(tx, &account).map_err(|reason| {
+                warn!(target: "backend", "[{:?}] rejected by custom validator: {}", tx.hash(), reason);
+                InvalidTransactionError::Rejected(reason)
+            })?;
+        }
+        Ok(())
     }
 
     fn validate_pool_transaction_for(
@@ -2349,6 +5451,21 @@ impl TransactionValidator for Backend {
             }));
         }
 
+        // reject impersonated transactions of accounts whose impersonation expired in the meantime
+        if tx.is_impersonated() && self.cheats.is_impersonation_expired(*pending.sender()) {
+            warn!(target: "backend", "[{:?}] impersonation expired", tx.hash());
+            return Err(InvalidTransactionError::ImpersonationExpired);
+        }
+
+        // EIP-3607: reject senders with code, unless allowed for this sender
+        if !env.cfg.disable_eip3607 &&
+            account.code_hash != KECCAK_EMPTY &&
+            !self.is_sender_with_code_allowed(*pending.sender())
+        {
+            warn!(target: "backend", "[{:?}] sender has code", tx.hash());
+            return Err(InvalidTransactionError::SenderNoEOA);
+        }
+
         // check nonce
         let is_deposit_tx =
             matches!(&pending.transaction.transaction, TypedTransaction::Deposit(_));
@@ -2359,7 +5476,10 @@ impl TransactionValidator for Backend {
         }
 
         if (env.handler_cfg.spec_id as u8) >= (SpecId::LONDON as u8) {
-            if tx.gas_price() < env.block.basefee.to() && !is_deposit_tx {
+            if tx.gas_price() < env.block.basefee.to() &&
+                !is_deposit_tx &&
+                !self.accepts_underpriced()
+            {
                 warn!(target: "backend", "max fee per gas={}, too low, block basefee={}",tx.gas_price(),  env.block.basefee);
                 return Err(InvalidTransactionError::FeeCapTooLow);
             }
@@ -2400,8 +5520,9 @@ impl TransactionValidator for Backend {
             }
 
             // Ensure the tx does not exceed the max blobs per block.
-            if blob_count > MAX_BLOBS_PER_BLOCK {
-                return Err(InvalidTransactionError::TooManyBlobs(MAX_BLOBS_PER_BLOCK, blob_count))
+            let max_blob_count = self.fees.blob_params().max_blob_count as usize;
+            if blob_count > max_blob_count {
+                return Err(InvalidTransactionError::TooManyBlobs(max_blob_count, blob_count))
             }
 
             // Check for any blob validation errors
@@ -2499,6 +5620,28 @@ pub fn transaction_build(
     WithOtherFields::new(transaction)
 }
 
+/// Same as [transaction_build], but the gas price of a pending dynamic fee transaction is its
+/// effective gas price at the given live `base_fee`: `min(max_fee_per_gas, base_fee +
+/// max_priority_fee_per_gas)`, instead of its `max_fee_per_gas`.
+pub fn transaction_build_with_base_fee(
+    tx_hash: Option<B256>,
+    eth_transaction: MaybeImpersonatedTransaction,
+    block: Option<&Block>,
+    info: Option<TransactionInfo>,
+    base_fee: u128,
+) -> WithOtherFields<Transaction> {
+    let is_pending = block.is_none() && info.is_none();
+    let mut transaction = transaction_build(tx_hash, eth_transaction, block, info, Some(base_fee));
+    if is_pending {
+        if let Some(max_fee_per_gas) = transaction.max_fee_per_gas {
+            let max_priority_fee_per_gas = transaction.max_priority_fee_per_gas.unwrap_or(0);
+            transaction.gas_price =
+                Some(max_fee_per_gas.min(base_fee.saturating_add(max_priority_fee_per_gas)));
+        }
+    }
+    transaction
+}
+
 /// Prove a storage key's existence or nonexistence in the account's storage
 /// trie.
 /// `storage_key` is the hash of the desired storage key, meaning
@@ -3,13 +3,16 @@
 use crate::eth::error::BlockchainError;
 use alloy_primitives::{keccak256, Address, B256, U256};
 use alloy_rlp::Encodable;
-use alloy_rpc_types::state::StateOverride;
-use alloy_trie::{HashBuilder, Nibbles};
+use alloy_rpc_types::{state::StateOverride, EIP1186AccountProofResponse};
+use alloy_trie::{
+    proof::{verify_proof, ProofVerificationError},
+    HashBuilder, Nibbles, EMPTY_ROOT_HASH,
+};
 use foundry_evm::{
     backend::DatabaseError,
     revm::{
         db::{CacheDB, DatabaseRef, DbAccount},
-        primitives::{AccountInfo, Bytecode, HashMap},
+        primitives::{AccountInfo, Bytecode, HashMap, KECCAK_EMPTY},
     },
 };
 
@@ -35,6 +38,8 @@ pub fn storage_root(storage: &HashMap<U256, U256>) -> B256 {
 pub fn trie_storage(storage: &HashMap<U256, U256>) -> Vec<(Nibbles, Vec<u8>)> {
     let mut storage = storage
         .iter()
+        // zero values are not part of the trie
+        .filter(|(_, value)| !value.is_zero())
         .map(|(key, value)| {
             let data = alloy_rlp::encode(value);
             (Nibbles::unpack(keccak256(key.to_be_bytes::<32>())), data)
@@ -70,6 +75,42 @@ pub fn trie_account_rlp(info: &AccountInfo, storage: &HashMap<U256, U256>) -> Ve
     out
 }
 
+/// Verifies the account proof of `proof` against `state_root`, and its storage proofs against
+/// the proven `storage_hash`
+///
+/// Missing accounts and zero storage values are verified as exclusion proofs.
+pub fn verify_account_proof(
+    state_root: B256,
+    proof: &EIP1186AccountProofResponse,
+) -> Result<(), BlockchainError> {
+    let to_err = |err: ProofVerificationError| BlockchainError::TrieError(err.to_string());
+    let key = Nibbles::unpack(keccak256(proof.address));
+    let mut account = Vec::new();
+    let list: [&dyn Encodable; 4] =
+        [&proof.nonce, &proof.balance, &proof.storage_hash, &proof.code_hash];
+    alloy_rlp::encode_list::<_, dyn Encodable>(&list, &mut account);
+
+    let is_empty = proof.nonce.is_zero() &&
+        proof.balance.is_zero() &&
+        proof.code_hash == KECCAK_EMPTY &&
+        proof.storage_hash == EMPTY_ROOT_HASH;
+    match verify_proof(state_root, key.clone(), Some(account), &proof.account_proof) {
+        // an empty account may as well not exist at all
+        Err(_) if is_empty => {
+            verify_proof(state_root, key, None, &proof.account_proof).map_err(to_err)?
+        }
+        res => res.map_err(to_err)?,
+    }
+
+    for storage in &proof.storage_proof {
+        let key = Nibbles::unpack(keccak256(storage.key.0));
+        let value = (!storage.value.is_zero()).then(|| alloy_rlp::encode(storage.value));
+        verify_proof(proof.storage_hash, key, value, &storage.proof).map_err(to_err)?;
+    }
+
+    Ok(())
+}
+
 /// Applies the given state overrides to the state, returning a new CacheDB state
 pub fn apply_state_override<D>(
     overrides: StateOverride,
@@ -1,13 +1,15 @@
 //! In-memory blockchain storage
 use crate::eth::{
     backend::{
-        db::{MaybeFullDatabase, SerializableBlock, StateDb},
+        db::{MaybeFullDatabase, SerializableBlock, SerializableTransaction, StateDb},
         mem::cache::DiskStateCache,
     },
-    error::BlockchainError,
+    error::{BlockchainError, InvalidTransactionError},
     pool::transactions::PoolTransaction,
 };
-use alloy_primitives::{Bytes, TxHash, B256, U256, U64};
+use alloy_eips::eip4895::Withdrawal;
+use alloy_primitives::{Address, Bytes, TxHash, B256, U256, U64};
+use alloy_rlp::Encodable;
 use alloy_rpc_types::{
     trace::{
         geth::{
@@ -25,7 +27,10 @@ use anvil_core::eth::{
 use anvil_rpc::error::RpcError;
 use foundry_evm::{
     revm::primitives::Env,
-    traces::{FourByteInspector, GethTraceBuilder, ParityTraceBuilder, TracingInspectorConfig},
+    traces::{
+        CallTraceNode, FourByteInspector, GethTraceBuilder, ParityTraceBuilder,
+        TracingInspectorConfig,
+    },
 };
 use parking_lot::RwLock;
 use std::{
@@ -85,6 +90,15 @@ impl InMemoryBlockStates {
         self
     }
 
+    /// Configures to keep the state of every block.
+    ///
+    /// States exceeding the in memory limit are moved to disk instead of being discarded, so memory
+    /// usage stays bounded while disk usage grows with every mined block.
+    pub fn archive(mut self) -> Self {
+        self.max_on_disk_limit = usize::MAX;
+        self
+    }
+
     /// This modifies the `limit` what to keep stored in memory.
     ///
     /// This will ensure the new limit adjusts based on the block time.
@@ -171,6 +185,25 @@ impl InMemoryBlockStates {
         })
     }
 
+    /// Returns the hashes of all stored states, starting with the oldest state
+    pub fn hashes_oldest_first(&self) -> impl Iterator<Item = &B256> + '_ {
+        self.oldest_on_disk.iter().chain(self.present.iter())
+    }
+
+    /// Removes the state for the given `hash`, whether it's kept in memory or on disk
+    pub fn remove(&mut self, hash: &B256) -> bool {
+        if self.states.remove(hash).is_some() {
+            self.present.retain(|present| present != hash);
+            return true
+        }
+        if self.on_disk_states.remove(hash).is_some() {
+            self.oldest_on_disk.retain(|on_disk| on_disk != hash);
+            self.disk_cache.remove(*hash);
+            return true
+        }
+        false
+    }
+
     /// Sets the maximum number of stats we keep in memory
     pub fn set_cache_limit(&mut self, limit: usize) {
         self.in_memory_limit = limit;
@@ -224,6 +257,12 @@ pub struct BlockchainStorage {
     pub transactions: HashMap<TxHash, MinedTransaction>,
     /// The total difficulty of the chain until this block
     pub total_difficulty: U256,
+    /// The combined RLP-encoded size of all stored blocks, in bytes
+    pub total_size: u64,
+    /// The accounts that were modified in each block, keyed by block hash
+    pub touched_accounts: HashMap<B256, Vec<Address>>,
+    /// The withdrawals that were processed in each block, keyed by block hash
+    pub withdrawals: HashMap<B256, Vec<Withdrawal>>,
 }
 
 impl BlockchainStorage {
@@ -244,6 +283,7 @@ impl BlockchainStorage {
         let genesis_hash = block.header.hash_slow();
         let best_hash = genesis_hash;
         let best_number: U64 = U64::from(0u64);
+        let total_size = block.length() as u64;
 
         Self {
             blocks: HashMap::from([(genesis_hash, block)]),
@@ -253,6 +293,9 @@ impl BlockchainStorage {
             genesis_hash,
             transactions: Default::default(),
             total_difficulty: Default::default(),
+            total_size,
+            touched_accounts: Default::default(),
+            withdrawals: Default::default(),
         }
     }
 
@@ -265,6 +308,9 @@ impl BlockchainStorage {
             genesis_hash: Default::default(),
             transactions: Default::default(),
             total_difficulty,
+            total_size: 0,
+            touched_accounts: Default::default(),
+            withdrawals: Default::default(),
         }
     }
 
@@ -278,6 +324,9 @@ impl BlockchainStorage {
             genesis_hash: Default::default(),
             transactions: Default::default(),
             total_difficulty: Default::default(),
+            total_size: 0,
+            touched_accounts: Default::default(),
+            withdrawals: Default::default(),
         }
     }
 
@@ -294,9 +343,30 @@ impl BlockchainStorage {
             for tx in block.transactions.iter() {
                 self.transactions.remove(&tx.hash());
             }
+            self.total_size -= block.length() as u64;
             block.transactions.clear();
+            self.total_size += block.length() as u64;
         }
     }
+
+    /// Removes all blocks after the given block, which becomes the new best block
+    pub fn unwind_to(&mut self, block_number: u64, block_hash: B256) {
+        let best_number: u64 = self.best_number.try_into().unwrap_or(0);
+        for number in ((block_number + 1)..=best_number).rev() {
+            if let Some(hash) = self.hashes.remove(&U64::from(number)) {
+                if let Some(block) = self.blocks.remove(&hash) {
+                    self.total_size -= block.length() as u64;
+                    for tx in block.transactions {
+                        self.transactions.remove(&tx.hash());
+                    }
+                }
+                self.touched_accounts.remove(&hash);
+                self.withdrawals.remove(&hash);
+            }
+        }
+        self.best_hash = block_hash;
+        self.best_number = U64::from(block_number);
+    }
 }
 
 impl BlockchainStorage {
@@ -337,10 +407,25 @@ impl BlockchainStorage {
             let block: Block = serializable_block.clone().into();
             let block_hash = block.header.hash_slow();
             let block_number = block.header.number;
-            self.blocks.insert(block_hash, block);
+            self.total_size += block.length() as u64;
+            if let Some(prev) = self.blocks.insert(block_hash, block) {
+                self.total_size -= prev.length() as u64;
+            }
             self.hashes.insert(U64::from(block_number), block_hash);
         }
     }
+
+    pub fn serialized_transactions(&self) -> Vec<SerializableTransaction> {
+        self.transactions.values().map(|tx| tx.clone().into()).collect()
+    }
+
+    /// Deserialize and add all mined transactions to the backend storage
+    pub fn load_transactions(&mut self, serializable_transactions: Vec<SerializableTransaction>) {
+        for serializable_transaction in serializable_transactions {
+            let transaction: MinedTransaction = serializable_transaction.into();
+            self.transactions.insert(transaction.info.transaction_hash, transaction);
+        }
+    }
 }
 
 /// A simple in-memory blockchain
@@ -393,6 +478,8 @@ impl Blockchain {
 pub struct MinedBlockOutcome {
     /// The block that was mined
     pub block_number: U64,
+    /// The hash of the block that was mined
+    pub block_hash: B256,
     /// All transactions included in the block
     pub included: Vec<Arc<PoolTransaction>>,
     /// All transactions that were attempted to be included but were invalid at the time of
@@ -400,6 +487,32 @@ pub struct MinedBlockOutcome {
     pub invalid: Vec<Arc<PoolTransaction>>,
 }
 
+/// The gas usage of a pending block, without the block itself
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PendingBlockGasUsage {
+    /// The gas limit of the pending block
+    pub gas_limit: u64,
+    /// The total gas used by all included transactions
+    pub gas_used: u64,
+    /// The total blob gas used by all included transactions
+    pub blob_gas_used: u64,
+    /// Whether the given transactions are included in the pending block, in their given order
+    pub included: Vec<(TxHash, bool)>,
+}
+
+/// The outcome of executing transactions in a block without committing it, see
+/// [Backend::dry_run_mine](crate::eth::backend::mem::Backend::dry_run_mine)
+#[derive(Debug, Default)]
+pub struct DryRunOutcome {
+    /// The transactions that would be included, in their execution order
+    pub included: Vec<TxHash>,
+    /// The transactions that would be rejected because they're invalid at the point of their
+    /// execution, with the reason
+    pub invalid: Vec<(TxHash, InvalidTransactionError)>,
+    /// The total gas used by all included transactions
+    pub gas_used: u64,
+}
+
 /// Container type for a mined transaction
 #[derive(Clone, Debug)]
 pub struct MinedTransaction {
@@ -409,6 +522,28 @@ pub struct MinedTransaction {
     pub block_number: u64,
 }
 
+impl From<MinedTransaction> for SerializableTransaction {
+    fn from(transaction: MinedTransaction) -> Self {
+        Self {
+            info: transaction.info,
+            receipt: transaction.receipt,
+            block_hash: transaction.block_hash,
+            block_number: transaction.block_number,
+        }
+    }
+}
+
+impl From<SerializableTransaction> for MinedTransaction {
+    fn from(transaction: SerializableTransaction) -> Self {
+        Self {
+            info: transaction.info,
+            receipt: transaction.receipt,
+            block_hash: transaction.block_hash,
+            block_number: transaction.block_number,
+        }
+    }
+}
+
 impl MinedTransaction {
     /// Returns the traces of the transaction for `trace_transaction`
     pub fn parity_traces(&self) -> Vec<LocalizedTransactionTrace> {
@@ -427,53 +562,63 @@ impl MinedTransaction {
     }
 
     pub fn geth_trace(&self, opts: GethDebugTracingOptions) -> Result<GethTrace, BlockchainError> {
-        let GethDebugTracingOptions { config, tracer, tracer_config, .. } = opts;
-
-        if let Some(tracer) = tracer {
-            match tracer {
-                GethDebugTracerType::BuiltInTracer(tracer) => match tracer {
-                    GethDebugBuiltInTracerType::FourByteTracer => {
-                        let inspector = FourByteInspector::default();
-                        return Ok(FourByteFrame::from(inspector).into())
-                    }
-                    GethDebugBuiltInTracerType::CallTracer => {
-                        return match tracer_config.into_call_config() {
-                            Ok(call_config) => Ok(GethTraceBuilder::new(
-                                self.info.traces.clone(),
-                                TracingInspectorConfig::from_geth_config(&config),
-                            )
-                            .geth_call_traces(
-                                call_config,
-                                self.receipt.cumulative_gas_used() as u64,
-                            )
-                            .into()),
-                            Err(e) => Err(RpcError::invalid_params(e.to_string()).into()),
-                        };
-                    }
-                    GethDebugBuiltInTracerType::PreStateTracer |
-                    GethDebugBuiltInTracerType::NoopTracer |
-                    GethDebugBuiltInTracerType::MuxTracer => {}
-                },
-                GethDebugTracerType::JsTracer(_code) => {}
-            }
-
-            return Ok(NoopFrame::default().into());
-        }
-
-        // default structlog tracer
-        Ok(GethTraceBuilder::new(
+        geth_trace(
             self.info.traces.clone(),
-            TracingInspectorConfig::from_geth_config(&config),
-        )
-        .geth_traces(
             self.receipt.cumulative_gas_used() as u64,
             self.info.out.clone().unwrap_or_default(),
-            opts.config,
+            opts,
         )
-        .into())
     }
 }
 
+/// Builds the geth trace of the given call traces with the tracer configured in `opts`
+pub fn geth_trace(
+    traces: Vec<CallTraceNode>,
+    gas_used: u64,
+    output: Bytes,
+    opts: GethDebugTracingOptions,
+) -> Result<GethTrace, BlockchainError> {
+    let GethDebugTracingOptions { config, tracer, tracer_config, .. } = opts;
+
+    if let Some(tracer) = tracer {
+        match tracer {
+            GethDebugTracerType::BuiltInTracer(tracer) => match tracer {
+                GethDebugBuiltInTracerType::FourByteTracer => {
+                    let inspector = FourByteInspector::default();
+                    return Ok(FourByteFrame::from(inspector).into())
+                }
+                GethDebugBuiltInTracerType::CallTracer => {
+                    return match tracer_config.into_call_config() {
+                        Ok(call_config) => Ok(GethTraceBuilder::new(
+                            traces,
+                            TracingInspectorConfig::from_geth_config(&config),
+                        )
+                        .geth_call_traces(call_config, gas_used)
+                        .into()),
+                        Err(e) => Err(RpcError::invalid_params(e.to_string()).into()),
+                    };
+                }
+                GethDebugBuiltInTracerType::PreStateTracer |
+                GethDebugBuiltInTracerType::NoopTracer |
+                GethDebugBuiltInTracerType::MuxTracer => {}
+            },
+            GethDebugTracerType::JsTracer(_code) => {}
+        }
+
+        return Ok(NoopFrame::default().into());
+    }
+
+    // default structlog tracer
+    let mut frame =
+        GethTraceBuilder::new(traces, TracingInspectorConfig::from_geth_config(&config))
+            .geth_traces(gas_used, output, config);
+    // a limit of zero means no limit
+    if let Some(limit) = config.limit.filter(|limit| *limit > 0) {
+        frame.struct_logs.truncate(limit as usize);
+    }
+    Ok(frame.into())
+}
+
 /// Intermediary Anvil representation of a receipt
 #[derive(Clone, Debug)]
 pub struct MinedTransactionReceipt {
@@ -487,7 +632,7 @@ pub struct MinedTransactionReceipt {
 mod tests {
     use super::*;
     use crate::eth::backend::db::Db;
-    use alloy_primitives::{hex, Address};
+    use alloy_primitives::hex;
     use alloy_rlp::Decodable;
     use anvil_core::eth::transaction::TypedTransaction;
     use foundry_evm::{
@@ -1,5 +1,6 @@
 //! Notifications emitted from the backed
 
+use crate::eth::{backend::mem::storage::MinedBlockOutcome, pool::transactions::PoolTransaction};
 use alloy_consensus::Header;
 use alloy_primitives::B256;
 use futures::channel::mpsc::UnboundedReceiver;
@@ -16,3 +17,37 @@ pub struct NewBlockNotification {
 
 /// Type alias for a receiver that receives [NewBlockNotification]
 pub type NewBlockNotifications = UnboundedReceiver<NewBlockNotification>;
+
+/// A notification that's emitted when a new block was mined, including the outcome of mining it
+#[derive(Clone, Debug)]
+pub struct NewBlockOutcomeNotification {
+    /// Hash of the mined block
+    pub hash: B256,
+    /// block header
+    pub header: Arc<Header>,
+    /// the transactions that were included in, or rejected from, the block
+    pub outcome: Arc<MinedBlockOutcome>,
+}
+
+impl NewBlockOutcomeNotification {
+    /// Returns the number of transactions included in the block
+    pub fn included_count(&self) -> usize {
+        self.outcome.included.len()
+    }
+
+    /// Returns the number of transactions that were invalid at the time of execution
+    pub fn invalid_count(&self) -> usize {
+        self.outcome.invalid.len()
+    }
+
+    /// Returns the total gas used by the block
+    pub fn gas_used(&self) -> u128 {
+        self.header.gas_used
+    }
+}
+
+/// Type alias for a receiver that receives [NewBlockOutcomeNotification]
+pub type NewBlockOutcomeNotifications = UnboundedReceiver<NewBlockOutcomeNotification>;
+
+/// Type alias for a receiver that receives every transaction that was added to the pool
+pub type NewPendingTransactionNotifications = UnboundedReceiver<Arc<PoolTransaction>>;
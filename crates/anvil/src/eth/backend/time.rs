@@ -23,6 +23,8 @@ pub struct TimeManager {
     next_exact_timestamp: Arc<RwLock<Option<u64>>>,
     /// The interval to use when determining the next block's timestamp
     interval: Arc<RwLock<Option<u64>>>,
+    /// Whether the next block's timestamp is independent of the wall clock
+    deterministic: Arc<RwLock<bool>>,
 }
 
 impl TimeManager {
@@ -32,6 +34,7 @@ impl TimeManager {
             offset: Default::default(),
             next_exact_timestamp: Default::default(),
             interval: Default::default(),
+            deterministic: Default::default(),
         };
         time_manager.reset(start_timestamp);
         time_manager
@@ -98,6 +101,16 @@ impl TimeManager {
         }
     }
 
+    /// Sets whether the next timestamps are independent of the wall clock
+    ///
+    /// If enabled, the next timestamp is the last timestamp plus the configured interval, or plus
+    /// one second if there's no interval. Exact timestamps set via
+    /// [Self::set_next_block_timestamp] are still used.
+    pub fn set_deterministic(&self, deterministic: bool) {
+        trace!(target: "time", "set deterministic {}", deterministic);
+        *self.deterministic.write() = deterministic;
+    }
+
     /// Computes the next timestamp without updating internals
     fn compute_next_timestamp(&self) -> (u64, Option<i128>) {
         let current = duration_since_unix_epoch().as_secs() as i128;
@@ -108,6 +121,8 @@ impl TimeManager {
                 (next, true)
             } else if let Some(interval) = *self.interval.read() {
                 (last_timestamp.saturating_add(interval), false)
+            } else if *self.deterministic.read() {
+                (last_timestamp.saturating_add(1), false)
             } else {
                 (current.saturating_add(self.offset()) as u64, false)
             };
@@ -18,6 +18,7 @@ use foundry_evm::{
     },
 };
 use serde::Serialize;
+use std::time::Duration;
 
 pub(crate) type Result<T> = std::result::Result<T, BlockchainError>;
 
@@ -65,8 +66,12 @@ pub enum BlockchainError {
     Internal(String),
     #[error("BlockOutOfRangeError: block height is {0} but requested was {1}")]
     BlockOutOfRange(u64, u64),
+    #[error("Historical state for block {0} is no longer retained")]
+    HistoricalStatePruned(u64),
     #[error("Resource not found")]
     BlockNotFound,
+    #[error("Broken hash chain: parent hash of block {0} does not match the previous block")]
+    BrokenHashChain(u64),
     #[error("Required data unavailable")]
     DataUnavailable,
     #[error("Trie error: {0}")]
@@ -91,6 +96,8 @@ pub enum BlockchainError {
     ExcessBlobGasNotSet,
     #[error("{0}")]
     Message(String),
+    #[error("execution aborted (timeout = {0:?})")]
+    ExecutionTimeout(Duration),
 }
 
 impl From<RpcError> for BlockchainError {
@@ -136,6 +143,8 @@ pub enum FeeHistoryError {
     InvalidBlockRange,
     #[error("could not find newest block number requested: {0}")]
     BlockNotFound(BlockNumberOrTag),
+    #[error("reward percentiles must be monotonically increasing and within 0..=100")]
+    InvalidRewardPercentiles,
 }
 
 #[derive(Debug)]
@@ -229,6 +238,13 @@ pub enum InvalidTransactionError {
     /// Thrown when there are no `blob_hashes` in the transaction.
     #[error("There should be at least one blob in a Blob transaction.")]
     EmptyBlobs,
+    /// Thrown when an impersonated transaction is executed after the sender's impersonation
+    /// expired.
+    #[error("impersonation of sender has expired")]
+    ImpersonationExpired,
+    /// Thrown when the transaction is rejected by the custom transaction validator.
+    #[error("{0}")]
+    Rejected(String),
 }
 
 impl From<revm::primitives::InvalidTransaction> for InvalidTransactionError {
@@ -387,12 +403,18 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                 err @ BlockchainError::BlockOutOfRange(_, _) => {
                     RpcError::invalid_params(err.to_string())
                 }
+                err @ BlockchainError::HistoricalStatePruned(_) => {
+                    RpcError::invalid_params(err.to_string())
+                }
                 err @ BlockchainError::BlockNotFound => RpcError {
                     // <https://eips.ethereum.org/EIPS/eip-1898>
                     code: ErrorCode::ServerError(-32001),
                     message: err.to_string().into(),
                     data: None,
                 },
+                err @ BlockchainError::BrokenHashChain(_) => {
+                    RpcError::internal_error_with(err.to_string())
+                }
                 err @ BlockchainError::DataUnavailable => {
                     RpcError::internal_error_with(err.to_string())
                 }
@@ -425,6 +447,11 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                     RpcError::invalid_params(err.to_string())
                 }
                 err @ BlockchainError::Message(_) => RpcError::internal_error_with(err.to_string()),
+                err @ BlockchainError::ExecutionTimeout(_) => RpcError {
+                    code: ErrorCode::ServerError(-32000),
+                    message: err.to_string().into(),
+                    data: None,
+                },
             }
             .into(),
         }
@@ -2,12 +2,13 @@ use crate::eth::{
     backend::{info::StorageInfo, notifications::NewBlockNotifications},
     error::BlockchainError,
 };
-use alloy_eips::{
-    calc_next_block_base_fee, eip1559::BaseFeeParams, eip4844::MAX_DATA_GAS_PER_BLOCK,
-};
+use alloy_eips::{calc_next_block_base_fee, eip1559::BaseFeeParams};
 use alloy_primitives::B256;
 use anvil_core::eth::transaction::TypedTransaction;
-use foundry_evm::revm::primitives::{BlobExcessGasAndPrice, SpecId};
+use foundry_evm::revm::primitives::{
+    fake_exponential, BlobExcessGasAndPrice, SpecId, BLOB_GASPRICE_UPDATE_FRACTION, GAS_PER_BLOB,
+    MAX_BLOB_NUMBER_PER_BLOCK, MIN_BLOB_GASPRICE, TARGET_BLOB_NUMBER_PER_BLOCK,
+};
 use futures::StreamExt;
 use parking_lot::{Mutex, RwLock};
 use std::{
@@ -38,11 +39,69 @@ pub fn default_elasticity() -> f64 {
     1f64 / BaseFeeParams::ethereum().elasticity_multiplier as f64
 }
 
+/// The blob schedule of [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844), defaults to the
+/// Cancun parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlobParams {
+    /// The maximum number of blobs per block
+    pub max_blob_count: u64,
+    /// The number of blobs per block the blob base fee targets
+    pub target_blob_count: u64,
+    /// Controls how fast the blob base fee changes with the excess blob gas
+    pub update_fraction: u64,
+}
+
+impl BlobParams {
+    /// Returns the blob parameters of the Cancun hardfork
+    pub const fn cancun() -> Self {
+        Self {
+            max_blob_count: MAX_BLOB_NUMBER_PER_BLOCK,
+            target_blob_count: TARGET_BLOB_NUMBER_PER_BLOCK,
+            update_fraction: BLOB_GASPRICE_UPDATE_FRACTION,
+        }
+    }
+
+    /// Returns the maximum blob gas per block
+    pub const fn max_blob_gas_per_block(&self) -> u64 {
+        self.max_blob_count.saturating_mul(GAS_PER_BLOB)
+    }
+
+    /// Returns the blob gas per block the blob base fee targets
+    pub const fn target_blob_gas_per_block(&self) -> u64 {
+        self.target_blob_count.saturating_mul(GAS_PER_BLOB)
+    }
+
+    /// Calculates the excess blob gas of the next block, see also
+    /// [`calc_excess_blob_gas`](foundry_evm::revm::primitives::calc_excess_blob_gas)
+    pub const fn next_block_excess_blob_gas(
+        &self,
+        parent_excess_blob_gas: u64,
+        parent_blob_gas_used: u64,
+    ) -> u64 {
+        parent_excess_blob_gas
+            .saturating_add(parent_blob_gas_used)
+            .saturating_sub(self.target_blob_gas_per_block())
+    }
+
+    /// Calculates the blob base fee for the given excess blob gas
+    pub fn blob_gasprice(&self, excess_blob_gas: u64) -> u128 {
+        fake_exponential(MIN_BLOB_GASPRICE, excess_blob_gas, self.update_fraction)
+    }
+}
+
+impl Default for BlobParams {
+    fn default() -> Self {
+        Self::cancun()
+    }
+}
+
 /// Stores the fee related information
 #[derive(Clone, Debug)]
 pub struct FeeManager {
     /// Hardfork identifier
-    spec_id: SpecId,
+    ///
+    /// This value may be updated when a scheduled hardfork is activated
+    spec_id: Arc<RwLock<SpecId>>,
     /// Tracks the base fee for the next block post London
     ///
     /// This value will be updated after a new block was mined
@@ -56,6 +115,14 @@ pub struct FeeManager {
     /// This will be constant value unless changed manually
     gas_price: Arc<RwLock<u128>>,
     elasticity: Arc<RwLock<f64>>,
+    /// The gas target used to calculate the next block's base fee
+    ///
+    /// If not set, this is the gas limit divided by the elasticity multiplier
+    gas_target: Arc<RwLock<Option<u128>>>,
+    /// The minimum base fee of the next blocks, if any
+    base_fee_floor: Arc<RwLock<Option<u128>>>,
+    /// The blob schedule used for the blob base fee and the blob limits of the next blocks
+    blob_params: Arc<RwLock<BlobParams>>,
 }
 
 impl FeeManager {
@@ -66,11 +133,14 @@ impl FeeManager {
         blob_excess_gas_and_price: BlobExcessGasAndPrice,
     ) -> Self {
         Self {
-            spec_id,
+            spec_id: Arc::new(RwLock::new(spec_id)),
             base_fee: Arc::new(RwLock::new(base_fee)),
             gas_price: Arc::new(RwLock::new(gas_price)),
             blob_excess_gas_and_price: Arc::new(RwLock::new(blob_excess_gas_and_price)),
             elasticity: Arc::new(RwLock::new(default_elasticity())),
+            gas_target: Default::default(),
+            base_fee_floor: Default::default(),
+            blob_params: Default::default(),
         }
     }
 
@@ -78,13 +148,86 @@ impl FeeManager {
         *self.elasticity.read()
     }
 
+    /// Returns the custom gas target, if any
+    pub fn gas_target(&self) -> Option<u128> {
+        *self.gas_target.read()
+    }
+
+    /// Sets a custom gas target for the base fee calculation, `None` restores the default of
+    /// gas limit / elasticity multiplier
+    pub fn set_gas_target(&self, gas_target: Option<u128>) {
+        trace!(target: "backend::fees", "updated gas target {:?}", gas_target);
+        *self.gas_target.write() = gas_target.filter(|target| *target > 0);
+    }
+
+    /// Returns the minimum base fee of the next blocks, if any
+    pub fn base_fee_floor(&self) -> Option<u128> {
+        *self.base_fee_floor.read()
+    }
+
+    /// Sets the minimum base fee the next blocks' base fee is clamped to, `None` removes the floor
+    pub fn set_base_fee_floor(&self, floor: Option<u128>) {
+        trace!(target: "backend::fees", "updated base fee floor {:?}", floor);
+        *self.base_fee_floor.write() = floor;
+    }
+
+    /// Sets the hardfork identifier, e.g. when a scheduled hardfork is activated
+    pub fn set_spec_id(&self, spec_id: SpecId) {
+        trace!(target: "backend::fees", "updated spec id {:?}", spec_id);
+        *self.spec_id.write() = spec_id;
+    }
+
+    /// Returns the blob schedule of the next blocks
+    pub fn blob_params(&self) -> BlobParams {
+        *self.blob_params.read()
+    }
+
+    /// Overrides the blob schedule of the next blocks
+    ///
+    /// The next block is repriced right away with the new update fraction, the new target is used
+    /// for the excess blob gas of all blocks mined afterwards. An update fraction of `0` restores
+    /// the EIP-4844 default.
+    pub fn set_blob_params(&self, mut params: BlobParams) {
+        if params.update_fraction == 0 {
+            params.update_fraction = BLOB_GASPRICE_UPDATE_FRACTION;
+        }
+        trace!(target: "backend::fees", "updated blob params {:?}", params);
+        *self.blob_params.write() = params;
+        let excess_blob_gas = self.blob_excess_gas_and_price.read().excess_blob_gas;
+        self.set_blob_excess_gas_and_price(BlobExcessGasAndPrice::new(excess_blob_gas));
+    }
+
+    /// Returns the custom blob base fee update fraction, if any
+    pub fn blob_base_fee_update_fraction(&self) -> Option<u64> {
+        Some(self.blob_params().update_fraction)
+            .filter(|fraction| *fraction != BLOB_GASPRICE_UPDATE_FRACTION)
+    }
+
+    /// Overrides the blob base fee update fraction, `None` restores the EIP-4844 default of
+    /// [`BLOB_GASPRICE_UPDATE_FRACTION`]
+    ///
+    /// The fraction controls how fast the blob base fee changes with the excess blob gas. It's used
+    /// for all blob base fees this computes, including the one of the next block, which is
+    /// repriced right away.
+    pub fn set_blob_base_fee_update_fraction(&self, fraction: Option<u64>) {
+        let update_fraction =
+            fraction.filter(|fraction| *fraction > 0).unwrap_or(BLOB_GASPRICE_UPDATE_FRACTION);
+        self.set_blob_params(BlobParams { update_fraction, ..self.blob_params() });
+    }
+
+    /// Returns the blob base fee for the given excess blob gas, using the custom blob base fee
+    /// update fraction if set
+    pub fn calc_blob_gasprice(&self, excess_blob_gas: u64) -> u128 {
+        self.blob_params().blob_gasprice(excess_blob_gas)
+    }
+
     /// Returns true for post London
     pub fn is_eip1559(&self) -> bool {
-        (self.spec_id as u8) >= (SpecId::LONDON as u8)
+        (*self.spec_id.read() as u8) >= (SpecId::LONDON as u8)
     }
 
     pub fn is_eip4844(&self) -> bool {
-        (self.spec_id as u8) >= (SpecId::CANCUN as u8)
+        (*self.spec_id.read() as u8) >= (SpecId::CANCUN as u8)
     }
 
     /// Calculates the current blob gas price
@@ -139,7 +282,14 @@ impl FeeManager {
     }
 
     /// Sets the current blob excess gas and price
-    pub fn set_blob_excess_gas_and_price(&self, blob_excess_gas_and_price: BlobExcessGasAndPrice) {
+    ///
+    /// The price is recomputed with the update fraction of the current blob schedule.
+    pub fn set_blob_excess_gas_and_price(
+        &self,
+        mut blob_excess_gas_and_price: BlobExcessGasAndPrice,
+    ) {
+        blob_excess_gas_and_price.blob_gasprice =
+            self.calc_blob_gasprice(blob_excess_gas_and_price.excess_blob_gas);
         trace!(target: "backend::fees", "updated blob base fee {:?}", blob_excess_gas_and_price);
         let mut base = self.blob_excess_gas_and_price.write();
         *base = blob_excess_gas_and_price;
@@ -158,22 +308,28 @@ impl FeeManager {
         if self.base_fee() == 0 {
             return 0
         }
-        calculate_next_block_base_fee(gas_used, gas_limit, last_fee_per_gas)
+        let next_base_fee = if let Some(gas_target) = self.gas_target() {
+            calculate_next_block_base_fee_with_target(gas_used, gas_target, last_fee_per_gas)
+        } else {
+            calculate_next_block_base_fee(gas_used, gas_limit, last_fee_per_gas)
+        };
+        next_base_fee.max(self.base_fee_floor().unwrap_or_default())
     }
 
     /// Calculates the next block blob base fee, using the provided excess blob gas
     pub fn get_next_block_blob_base_fee_per_gas(&self, excess_blob_gas: u128) -> u128 {
-        crate::revm::primitives::calc_blob_gasprice(excess_blob_gas as u64)
+        self.calc_blob_gasprice(excess_blob_gas as u64)
     }
 
-    /// Calculates the next block blob excess gas, using the provided parent blob gas used and
-    /// parent blob excess gas
+    /// Calculates the next block blob excess gas, using the provided parent blob excess gas and
+    /// parent blob gas used, against the target of the current blob schedule
     pub fn get_next_block_blob_excess_gas(
         &self,
-        blob_gas_used: u128,
-        blob_excess_gas: u128,
+        parent_excess_blob_gas: u128,
+        parent_blob_gas_used: u128,
     ) -> u64 {
-        crate::revm::primitives::calc_excess_blob_gas(blob_gas_used as u64, blob_excess_gas as u64)
+        self.blob_params()
+            .next_block_excess_blob_gas(parent_excess_blob_gas as u64, parent_blob_gas_used as u64)
     }
 }
 
@@ -182,6 +338,18 @@ pub fn calculate_next_block_base_fee(gas_used: u128, gas_limit: u128, base_fee:
     calc_next_block_base_fee(gas_used, gas_limit, base_fee, BaseFeeParams::ethereum())
 }
 
+/// Calculate base fee for next block against an explicit gas target instead of the gas limit
+/// divided by the elasticity multiplier.
+pub fn calculate_next_block_base_fee_with_target(
+    gas_used: u128,
+    gas_target: u128,
+    base_fee: u128,
+) -> u128 {
+    // with an elasticity multiplier of 1 the gas limit is used as the gas target as is
+    let params = BaseFeeParams::new(BaseFeeParams::ethereum().max_change_denominator, 1);
+    calc_next_block_base_fee(gas_used, gas_target, base_fee, params)
+}
+
 /// An async service that takes care of the `FeeHistory` cache
 pub struct FeeHistoryService {
     /// incoming notifications about new blocks
@@ -260,8 +428,8 @@ impl FeeHistoryService {
             let gas_used = block.header.gas_used as f64;
             let blob_gas_used = block.header.blob_gas_used.map(|g| g as f64);
             item.gas_used_ratio = gas_used / block.header.gas_limit as f64;
-            item.blob_gas_used_ratio =
-                blob_gas_used.map(|g| g / MAX_DATA_GAS_PER_BLOCK as f64).unwrap_or(0 as f64);
+            let max_blob_gas = self.fees.blob_params().max_blob_gas_per_block() as f64;
+            item.blob_gas_used_ratio = blob_gas_used.map(|g| g / max_blob_gas).unwrap_or(0 as f64);
 
             // extract useful tx info (gas_used, effective_reward)
             let mut transactions: Vec<(u128, u128)> = receipts
@@ -369,6 +537,49 @@ pub struct FeeHistoryCacheItem {
     pub rewards: Vec<u128>,
 }
 
+/// The width of the priority fee tiers of a [PoolFeeLandscape], 1 gwei
+pub const POOL_FEE_TIER_SIZE: u128 = 1e9 as u128;
+
+/// A histogram of the priority fees that transactions in the pool are bidding
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolFeeLandscape {
+    /// The base fee the effective priority fees were computed with
+    pub base_fee: u128,
+    /// All non-empty tiers, sorted from the highest to the lowest priority fee
+    pub tiers: Vec<PoolFeeTier>,
+}
+
+impl PoolFeeLandscape {
+    /// Buckets the `transactions` by the priority fee they effectively pay on top of `base_fee`
+    pub fn new<'a>(
+        base_fee: u128,
+        transactions: impl IntoIterator<Item = &'a TypedTransaction>,
+    ) -> Self {
+        let mut tiers = BTreeMap::<u128, PoolFeeTier>::new();
+        for tx in transactions {
+            let priority_fee = tx.effective_gas_price(Some(base_fee)).saturating_sub(base_fee);
+            let min_priority_fee = priority_fee - priority_fee % POOL_FEE_TIER_SIZE;
+            let tier = tiers
+                .entry(min_priority_fee)
+                .or_insert_with(|| PoolFeeTier { min_priority_fee, ..Default::default() });
+            tier.transactions += 1;
+            tier.gas += tx.gas_limit();
+        }
+        Self { base_fee, tiers: tiers.into_values().rev().collect() }
+    }
+}
+
+/// All transactions of a [PoolFeeLandscape] within a range of [POOL_FEE_TIER_SIZE] priority fees
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolFeeTier {
+    /// The lower bound of the effective priority fees in this tier
+    pub min_priority_fee: u128,
+    /// The number of transactions in this tier
+    pub transactions: usize,
+    /// The total gas limit of all transactions in this tier
+    pub gas: u128,
+}
+
 #[derive(Clone, Default)]
 pub struct FeeDetails {
     pub gas_price: Option<u128>,
@@ -84,7 +84,7 @@ impl Pool {
     ///
     /// This will remove the transactions from the pool.
     pub fn on_mined_block(&self, outcome: MinedBlockOutcome) -> PruneResult {
-        let MinedBlockOutcome { block_number, included, invalid } = outcome;
+        let MinedBlockOutcome { block_number, included, invalid, .. } = outcome;
 
         // remove invalid transactions from the pool
         self.remove_invalid(invalid.into_iter().map(|tx| tx.hash()).collect());
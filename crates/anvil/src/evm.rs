@@ -1,5 +1,8 @@
-use alloy_primitives::Address;
-use foundry_evm::revm::precompile::Precompile;
+use alloy_primitives::{Address, Bytes};
+use foundry_evm::revm::{
+    precompile::Precompile,
+    primitives::{Env, PrecompileResult, StatefulPrecompile},
+};
 use std::{fmt::Debug, sync::Arc};
 
 /// Object-safe trait that enables injecting extra precompiles when using
@@ -9,6 +12,25 @@ pub trait PrecompileFactory: Send + Sync + Unpin + Debug {
     fn precompiles(&self) -> Vec<(Address, Precompile)>;
 }
 
+/// The handler of a mocked precompile, called with the call input.
+pub type MockPrecompileHandler = Arc<dyn Fn(&[u8]) -> PrecompileResult + Send + Sync>;
+
+/// A precompile that forwards every call to a [MockPrecompileHandler].
+#[derive(Clone)]
+pub struct MockPrecompile(pub MockPrecompileHandler);
+
+impl StatefulPrecompile for MockPrecompile {
+    fn call(&self, bytes: &Bytes, _gas_limit: u64, _env: &Env) -> PrecompileResult {
+        (self.0)(bytes)
+    }
+}
+
+impl From<MockPrecompile> for Precompile {
+    fn from(precompile: MockPrecompile) -> Self {
+        Self::Stateful(Arc::new(precompile))
+    }
+}
+
 /// Appends a handler register to `evm` that injects the given `precompiles`.
 ///
 /// This will add an additional handler that extends the default precompiles with the given set of
@@ -21,6 +21,7 @@ pub enum Hardfork {
     Paris,
     Shanghai,
     Cancun,
+    Prague,
     #[default]
     Latest,
 }
@@ -45,6 +46,8 @@ impl Hardfork {
             Self::Paris => 15537394,
             Self::Shanghai => 17034870,
             Self::Cancun | Self::Latest => 19426587,
+            // not yet scheduled on mainnet
+            Self::Prague => u64::MAX,
         }
     }
 }
@@ -72,6 +75,7 @@ impl FromStr for Hardfork {
             "paris" | "merge" | "15" => Self::Paris,
             "shanghai" | "16" => Self::Shanghai,
             "cancun" | "17" => Self::Cancun,
+            "prague" | "18" => Self::Prague,
             "latest" => Self::Latest,
             _ => return Err(format!("Unknown hardfork {s}")),
         };
@@ -99,6 +103,7 @@ impl From<Hardfork> for SpecId {
             Hardfork::Paris => Self::MERGE,
             Hardfork::Shanghai => Self::SHANGHAI,
             Hardfork::Cancun | Hardfork::Latest => Self::CANCUN,
+            Hardfork::Prague => Self::PRAGUE,
         }
     }
 }
@@ -54,7 +54,7 @@ pub use hardfork::Hardfork;
 pub mod eth;
 /// Evm related abstractions
 mod evm;
-pub use evm::{inject_precompiles, PrecompileFactory};
+pub use evm::{inject_precompiles, MockPrecompile, MockPrecompileHandler, PrecompileFactory};
 /// support for polling filters
 pub mod filter;
 /// commandline output
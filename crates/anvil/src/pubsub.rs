@@ -1,5 +1,9 @@
 use crate::{
-    eth::{backend::notifications::NewBlockNotifications, error::to_rpc_result},
+    eth::{
+        backend::notifications::{NewBlockNotifications, NewPendingTransactionNotifications},
+        error::to_rpc_result,
+    },
+    mem::transaction_build,
     StorageInfo,
 };
 use alloy_primitives::{TxHash, B256};
@@ -86,6 +90,7 @@ pub enum EthSubscription {
     Logs(Box<LogsSubscription>),
     Header(NewBlockNotifications, StorageInfo, SubscriptionId),
     PendingTransactions(Receiver<TxHash>, SubscriptionId),
+    FullPendingTransactions(NewPendingTransactionNotifications, SubscriptionId),
 }
 
 impl EthSubscription {
@@ -120,6 +125,29 @@ impl EthSubscription {
                     });
                 Poll::Ready(res)
             }
+            Self::FullPendingTransactions(tx, id) => {
+                let res = ready!(tx.poll_next_unpin(cx))
+                    .map(|tx| {
+                        let from = *tx.pending_transaction.sender();
+                        let mut tx = transaction_build(
+                            Some(tx.hash()),
+                            tx.pending_transaction.transaction.clone(),
+                            None,
+                            None,
+                            None,
+                        );
+                        // the sender of an impersonated transaction can't be recovered from its
+                        // signature
+                        tx.from = from;
+                        SubscriptionResult::FullTransaction(Box::new(tx.inner))
+                    })
+                    .map(to_rpc_result)
+                    .map(|result| {
+                        let params = EthSubscriptionParams { subscription: id.clone(), result };
+                        EthSubscriptionResponse::new(params)
+                    });
+                Poll::Ready(res)
+            }
         }
     }
 }
@@ -59,9 +59,12 @@ impl PubSubEthRpcHandler {
                 ResponseResult::Success(canceled.into())
             }
             EthPubSub::EthSubscribe(kind, params) => {
-                let filter = match *params {
-                    Params::None => None,
-                    Params::Logs(filter) => Some(*filter),
+                let (filter, full_transactions) = match *params {
+                    Params::None => (None, false),
+                    Params::Logs(filter) => (Some(*filter), false),
+                    Params::Bool(full) if kind == SubscriptionKind::NewPendingTransactions => {
+                        (None, full)
+                    }
                     Params::Bool(_) => {
                         return ResponseResult::Error(RpcError::invalid_params(
                             "Expected params for logs subscription",
@@ -91,10 +94,17 @@ impl PubSubEthRpcHandler {
                     }
                     SubscriptionKind::NewPendingTransactions => {
                         trace!(target: "rpc::ws", "received pending transactions subscription");
-                        EthSubscription::PendingTransactions(
-                            self.api.new_ready_transactions(),
-                            id.clone(),
-                        )
+                        if full_transactions {
+                            EthSubscription::FullPendingTransactions(
+                                self.api.new_pending_transaction_notifications(),
+                                id.clone(),
+                            )
+                        } else {
+                            EthSubscription::PendingTransactions(
+                                self.api.new_ready_transactions(),
+                                id.clone(),
+                            )
+                        }
                     }
                     SubscriptionKind::Syncing => {
                         return RpcError::internal_error_with("Not implemented").into()
@@ -20,6 +20,13 @@ sol!(
     "test-data/multicall.json"
 );
 
+sol!(
+    #[allow(clippy::too_many_arguments)]
+    #[sol(rpc)]
+    MockERC20,
+    "test-data/ERC20.json"
+);
+
 sol!(
     #[sol(rpc)]
     contract BUSD {
@@ -5,19 +5,34 @@ use crate::{
     fork::fork_config,
     utils::http_provider_with_signer,
 };
+use alloy_eips::eip2935::HISTORY_STORAGE_ADDRESS;
 use alloy_network::{EthereumWallet, TransactionBuilder};
-use alloy_primitives::{address, fixed_bytes, Address, U256};
+use alloy_primitives::{address, fixed_bytes, keccak256, Address, Bytes, B256, U256};
 use alloy_provider::{ext::TxPoolApi, Provider};
 use alloy_rpc_types::{
     anvil::{ForkedNetwork, Forking, Metadata, NodeEnvironment, NodeForkConfig, NodeInfo},
     BlockId, BlockNumberOrTag, TransactionRequest,
 };
 use alloy_serde::WithOtherFields;
-use anvil::{eth::api::CLIENT_VERSION, spawn, Hardfork, NodeConfig};
-use anvil_core::eth::EthRequest;
-use foundry_evm::revm::primitives::SpecId;
+use alloy_sol_types::SolValue;
+use anvil::{
+    eth::{
+        api::CLIENT_VERSION,
+        backend::{
+            db::FullAccount,
+            mem::{BlockRequest, PrevRandao, EIP7702_DELEGATION_DESIGNATOR},
+        },
+        error::BlockchainError,
+    },
+    spawn, Hardfork, NodeConfig,
+};
+use anvil_core::{eth::EthRequest, types::ImpersonatedAccounts};
+use foundry_common::ens::{namehash, reverse_address};
+use foundry_evm::revm::primitives::{PrecompileOutput, SpecId};
 use std::{
+    collections::{BTreeMap, HashMap},
     str::FromStr,
+    sync::Arc,
     time::{Duration, SystemTime},
 };
 
@@ -141,6 +156,89 @@ async fn can_auto_impersonate_account() {
     assert!(api.accounts().unwrap().contains(&impersonate));
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_impersonated_accounts() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+
+    let state = api.anvil_impersonated_accounts().unwrap();
+    assert!(state.accounts.is_empty());
+    assert!(!state.auto_impersonate);
+
+    let mut accounts = vec![Address::random(), Address::random()];
+    accounts.sort();
+    for account in &accounts {
+        api.anvil_impersonate_account(*account).await.unwrap();
+    }
+    api.anvil_auto_impersonate_account(true).await.unwrap();
+
+    let state: ImpersonatedAccounts =
+        handle.http_provider().raw_request("anvil_impersonatedAccounts".into(), ()).await.unwrap();
+    assert_eq!(state.accounts, accounts);
+    assert!(state.auto_impersonate);
+
+    api.anvil_stop_impersonating_account(accounts[0]).await.unwrap();
+    api.anvil_auto_impersonate_account(false).await.unwrap();
+
+    let state = api.anvil_impersonated_accounts().unwrap();
+    assert_eq!(state.accounts, vec![accounts[1]]);
+    assert!(!state.auto_impersonate);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_impersonate_account_for_blocks() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+
+    let provider = handle.http_provider();
+
+    let impersonate = Address::random();
+    let to = Address::random();
+    let val = U256::from(1337);
+    api.anvil_set_balance(impersonate, U256::from(1e18 as u64)).await.unwrap();
+
+    let tx = TransactionRequest::default().with_from(impersonate).with_to(to).with_value(val);
+    let tx = WithOtherFields::new(tx);
+
+    api.backend.impersonate_for_blocks(impersonate, 2).await.unwrap();
+    assert!(api.accounts().unwrap().contains(&impersonate));
+
+    provider.send_transaction(tx.clone()).await.unwrap().get_receipt().await.unwrap();
+    assert!(api.is_impersonated(impersonate));
+
+    api.mine_one().await;
+    assert!(!api.is_impersonated(impersonate));
+    assert!(!api.accounts().unwrap().contains(&impersonate));
+
+    let res = provider.send_transaction(tx).await;
+    res.unwrap_err();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn skips_pending_tx_after_impersonation_expired() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+
+    let provider = handle.http_provider();
+
+    let impersonate = Address::random();
+    let to = Address::random();
+    api.anvil_set_balance(impersonate, U256::from(1e18 as u64)).await.unwrap();
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    api.backend.impersonate_for(impersonate, Duration::from_millis(200)).await.unwrap();
+
+    let tx =
+        TransactionRequest::default().with_from(impersonate).with_to(to).with_value(U256::from(1));
+    let tx = WithOtherFields::new(tx);
+    let _ = provider.send_transaction(tx).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!api.is_impersonated(impersonate));
+
+    api.mine_one().await;
+    let block = api.block_by_number(BlockNumberOrTag::Latest).await.unwrap().unwrap();
+    assert!(block.transactions.is_empty());
+    assert_eq!(provider.get_balance(to).await.unwrap(), U256::ZERO);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_impersonate_contract() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -574,6 +672,116 @@ async fn test_set_chain_id() {
     assert_eq!(chain_id, 1234);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_replace_account() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let wallet = handle.dev_wallets().next().unwrap();
+    let provider = http_provider_with_signer(&handle.http_endpoint(), wallet.into());
+
+    let greeter = Greeter::deploy(&provider, "Hello World!".to_string()).await.unwrap();
+    let addr = *greeter.address();
+    assert_ne!(api.storage_at(addr, U256::ZERO, None).await.unwrap(), B256::ZERO);
+
+    let account = FullAccount {
+        balance: U256::from(1337),
+        nonce: 7,
+        code: Bytes::from_static(&[0x60, 0x00]),
+        storage: BTreeMap::from([(U256::from(5), U256::from(42))]),
+    };
+    api.backend.replace_account(addr, account.clone()).await.unwrap();
+
+    assert_eq!(api.balance(addr, None).await.unwrap(), account.balance);
+    assert_eq!(api.transaction_count(addr, None).await.unwrap(), U256::from(account.nonce));
+    assert_eq!(api.get_code(addr, None).await.unwrap(), account.code);
+    assert_eq!(api.storage_at(addr, U256::ZERO, None).await.unwrap(), B256::ZERO);
+    assert_eq!(
+        api.storage_at(addr, U256::from(5), None).await.unwrap(),
+        B256::from(U256::from(42))
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_account_storage() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let wallet = handle.dev_wallets().next().unwrap();
+    let provider = http_provider_with_signer(&handle.http_endpoint(), wallet.into());
+
+    let greeter = Greeter::deploy(&provider, "Hello World!".to_string()).await.unwrap();
+    let addr = *greeter.address();
+    let greeting = api.storage_at(addr, U256::ZERO, None).await.unwrap();
+    assert_ne!(greeting, B256::ZERO);
+
+    let storage: HashMap<_, _> =
+        (1..20u64).map(|slot| (U256::from(slot), B256::from(U256::from(slot * 2)))).collect();
+    api.backend.set_account_storage(addr, storage.clone(), false).await.unwrap();
+    for (slot, val) in &storage {
+        assert_eq!(api.storage_at(addr, *slot, None).await.unwrap(), *val);
+    }
+    assert_eq!(api.storage_at(addr, U256::ZERO, None).await.unwrap(), greeting);
+
+    // existing slots are wiped
+    let storage = HashMap::from([(U256::from(42), B256::with_last_byte(1))]);
+    api.backend.set_account_storage(addr, storage, true).await.unwrap();
+    assert_eq!(api.storage_at(addr, U256::from(42), None).await.unwrap(), B256::with_last_byte(1));
+    assert_eq!(api.storage_at(addr, U256::from(1), None).await.unwrap(), B256::ZERO);
+    assert_eq!(api.storage_at(addr, U256::ZERO, None).await.unwrap(), B256::ZERO);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_revert_snapshot_repeatedly() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let addr = Address::random();
+
+    api.anvil_set_balance(addr, U256::from(1)).await.unwrap();
+    let snapshot_id = api.backend.create_snapshot().await;
+
+    for i in 2..5 {
+        api.anvil_set_balance(addr, U256::from(i)).await.unwrap();
+        api.mine_one().await;
+        assert!(api.backend.revert_snapshot_keep(snapshot_id).await.unwrap());
+        assert_eq!(api.balance(addr, None).await.unwrap(), U256::from(1));
+        assert_eq!(api.block_number().unwrap(), U256::ZERO);
+        assert!(api.backend.list_snapshots().contains_key(&snapshot_id));
+    }
+
+    // a regular revert discards the snapshot
+    assert!(api.backend.revert_snapshot(snapshot_id).await.unwrap());
+    assert!(!api.backend.revert_snapshot_keep(snapshot_id).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_revert_named_snapshots() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let addr = Address::random();
+
+    api.anvil_set_balance(addr, U256::from(1)).await.unwrap();
+    let before_migration = api.backend.create_named_snapshot("before_migration".into()).await;
+
+    api.anvil_set_balance(addr, U256::from(2)).await.unwrap();
+    api.mine_one().await;
+    let after_seed = api.backend.create_named_snapshot("after_seed".into()).await;
+
+    assert_eq!(
+        api.backend.list_named_snapshots().into_iter().collect::<Vec<_>>(),
+        vec![
+            ("after_seed".to_string(), after_seed),
+            ("before_migration".to_string(), before_migration)
+        ]
+    );
+
+    api.anvil_set_balance(addr, U256::from(3)).await.unwrap();
+    assert!(api.backend.revert_named_snapshot("after_seed").await.unwrap());
+    assert_eq!(api.balance(addr, None).await.unwrap(), U256::from(2));
+    assert_eq!(api.block_number().unwrap(), U256::from(1));
+
+    assert!(api.backend.revert_named_snapshot("before_migration").await.unwrap());
+    assert_eq!(api.balance(addr, None).await.unwrap(), U256::from(1));
+    assert_eq!(api.block_number().unwrap(), U256::ZERO);
+
+    assert!(api.backend.list_named_snapshots().is_empty());
+    assert!(!api.backend.revert_named_snapshot("after_seed").await.unwrap());
+}
+
 // <https://github.com/foundry-rs/foundry/issues/6096>
 #[tokio::test(flavor = "multi_thread")]
 async fn test_fork_revert_next_block_timestamp() {
@@ -629,6 +837,26 @@ async fn test_fork_revert_call_latest_block_timestamp() {
     assert_eq!(coinbase, latest_block.header.miner);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_rotate_coinbase() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let (configured, first, second) = (Address::random(), Address::random(), Address::random());
+    api.anvil_set_coinbase(configured).await.unwrap();
+
+    api.backend.set_coinbase_schedule(vec![first, second]);
+    for expected in [first, second, first] {
+        api.mine_one().await;
+        assert_eq!(api.backend.best_block().unwrap().header.beneficiary, expected);
+        assert_eq!(api.backend.coinbase(), expected);
+    }
+
+    // an empty schedule falls back to the configured coinbase
+    api.backend.set_coinbase_schedule(vec![]);
+    assert_eq!(api.backend.coinbase(), configured);
+    api.mine_one().await;
+    assert_eq!(api.backend.best_block().unwrap().header.beneficiary, configured);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_remove_pool_transactions() {
     let (api, handle) =
@@ -656,3 +884,605 @@ async fn can_remove_pool_transactions() {
     let final_txs = provider.txpool_inspect().await.unwrap();
     assert_eq!(final_txs.pending.len(), 0);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_prevrandao() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let prevrandao = B256::random();
+    api.backend.set_prevrandao(Some(prevrandao));
+    for _ in 0..2 {
+        api.mine_one().await;
+        let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+        assert_eq!(block.header.mix_hash, Some(prevrandao));
+    }
+
+    let seed = B256::random();
+    api.backend.set_prevrandao_mode(PrevRandao::Seeded(seed));
+    assert_eq!(api.backend.prevrandao_mode(), PrevRandao::Seeded(seed));
+    api.mine_one().await;
+    let first = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    api.mine_one().await;
+    let second = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_ne!(first.header.mix_hash, second.header.mix_hash);
+    assert_eq!(first.header.mix_hash, Some(PrevRandao::Seeded(seed).for_block(U256::from(3))));
+    assert_eq!(second.header.mix_hash, Some(PrevRandao::Seeded(seed).for_block(U256::from(4))));
+
+    api.backend.set_prevrandao(None);
+    assert_eq!(api.backend.prevrandao_mode(), PrevRandao::Random);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_rewind() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+    let to = Address::random();
+
+    let mut hashes = Vec::new();
+    for _ in 0..3 {
+        let tx =
+            TransactionRequest::default().with_from(from).with_to(to).with_value(U256::from(1));
+        let receipt = provider
+            .send_transaction(WithOtherFields::new(tx))
+            .await
+            .unwrap()
+            .get_receipt()
+            .await
+            .unwrap();
+        hashes.push(receipt.transaction_hash);
+    }
+    assert_eq!(api.block_number().unwrap(), U256::from(3));
+    let block = provider.get_block(BlockId::number(1), false.into()).await.unwrap().unwrap();
+
+    api.anvil_rewind(2).await.unwrap();
+
+    assert_eq!(api.block_number().unwrap(), U256::from(1));
+    assert_eq!(api.backend.best_hash(), block.header.hash.unwrap());
+    assert_eq!(provider.get_balance(to).await.unwrap(), U256::from(1));
+    assert_eq!(provider.get_transaction_count(from).await.unwrap(), 1);
+    assert!(provider.get_transaction_receipt(hashes[0]).await.unwrap().is_some());
+    assert!(provider.get_transaction_receipt(hashes[1]).await.unwrap().is_none());
+    assert!(provider.get_block(BlockId::number(2), false.into()).await.unwrap().is_none());
+
+    // the chain continues from the rewound block
+    api.mine_one().await;
+    let new_block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(new_block.header.number, Some(2));
+    assert_eq!(new_block.header.parent_hash, block.header.hash.unwrap());
+
+    // can't rewind past genesis
+    assert!(api.anvil_rewind(3).await.is_err());
+    assert_eq!(api.block_number().unwrap(), U256::from(2));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_rewind_block_env() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    api.anvil_mine(Some(U256::from(2)), None).await.unwrap();
+    let block = provider.get_block(BlockId::number(1), false.into()).await.unwrap().unwrap();
+    let base_fee = api.backend.base_fee();
+    let blob_excess_gas_and_price = api.backend.excess_blob_gas_and_price();
+    api.anvil_mine(Some(U256::from(2)), None).await.unwrap();
+
+    api.anvil_rewind(2).await.unwrap();
+
+    // PUSH1 1 BLOCKHASH PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let code =
+        Bytes::from_static(&[0x60, 0x01, 0x40, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+    let tx = TransactionRequest::default().with_input(code);
+    let output = api.call(WithOtherFields::new(tx), None, None).await.unwrap();
+    assert_eq!(B256::from_slice(&output), block.header.hash.unwrap());
+
+    assert_eq!(api.backend.base_fee(), base_fee);
+    assert_eq!(api.backend.excess_blob_gas_and_price(), blob_excess_gas_and_price);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_prune_states_with_transactions() {
+    let (api, handle) = spawn(
+        NodeConfig::test()
+            .with_transaction_block_keeper(Some(2usize))
+            .with_prune_states_with_transactions(true),
+    )
+    .await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+    let to = Address::random();
+
+    for _ in 0..5 {
+        let tx =
+            TransactionRequest::default().with_from(from).with_to(to).with_value(U256::from(1));
+        provider
+            .send_transaction(WithOtherFields::new(tx))
+            .await
+            .unwrap()
+            .get_receipt()
+            .await
+            .unwrap();
+    }
+    assert_eq!(api.block_number().unwrap(), U256::from(5));
+
+    // only the states of the blocks within the keeper window are retained
+    let balance = provider.get_balance(to).block_id(BlockId::number(4)).await.unwrap();
+    assert_eq!(balance, U256::from(4));
+    assert!(provider.get_balance(to).block_id(BlockId::number(3)).await.is_err());
+
+    // rolling back is only possible within the retained window
+    api.anvil_rewind(1).await.unwrap();
+    assert_eq!(api.block_number().unwrap(), U256::from(4));
+    assert_eq!(provider.get_balance(to).await.unwrap(), U256::from(4));
+    let err = api.anvil_rewind(1).await.unwrap_err();
+    assert!(err.to_string().contains("no longer retained"), "{err}");
+    assert_eq!(api.block_number().unwrap(), U256::from(4));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_report_pruned_historical_states() {
+    let (api, _handle) = spawn(NodeConfig::test().set_pruned_history(Some(Some(2)))).await;
+    let addr = Address::random();
+    api.anvil_mine(Some(U256::from(5)), None).await.unwrap();
+
+    let err = api.backend.get_balance(addr, Some(BlockRequest::Number(1))).await.unwrap_err();
+    assert!(matches!(err, BlockchainError::HistoricalStatePruned(1)), "{err}");
+    let err = api.backend.get_code(addr, Some(BlockRequest::Number(1))).await.unwrap_err();
+    assert!(matches!(err, BlockchainError::HistoricalStatePruned(1)), "{err}");
+    let err =
+        api.backend.storage_at(addr, U256::ZERO, Some(BlockRequest::Number(1))).await.unwrap_err();
+    assert!(matches!(err, BlockchainError::HistoricalStatePruned(1)), "{err}");
+    let err = api
+        .backend
+        .prove_account_at(addr, vec![], Some(BlockRequest::Number(1)))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, BlockchainError::HistoricalStatePruned(1)), "{err}");
+
+    // the most recent states are still retained
+    api.backend.get_balance(addr, Some(BlockRequest::Number(4))).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_oldest_historical_block() {
+    let (api, _handle) = spawn(NodeConfig::test().set_pruned_history(Some(Some(2)))).await;
+    assert_eq!(api.backend.oldest_historical_block(), 0);
+
+    api.anvil_mine(Some(U256::from(5)), None).await.unwrap();
+    let oldest = api.backend.oldest_historical_block();
+    assert_eq!(oldest, 3);
+    let addr = Address::random();
+    api.backend.get_balance(addr, Some(BlockRequest::Number(oldest))).await.unwrap();
+    let err =
+        api.backend.get_balance(addr, Some(BlockRequest::Number(oldest - 1))).await.unwrap_err();
+    assert!(matches!(err, BlockchainError::HistoricalStatePruned(_)), "{err}");
+
+    // every state is retained in archive mode
+    let (api, _handle) =
+        spawn(NodeConfig::test().set_pruned_history(Some(Some(2))).with_archive_mode(true)).await;
+    api.anvil_mine(Some(U256::from(5)), None).await.unwrap();
+    assert_eq!(api.backend.oldest_historical_block(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_canonical_chain() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    api.anvil_mine(Some(U256::from(5)), None).await.unwrap();
+
+    // `to` is clamped to the best block
+    let chain = api.backend.canonical_chain(2, 100);
+    assert_eq!(chain.len(), 4);
+    for (number, hash) in chain {
+        let block = provider.get_block(number.into(), false.into()).await.unwrap().unwrap();
+        assert_eq!(block.header.hash, Some(hash));
+    }
+
+    assert_eq!(api.backend.canonical_chain(0, 5).len(), 6);
+    assert!(api.backend.canonical_chain(6, 10).is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_mine_one_and_n_blocks() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let tx = TransactionRequest::default()
+        .with_from(wallet.address())
+        .with_to(Address::random())
+        .with_value(U256::from(1));
+    let tx = WithOtherFields::new(tx);
+    let pending = provider.send_transaction(tx).await.unwrap();
+
+    // the api includes the ready transactions of the pool
+    let outcome = api.mine_one().await;
+    assert_eq!(outcome.block_number.to::<u64>(), 1);
+    assert_eq!(outcome.included.len(), 1);
+    assert_eq!(outcome.included[0].hash(), *pending.tx_hash());
+    let block = provider.get_block(BlockId::number(1), false.into()).await.unwrap().unwrap();
+    assert_eq!(block.header.hash, Some(outcome.block_hash));
+
+    let outcome = api.backend.mine_one().await;
+    assert_eq!(outcome.block_number.to::<u64>(), 2);
+    assert!(outcome.included.is_empty());
+
+    api.evm_set_block_timestamp_interval(10).unwrap();
+    let outcomes = api.backend.mine_n(3).await;
+    assert_eq!(
+        outcomes.iter().map(|outcome| outcome.block_number.to::<u64>()).collect::<Vec<_>>(),
+        vec![3, 4, 5]
+    );
+    let mut timestamps = Vec::new();
+    for outcome in &outcomes {
+        let block = api.backend.get_block(outcome.block_hash).unwrap();
+        timestamps.push(block.header.timestamp);
+    }
+    assert_eq!(timestamps[1], timestamps[0] + 10);
+    assert_eq!(timestamps[2], timestamps[1] + 10);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_query_any_past_block_in_archive_mode() {
+    let (api, handle) =
+        spawn(NodeConfig::test().set_pruned_history(Some(Some(2))).with_archive_mode(true)).await;
+    let provider = handle.http_provider();
+    let addr = Address::random();
+    let slot = U256::ZERO;
+
+    api.anvil_set_code(addr, Bytes::from_static(&[0x00])).await.unwrap();
+    api.anvil_set_storage_at(addr, slot, B256::with_last_byte(1)).await.unwrap();
+    api.anvil_mine(Some(U256::from(2)), None).await.unwrap();
+
+    api.anvil_set_code(addr, Bytes::from_static(&[0x01])).await.unwrap();
+    api.anvil_set_storage_at(addr, slot, B256::with_last_byte(2)).await.unwrap();
+    api.anvil_mine(Some(U256::from(5)), None).await.unwrap();
+    assert_eq!(api.block_number().unwrap(), U256::from(7));
+
+    // the pruning limit is ignored, the state of every block is retained
+    let code = provider.get_code_at(addr).block_id(BlockId::number(1)).await.unwrap();
+    assert_eq!(code, Bytes::from_static(&[0x00]));
+    let value = provider.get_storage_at(addr, slot).block_id(BlockId::number(1)).await.unwrap();
+    assert_eq!(value, U256::from(1));
+    let value = provider.get_storage_at(addr, slot).block_id(BlockId::number(6)).await.unwrap();
+    assert_eq!(value, U256::from(2));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_block_difficulty() {
+    let (api, handle) = spawn(NodeConfig::test().with_hardfork(Some(Hardfork::London))).await;
+    let provider = handle.http_provider();
+
+    let difficulty = U256::from(1000);
+    api.backend.set_block_difficulty(difficulty);
+    assert_eq!(api.backend.block_difficulty(), Some(difficulty));
+
+    for number in 1..=3u64 {
+        api.mine_one().await;
+        let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+        assert_eq!(block.header.difficulty, difficulty);
+        assert_eq!(block.header.total_difficulty, Some(difficulty * U256::from(number)));
+    }
+
+    // `block.difficulty` in calls: DIFFICULTY PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let target = Address::random();
+    api.anvil_set_code(
+        target,
+        Bytes::from_static(&[0x44, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]),
+    )
+    .await
+    .unwrap();
+    let tx = TransactionRequest::default().with_to(target);
+    let out = api.call(WithOtherFields::new(tx), None, None).await.unwrap();
+    assert_eq!(U256::from_be_slice(&out), difficulty);
+
+    // ignored after the merge
+    let (api, _handle) = spawn(NodeConfig::test().with_hardfork(Some(Hardfork::Paris))).await;
+    api.backend.set_block_difficulty(difficulty);
+    assert_eq!(api.backend.block_difficulty(), None);
+    api.mine_one().await;
+    assert_eq!(api.backend.total_difficulty(), U256::ZERO);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_read_block_hashes_from_history_contract() {
+    let (api, handle) = spawn(NodeConfig::test().with_hardfork(Some(Hardfork::Prague))).await;
+    let provider = handle.http_provider();
+
+    for _ in 0..3 {
+        api.mine_one().await;
+    }
+
+    let history_call = |number: u64| {
+        let tx = TransactionRequest::default()
+            .with_to(HISTORY_STORAGE_ADDRESS)
+            .with_input(U256::from(number).abi_encode());
+        WithOtherFields::new(tx)
+    };
+
+    for number in 0..3u64 {
+        let block =
+            provider.get_block(BlockId::number(number), false.into()).await.unwrap().unwrap();
+        let out = api.call(history_call(number), None, None).await.unwrap();
+        assert_eq!(B256::from_slice(&out), block.header.hash.unwrap());
+    }
+    // not yet stored
+    let out = api.call(history_call(3), None, None).await.unwrap();
+    assert_eq!(B256::from_slice(&out), B256::ZERO);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_resolve_reverse() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+
+    let address = Address::random();
+    let registry = Address::random();
+    let resolver = Address::random();
+
+    // registry without any records
+    // `resolver(node)`: PUSH1 4 CALLDATALOAD SLOAD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    api.anvil_set_code(
+        registry,
+        Bytes::from_static(&[
+            0x60, 0x04, 0x35, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ]),
+    )
+    .await
+    .unwrap();
+    assert_eq!(api.backend.resolve_reverse(address, registry).await.unwrap(), None);
+
+    // `name(node)` returns the ABI-encoded name appended to the runtime code:
+    // PUSH1 len PUSH1 12 PUSH1 0 CODECOPY PUSH1 len PUSH1 0 RETURN
+    let name = ("alice.eth".to_string(),).abi_encode_params();
+    let len = name.len() as u8;
+    let mut code = vec![0x60, len, 0x60, 0x0c, 0x60, 0x00, 0x39, 0x60, len, 0x60, 0x00, 0xf3];
+    code.extend(name);
+    api.anvil_set_code(resolver, code.into()).await.unwrap();
+
+    let node = namehash(&reverse_address(&address));
+    api.anvil_set_storage_at(registry, node.into(), resolver.into_word()).await.unwrap();
+    assert_eq!(
+        api.backend.resolve_reverse(address, registry).await.unwrap(),
+        Some("alice.eth".to_string())
+    );
+
+    // other addresses still have no reverse record
+    assert_eq!(api.backend.resolve_reverse(Address::random(), registry).await.unwrap(), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_mock_precompile() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let ecrecover = address!("0000000000000000000000000000000000000001");
+    let signer = Address::random();
+
+    // forwards the calldata to ecrecover and returns the output:
+    // CALLDATASIZE PUSH0 PUSH0 CALLDATACOPY
+    // PUSH1 32 PUSH0 CALLDATASIZE PUSH0 PUSH1 1 GAS STATICCALL POP
+    // PUSH1 32 PUSH0 RETURN
+    let target = Address::random();
+    api.anvil_set_code(
+        target,
+        Bytes::from_static(&[
+            0x36, 0x5f, 0x5f, 0x37, 0x60, 0x20, 0x5f, 0x36, 0x5f, 0x60, 0x01, 0x5a, 0xfa, 0x50,
+            0x60, 0x20, 0x5f, 0xf3,
+        ]),
+    )
+    .await
+    .unwrap();
+    let tx = WithOtherFields::new(TransactionRequest::default().with_to(target));
+
+    // the built-in ecrecover returns nothing for invalid input
+    let out = api.call(tx.clone(), None, None).await.unwrap();
+    assert_eq!(out, Bytes::from(B256::ZERO.to_vec()));
+
+    api.backend.mock_precompile(
+        ecrecover,
+        Arc::new(move |_input| {
+            Ok(PrecompileOutput { gas_used: 3000, bytes: signer.into_word().to_vec().into() })
+        }),
+    );
+    let out = api.call(tx, None, None).await.unwrap();
+    assert_eq!(Address::from_word(B256::from_slice(&out)), signer);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_accounts_in_batch() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let addrs: Vec<_> = (0..100).map(|_| Address::random()).collect();
+
+    api.backend
+        .set_balances(addrs.iter().enumerate().map(|(i, a)| (*a, U256::from(i + 1))).collect())
+        .await
+        .unwrap();
+    api.backend
+        .set_nonces(addrs.iter().enumerate().map(|(i, a)| (*a, U256::from(i))).collect())
+        .await
+        .unwrap();
+    api.backend
+        .set_codes(addrs.iter().map(|a| (*a, Bytes::from_static(&[0x00]))).collect())
+        .await
+        .unwrap();
+
+    for (i, addr) in addrs.into_iter().enumerate() {
+        assert_eq!(provider.get_balance(addr).await.unwrap(), U256::from(i + 1));
+        assert_eq!(provider.get_transaction_count(addr).await.unwrap(), i as u64);
+        assert_eq!(provider.get_code_at(addr).await.unwrap(), Bytes::from_static(&[0x00]));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_account_storage() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let addr = Address::random();
+    assert!(api.backend.account_storage(addr, None).await.unwrap().is_empty());
+
+    api.anvil_set_storage_at(addr, U256::from(1), B256::with_last_byte(5)).await.unwrap();
+    api.anvil_set_storage_at(addr, U256::from(2), B256::with_last_byte(7)).await.unwrap();
+    api.mine_one().await;
+    api.mine_one().await;
+    api.anvil_set_storage_at(addr, U256::from(1), B256::ZERO).await.unwrap();
+    api.mine_one().await;
+
+    let storage = api.backend.account_storage(addr, None).await.unwrap();
+    assert_eq!(storage.into_iter().collect::<Vec<_>>(), vec![(U256::from(2), U256::from(7))]);
+
+    let storage = api.backend.account_storage(addr, Some(BlockRequest::Number(1))).await.unwrap();
+    assert_eq!(
+        storage.into_iter().collect::<BTreeMap<_, _>>(),
+        BTreeMap::from([(U256::from(1), U256::from(5)), (U256::from(2), U256::from(7))])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_storage_range() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let addr = Address::random();
+
+    let range = api.backend.storage_range_at(addr, B256::ZERO, 10, None).await.unwrap();
+    assert!(range.storage.is_empty());
+    assert_eq!(range.next_key, None);
+
+    for slot in 1..=5u64 {
+        api.anvil_set_storage_at(addr, U256::from(slot), B256::with_last_byte(slot as u8))
+            .await
+            .unwrap();
+    }
+    api.mine_one().await;
+
+    let mut hashed =
+        (1..=5u64).map(|slot| keccak256(B256::from(U256::from(slot)))).collect::<Vec<_>>();
+    hashed.sort();
+
+    // walk the storage in pages of two
+    let mut start = B256::ZERO;
+    let mut seen = Vec::new();
+    loop {
+        let range = api.backend.storage_range_at(addr, start, 2, None).await.unwrap();
+        assert!(range.storage.len() <= 2);
+        for (key, entry) in &range.storage {
+            assert_eq!(*key, keccak256(entry.key));
+            assert_eq!(U256::from_be_bytes(entry.value.0), U256::from_be_bytes(entry.key.0));
+        }
+        seen.extend(range.storage.into_keys());
+        match range.next_key {
+            Some(next) => start = next,
+            None => break,
+        }
+    }
+    assert_eq!(seen, hashed);
+
+    let range = api.backend.storage_range_at(addr, hashed[4], 2, None).await.unwrap();
+    assert_eq!(range.storage.keys().copied().collect::<Vec<_>>(), vec![hashed[4]]);
+    assert_eq!(range.next_key, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_transactions_touching_slot() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+
+    // PUSH1 0 CALLDATALOAD PUSH1 0 SSTORE STOP
+    let writer = Address::random();
+    api.anvil_set_code(writer, Bytes::from_static(&[0x60, 0x00, 0x35, 0x60, 0x00, 0x55, 0x00]))
+        .await
+        .unwrap();
+    // PUSH1 1 SLOAD POP STOP
+    let reader = Address::random();
+    api.anvil_set_code(reader, Bytes::from_static(&[0x60, 0x01, 0x54, 0x50, 0x00])).await.unwrap();
+    let start = provider.get_block_number().await.unwrap();
+
+    let send = |to: Address, input: Bytes| {
+        let provider = provider.clone();
+        async move {
+            let tx = TransactionRequest::default().with_from(from).with_to(to).with_input(input);
+            provider
+                .send_transaction(WithOtherFields::new(tx))
+                .await
+                .unwrap()
+                .get_receipt()
+                .await
+                .unwrap()
+                .transaction_hash
+        }
+    };
+    let first = send(writer, B256::with_last_byte(1).into()).await;
+    let _ = send(reader, Bytes::new()).await;
+    let _ = send(Address::random(), Bytes::new()).await;
+    let second = send(writer, B256::with_last_byte(2).into()).await;
+    let best = provider.get_block_number().await.unwrap();
+
+    let hashes =
+        api.backend.transactions_touching_slot(writer, U256::ZERO, start, best).await.unwrap();
+    assert_eq!(hashes, vec![first, second]);
+    let hashes =
+        api.backend.transactions_touching_slot(writer, U256::ZERO, start + 2, best).await.unwrap();
+    assert_eq!(hashes, vec![second]);
+    let hashes =
+        api.backend.transactions_touching_slot(reader, U256::from(1), start, best).await.unwrap();
+    assert_eq!(hashes.len(), 1);
+    assert!(api
+        .backend
+        .transactions_touching_slot(writer, U256::from(1), start, best)
+        .await
+        .unwrap()
+        .is_empty());
+
+    assert!(api.backend.transactions_touching_slot(writer, U256::ZERO, 0, best + 1).await.is_err());
+    assert!(api.backend.transactions_touching_slot(writer, U256::ZERO, best, 0).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_delegation() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let (account, target) = (Address::random(), Address::random());
+    assert_eq!(api.backend.get_delegation(account, None).await.unwrap(), None);
+
+    let mut code = EIP7702_DELEGATION_DESIGNATOR.to_vec();
+    code.extend_from_slice(target.as_slice());
+    api.anvil_set_code(account, code.into()).await.unwrap();
+    assert_eq!(api.backend.get_delegation(account, None).await.unwrap(), Some(target));
+
+    // regular code is not a delegation
+    api.anvil_set_code(account, Bytes::from_static(&[0x00])).await.unwrap();
+    assert_eq!(api.backend.get_delegation(account, None).await.unwrap(), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_mine_deterministically() {
+    let mine = || async {
+        let config = NodeConfig::test().with_genesis_timestamp(Some(1_700_000_000u64));
+        let (api, handle) = spawn(config).await;
+        api.backend.set_deterministic_mining(true);
+        let provider = handle.http_provider();
+        let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+        for value in 1..=3u64 {
+            let tx = TransactionRequest::default()
+                .with_from(accounts[0])
+                .with_to(accounts[1])
+                .with_value(U256::from(value));
+            provider
+                .send_transaction(WithOtherFields::new(tx))
+                .await
+                .unwrap()
+                .watch()
+                .await
+                .unwrap();
+        }
+        api.mine_one().await;
+
+        let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+        assert_eq!(block.header.number, Some(4));
+        assert_eq!(block.header.timestamp, 1_700_000_004);
+        block.header.hash.unwrap()
+    };
+
+    let hash = mine().await;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(mine().await, hash);
+}
@@ -1,19 +1,37 @@
 //! general eth api tests
 
 use crate::{
-    abi::{MulticallContract, SimpleStorage},
+    abi::{MockERC20, MulticallContract, SimpleStorage},
     utils::{connect_pubsub_with_wallet, http_provider_with_signer},
 };
 use alloy_network::{EthereumWallet, TransactionBuilder};
-use alloy_primitives::{Address, ChainId, B256, U256};
+use alloy_primitives::{Address, Bytes, ChainId, B256, U256};
 use alloy_provider::Provider;
 use alloy_rpc_types::{
-    request::TransactionRequest, state::AccountOverride, BlockId, BlockNumberOrTag,
-    BlockTransactions,
+    request::TransactionRequest, state::AccountOverride, AccessList, AccessListItem, BlockId,
+    BlockNumberOrTag, BlockTransactions, Withdrawal,
 };
 use alloy_serde::WithOtherFields;
-use anvil::{eth::api::CLIENT_VERSION, spawn, NodeConfig, CHAIN_ID};
-use std::{collections::HashMap, time::Duration};
+use anvil::{
+    eth::{
+        api::{TxStatus, CLIENT_VERSION},
+        backend::mem::inspector::CustomInspector,
+        error::{BlockchainError, InvalidTransactionError},
+        fees::FeeDetails,
+    },
+    spawn, NodeConfig, CHAIN_ID,
+};
+use anvil_core::eth::trie::ordered_trie_root;
+use foundry_evm::revm::interpreter::{CallInputs, Interpreter};
+use futures::StreamExt;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn can_get_block_number() {
@@ -125,6 +143,555 @@ async fn can_get_block_by_number() {
     assert_eq!(block.transactions.len(), 1);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_best_block() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let block = api.backend.best_block().unwrap();
+    assert_eq!(block.header.number, 0);
+    assert_eq!(block.header.hash_slow(), api.backend.best_hash());
+
+    api.mine_one().await;
+    api.mine_one().await;
+
+    let block = api.backend.best_block().unwrap();
+    let latest = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(block.header.number, 2);
+    assert_eq!(block.header.hash_slow(), api.backend.best_hash());
+    assert_eq!(Some(block.header.hash_slow()), latest.header.hash);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_chain_tip() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+    let tx = TransactionRequest::default().with_from(accounts[0]).with_to(accounts[1]);
+    let tx = WithOtherFields::new(tx);
+    provider.send_transaction(tx).await.unwrap().get_receipt().await.unwrap();
+
+    let tip = api.backend.chain_tip();
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(tip.number, 1);
+    assert_eq!(Some(tip.number), block.header.number);
+    assert_eq!(Some(tip.hash), block.header.hash);
+    assert_eq!(tip.parent_hash, block.header.parent_hash);
+    assert_eq!(tip.timestamp, block.header.timestamp);
+    assert_eq!(Some(tip.base_fee), block.header.base_fee_per_gas);
+    assert_eq!(tip.gas_used, block.header.gas_used);
+    assert!(tip.gas_used > 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_blocks_since_last_activity() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+    assert_eq!(api.backend.blocks_since_last_activity(accounts[0]), None);
+
+    let tx = TransactionRequest::default().with_from(accounts[0]).with_to(accounts[1]);
+    let tx = WithOtherFields::new(tx);
+    provider.send_transaction(tx).await.unwrap().get_receipt().await.unwrap();
+
+    assert_eq!(api.backend.blocks_since_last_activity(accounts[0]), Some(0));
+    assert_eq!(api.backend.blocks_since_last_activity(accounts[1]), Some(0));
+    assert_eq!(api.backend.blocks_since_last_activity(accounts[2]), None);
+
+    let snapshot = api.evm_snapshot().await.unwrap();
+    api.mine_one().await;
+    api.mine_one().await;
+    assert_eq!(api.backend.blocks_since_last_activity(accounts[0]), Some(2));
+
+    let tx = TransactionRequest::default().with_from(accounts[2]).with_to(accounts[0]);
+    let tx = WithOtherFields::new(tx);
+    provider.send_transaction(tx).await.unwrap().get_receipt().await.unwrap();
+    assert_eq!(api.backend.blocks_since_last_activity(accounts[0]), Some(0));
+    assert_eq!(api.backend.blocks_since_last_activity(accounts[2]), Some(0));
+
+    // reverting restores the previous activity
+    assert!(api.evm_revert(snapshot).await.unwrap());
+    assert_eq!(api.backend.blocks_since_last_activity(accounts[0]), Some(0));
+    assert_eq!(api.backend.blocks_since_last_activity(accounts[2]), None);
+    api.mine_one().await;
+    assert_eq!(api.backend.blocks_since_last_activity(accounts[0]), Some(1));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_accounts_changed_since() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+    let (set, coinbase) = (Address::random(), api.backend.env().read().block.coinbase);
+
+    api.mine_one().await;
+    assert!(api.backend.accounts_changed_since(1).unwrap().is_empty());
+
+    let tx = TransactionRequest::default().with_from(accounts[0]).with_to(accounts[1]);
+    provider.send_transaction(WithOtherFields::new(tx)).await.unwrap().get_receipt().await.unwrap();
+    let mut expected = vec![accounts[0], accounts[1], coinbase];
+    expected.sort();
+    assert_eq!(api.backend.accounts_changed_since(1).unwrap(), expected);
+    assert!(api.backend.accounts_changed_since(2).unwrap().is_empty());
+
+    // directly modified accounts are included before and after they're mined
+    api.anvil_set_balance(set, U256::from(1)).await.unwrap();
+    assert_eq!(api.backend.accounts_changed_since(2).unwrap(), vec![set]);
+    api.mine_one().await;
+    assert_eq!(api.backend.accounts_changed_since(2).unwrap(), vec![set]);
+    assert!(api.backend.accounts_changed_since(3).unwrap().is_empty());
+
+    let err = api.backend.accounts_changed_since(4).unwrap_err();
+    assert!(matches!(err, BlockchainError::BlockNotFound));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_uncles_of_mined_blocks() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    api.mine_one().await;
+
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    let hash = block.header.hash.unwrap();
+    assert_eq!(provider.get_uncle_count(hash.into()).await.unwrap(), 0);
+    assert_eq!(provider.get_uncle_count(BlockId::number(1)).await.unwrap(), 0);
+    assert!(api.uncle_by_block_hash_and_index(hash, 0.into()).await.unwrap().is_none());
+    let uncle = api.uncle_by_block_number_and_index(BlockNumberOrTag::Number(1), 0.into());
+    assert!(uncle.await.unwrap().is_none());
+
+    // missing blocks
+    let err = api.backend.uncle_count(B256::random()).await.unwrap_err();
+    assert!(matches!(err, BlockchainError::BlockNotFound), "{err}");
+    let err = api.backend.uncle_by_index(BlockNumberOrTag::Number(2), 0).await.unwrap_err();
+    assert!(matches!(err, BlockchainError::BlockOutOfRange(1, 2)), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_queue_withdrawals() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let recipient = Address::random();
+
+    let withdrawals = vec![
+        Withdrawal { index: 0, validator_index: 1, address: recipient, amount: 2 },
+        Withdrawal { index: 1, validator_index: 2, address: recipient, amount: 3 },
+    ];
+    api.backend.queue_withdrawals(withdrawals.clone());
+    api.mine_one().await;
+
+    let balance = provider.get_balance(recipient).await.unwrap();
+    assert_eq!(balance, U256::from(5_000_000_000u64));
+
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(block.withdrawals, Some(withdrawals.clone()));
+    let root = ordered_trie_root(withdrawals.iter().map(alloy_rlp::encode));
+    assert_eq!(block.header.withdrawals_root, Some(root));
+
+    // withdrawals are only included once
+    api.mine_one().await;
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(block.withdrawals, None);
+    assert_eq!(provider.get_balance(recipient).await.unwrap(), balance);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_custom_create2_deployer() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let deployer = Address::random();
+    assert!(!api.backend.has_create2_deployer(deployer).await.unwrap());
+
+    let code = Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3]);
+    api.backend.set_create2_deployer_code(deployer, code.clone()).await.unwrap();
+    assert!(api.backend.has_create2_deployer(deployer).await.unwrap());
+    assert_eq!(api.get_code(deployer, None).await.unwrap(), code);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_mining_throughput() {
+    // fits exactly 4 transfers per block
+    let (api, handle) = spawn(NodeConfig::test().with_gas_limit(Some(4 * 21_000))).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+    assert_eq!(api.backend.mining_throughput(10).unwrap(), 0.0);
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    for _ in 0..12 {
+        let tx = TransactionRequest::default()
+            .with_from(accounts[0])
+            .with_to(accounts[1])
+            .with_gas_limit(21_000);
+        let _ = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+    }
+
+    // mine 3 full blocks, 2 seconds apart
+    let start = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    for i in 1..=3 {
+        api.evm_set_next_block_timestamp(start.header.timestamp + 2 * i).unwrap();
+        api.mine_one().await;
+    }
+    assert_eq!(api.backend.best_block().unwrap().transactions.len(), 4);
+
+    let tps = api.backend.mining_throughput(3).unwrap();
+    assert!((tps - 2.0).abs() < f64::EPSILON, "{tps}");
+    // the window is capped at the chain length
+    assert!((api.backend.mining_throughput(100).unwrap() - 2.0).abs() < f64::EPSILON);
+    assert!((api.backend.mining_throughput(1).unwrap() - 2.0).abs() < f64::EPSILON);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_next_block_timestamp_once() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let interval = 10;
+    api.evm_set_block_timestamp_interval(interval).unwrap();
+    api.mine_one().await;
+    let parent = api.backend.best_block().unwrap().header.timestamp;
+
+    let err = api.backend.set_next_block_timestamp(parent).unwrap_err();
+    assert!(matches!(err, BlockchainError::TimestampError(_)));
+
+    let next = parent + 1_000;
+    api.backend.set_next_block_timestamp(next).unwrap();
+    api.mine_one().await;
+    assert_eq!(api.backend.best_block().unwrap().header.timestamp, next);
+
+    // mining continues with the interval afterwards
+    api.mine_one().await;
+    assert_eq!(api.backend.best_block().unwrap().header.timestamp, next + interval);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_total_chain_size() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+    let genesis = api.backend.best_block().unwrap();
+    assert_eq!(api.backend.total_chain_size(), alloy_rlp::encode(&genesis).len() as u64);
+
+    let snapshot = api.evm_snapshot().await.unwrap();
+    let mut total = api.backend.total_chain_size();
+    for _ in 0..3 {
+        let tx = TransactionRequest::default().with_from(accounts[0]).with_to(accounts[1]);
+        provider.send_transaction(WithOtherFields::new(tx)).await.unwrap().watch().await.unwrap();
+        let block = api.backend.best_block().unwrap();
+        assert_eq!(block.transactions.len(), 1);
+        total += alloy_rlp::encode(&block).len() as u64;
+        assert_eq!(api.backend.total_chain_size(), total);
+    }
+
+    // reverted blocks no longer count
+    assert!(api.evm_revert(snapshot).await.unwrap());
+    assert_eq!(api.backend.total_chain_size(), alloy_rlp::encode(&genesis).len() as u64);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_create_access_list_with_error() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let from = handle.dev_wallets().next().unwrap().address();
+    let other = Address::random();
+
+    // PUSH20 other BALANCE POP, followed by STOP or PUSH1 0 PUSH1 0 REVERT
+    let code = |end: &[u8]| {
+        let mut code = vec![0x73];
+        code.extend_from_slice(other.as_slice());
+        code.extend_from_slice(&[0x31, 0x50]);
+        code.extend_from_slice(end);
+        Bytes::from(code)
+    };
+    let (ok, reverts) = (Address::random(), Address::random());
+    api.anvil_set_code(ok, code(&[0x00])).await.unwrap();
+    api.anvil_set_code(reverts, code(&[0x60, 0x00, 0x60, 0x00, 0xfd])).await.unwrap();
+
+    let expected = AccessList(vec![AccessListItem { address: other, storage_keys: vec![] }]);
+    let request = |to| WithOtherFields::new(TransactionRequest::default().from(from).to(to));
+
+    let result = api.create_access_list_with_error(request(ok), None).await.unwrap();
+    assert_eq!(result.access_list, expected);
+    assert_eq!(result.error, None);
+
+    // the gas is estimated with the access list applied
+    let mut with_access_list = request(ok);
+    with_access_list.access_list = Some(expected.clone());
+    let estimate = api.estimate_gas(with_access_list.clone(), None, None).await.unwrap();
+    assert_eq!(U256::from(result.gas_used), estimate);
+
+    // while `eth_createAccessList` returns the gas used by executing it
+    let with_gas_used = api.create_access_list(request(ok), None).await.unwrap();
+    let gas_used =
+        api.backend.call(with_access_list, FeeDetails::zero(), None, None).await.unwrap().2;
+    assert_eq!(with_gas_used.gas_used, U256::from(gas_used));
+
+    // reverts still return the accounts accessed until the revert
+    api.create_access_list(request(reverts), None).await.unwrap_err();
+    let result = api.create_access_list_with_error(request(reverts), None).await.unwrap();
+    assert_eq!(result.access_list, expected);
+    assert!(result.error.unwrap().contains("execution reverted"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_estimate_gas_on_backend() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let from = handle.dev_wallets().next().unwrap().address();
+    let other = Address::random();
+
+    // PUSH20 other BALANCE POP, followed by STOP or PUSH1 0 PUSH1 0 REVERT
+    let code = |end: &[u8]| {
+        let mut code = vec![0x73];
+        code.extend_from_slice(other.as_slice());
+        code.extend_from_slice(&[0x31, 0x50]);
+        code.extend_from_slice(end);
+        Bytes::from(code)
+    };
+    let (ok, reverts) = (Address::random(), Address::random());
+    api.anvil_set_code(ok, code(&[0x00])).await.unwrap();
+    api.anvil_set_code(reverts, code(&[0x60, 0x00, 0x60, 0x00, 0xfd])).await.unwrap();
+    let request = |to| WithOtherFields::new(TransactionRequest::default().from(from).to(to));
+
+    let gas = api.backend.estimate_gas(request(ok), FeeDetails::zero(), None, None).await.unwrap();
+    assert_eq!(U256::from(gas), api.estimate_gas(request(ok), None, None).await.unwrap());
+
+    // warming `other` via the access list is cheaper than the cold `BALANCE`
+    let (refined, access_list) = api
+        .backend
+        .estimate_gas_with_access_list(request(ok), FeeDetails::zero(), None, None)
+        .await
+        .unwrap();
+    assert!(refined < gas);
+    assert_eq!(
+        access_list,
+        Some(AccessList(vec![AccessListItem { address: other, storage_keys: vec![] }]))
+    );
+
+    let err = api
+        .backend
+        .estimate_gas(request(reverts), FeeDetails::zero(), None, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, BlockchainError::InvalidTransaction(InvalidTransactionError::Revert(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_abort_calls_after_timeout() {
+    let config = NodeConfig::test()
+        .disable_block_gas_limit(true)
+        .with_call_timeout(Some(Duration::from_millis(100)));
+    let (api, _handle) = spawn(config).await;
+
+    // JUMPDEST PUSH0 JUMP
+    let spinner = Address::random();
+    api.anvil_set_code(spinner, Bytes::from_static(&[0x5b, 0x5f, 0x56])).await.unwrap();
+    let request = WithOtherFields::new(
+        TransactionRequest::default().to(spinner).gas_limit(u64::MAX as u128 / 2),
+    );
+
+    let err = api.call(request.clone(), None, None).await.unwrap_err();
+    assert!(err.to_string().contains("execution aborted"), "{err}");
+    let err = api.debug_trace_call(request, None, Default::default()).await.unwrap_err();
+    assert!(err.to_string().contains("execution aborted"), "{err}");
+
+    // calls that finish in time are unaffected
+    let stopper = Address::random();
+    api.anvil_set_code(stopper, Bytes::from_static(&[0x00])).await.unwrap();
+    let request = WithOtherFields::new(TransactionRequest::default().to(stopper));
+    api.call(request, None, None).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_transaction_status() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+    assert_eq!(api.transaction_status(B256::random()), TxStatus::Unknown);
+
+    // PUSH1 0 PUSH1 0 REVERT
+    let reverter = Address::random();
+    api.anvil_set_code(reverter, Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xfd]))
+        .await
+        .unwrap();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let mut hashes = vec![];
+    for to in [accounts[1], reverter] {
+        let tx = TransactionRequest::default()
+            .with_from(accounts[0])
+            .with_to(to)
+            .with_gas_limit(100_000);
+        let tx = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+        hashes.push(*tx.tx_hash());
+    }
+    assert_eq!(api.transaction_status(hashes[0]), TxStatus::Pending);
+    assert_eq!(api.transaction_status(hashes[1]), TxStatus::Pending);
+
+    api.mine_one().await;
+    assert_eq!(
+        api.transaction_status(hashes[0]),
+        TxStatus::Mined { block: 1, index: 0, success: true }
+    );
+    assert_eq!(
+        api.transaction_status(hashes[1]),
+        TxStatus::Mined { block: 1, index: 1, success: false }
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_new_block_outcome_notifications() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let mut notifications = api.backend.new_block_outcome_notifications();
+
+    for to in &accounts[1..3] {
+        let tx = TransactionRequest::default().with_from(accounts[0]).with_to(*to);
+        let _ = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+    }
+    api.mine_one().await;
+
+    let notification = notifications.next().await.unwrap();
+    assert_eq!(notification.hash, api.backend.best_hash());
+    assert_eq!(notification.header.number, 1);
+    assert_eq!(notification.included_count(), 2);
+    assert_eq!(notification.invalid_count(), 0);
+    assert_eq!(notification.gas_used(), 42_000);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_invoke_callback_on_block_mined() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+    let mined = Arc::new(Mutex::new(Vec::new()));
+    let recorded = mined.clone();
+    api.backend.set_on_block_mined(Arc::new(move |outcome| {
+        let hashes: Vec<_> = outcome.included.iter().map(|tx| tx.hash()).collect();
+        recorded.lock().unwrap().push((outcome.block_number.to::<u64>(), hashes));
+    }));
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let mut hashes = vec![];
+    for to in &accounts[1..3] {
+        let tx = TransactionRequest::default().with_from(accounts[0]).with_to(*to);
+        hashes.push(*provider.send_transaction(WithOtherFields::new(tx)).await.unwrap().tx_hash());
+    }
+    api.mine_one().await;
+    api.mine_one().await;
+
+    assert_eq!(*mined.lock().unwrap(), vec![(1, hashes), (2, vec![])]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_reject_transactions_with_custom_validator() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+    let blocked = accounts[1];
+    api.backend.set_transaction_validator(Some(Arc::new(move |tx, _account| {
+        if *tx.sender() == blocked {
+            return Err("sender is blocked".to_string())
+        }
+        Ok(())
+    })));
+
+    let tx = |from| WithOtherFields::new(TransactionRequest::default().from(from).to(accounts[2]));
+    let err = provider.send_transaction(tx(blocked)).await.unwrap_err();
+    assert!(err.to_string().contains("sender is blocked"), "{err}");
+    provider.send_transaction(tx(accounts[0])).await.unwrap().get_receipt().await.unwrap();
+
+    api.backend.set_transaction_validator(None);
+    provider.send_transaction(tx(blocked)).await.unwrap().get_receipt().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_custom_inspector() {
+    #[derive(Debug, Default)]
+    struct Counter {
+        calls: AtomicUsize,
+        steps: AtomicUsize,
+    }
+
+    impl CustomInspector for Counter {
+        fn step(&self, _interp: &Interpreter) {
+            self.steps.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn call(&self, _inputs: &CallInputs) {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+    let target = Address::random();
+    // PUSH1 0 PUSH1 0 RETURN
+    api.anvil_set_code(target, Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3])).await.unwrap();
+
+    let counter = Arc::new(Counter::default());
+    api.backend.set_custom_inspector(Some(counter.clone()));
+
+    // mined transactions, the transfer doesn't execute any code
+    let tx = TransactionRequest::default().from(accounts[0]).to(accounts[1]);
+    provider.send_transaction(WithOtherFields::new(tx)).await.unwrap().get_receipt().await.unwrap();
+    let calls = counter.calls.load(Ordering::Relaxed);
+    assert!(calls >= 1);
+    assert_eq!(counter.steps.load(Ordering::Relaxed), 0);
+
+    // calls
+    let tx = WithOtherFields::new(TransactionRequest::default().from(accounts[0]).to(target));
+    provider.call(&tx).await.unwrap();
+    assert_eq!(counter.calls.load(Ordering::Relaxed), calls + 1);
+    assert_eq!(counter.steps.load(Ordering::Relaxed), 3);
+
+    api.backend.set_custom_inspector(None);
+    provider.call(&tx).await.unwrap();
+    assert_eq!(counter.calls.load(Ordering::Relaxed), calls + 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_mapping_storage_at() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let wallet = handle.dev_wallets().next().unwrap();
+    let owner = wallet.address();
+    let spender = Address::random();
+    let provider = http_provider_with_signer(&handle.http_endpoint(), wallet.into());
+
+    let token =
+        MockERC20::deploy(&provider, "Token".to_string(), "TKN".to_string(), 18).await.unwrap();
+    token.mint(owner, U256::from(1337)).send().await.unwrap().get_receipt().await.unwrap();
+    token.approve(spender, U256::from(42)).send().await.unwrap().get_receipt().await.unwrap();
+
+    // solmate's `ERC20` stores `balanceOf` at slot 3 and `allowance` at slot 4
+    let balance = token.balanceOf(owner).call().await.unwrap()._0;
+    let stored = api
+        .backend
+        .mapping_storage_at(*token.address(), U256::from(3), owner.into_word(), None)
+        .await
+        .unwrap();
+    assert_eq!(U256::from_be_bytes(stored.0), balance);
+    assert_eq!(balance, U256::from(1337));
+
+    let allowance = token.allowance(owner, spender).call().await.unwrap()._0;
+    let stored = api
+        .backend
+        .nested_mapping_storage_at(
+            *token.address(),
+            U256::from(4),
+            &[owner.into_word(), spender.into_word()],
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(U256::from_be_bytes(stored.0), allowance);
+    assert_eq!(allowance, U256::from(42));
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_get_pending_block() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -2,11 +2,12 @@ use crate::utils::http_provider;
 use alloy_consensus::{SidecarBuilder, SimpleCoder};
 use alloy_eips::eip4844::{DATA_GAS_PER_BLOB, MAX_DATA_GAS_PER_BLOCK};
 use alloy_network::TransactionBuilder;
-use alloy_primitives::U256;
+use alloy_primitives::{hex, Address, Bytes, U256};
 use alloy_provider::Provider;
 use alloy_rpc_types::{BlockId, TransactionRequest};
 use alloy_serde::WithOtherFields;
-use anvil::{spawn, Hardfork, NodeConfig};
+use anvil::{eth::fees::BlobParams, spawn, Hardfork, NodeConfig};
+use foundry_evm::revm::primitives::SpecId;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn can_send_eip4844_transaction() {
@@ -119,6 +120,63 @@ async fn cannot_exceed_six_blobs() {
     assert!(err.to_string().contains("too many blobs"));
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_blob_params() {
+    let node_config = NodeConfig::test().with_hardfork(Some(Hardfork::Cancun));
+    let (api, handle) = spawn(node_config).await;
+
+    let wallets = handle.dev_wallets().collect::<Vec<_>>();
+
+    let from = wallets[0].address();
+    let to = wallets[1].address();
+
+    let provider = http_provider(&handle.http_endpoint());
+
+    let params = BlobParams { max_blob_count: 2, target_blob_count: 1, ..Default::default() };
+    api.backend.set_blob_params(params);
+    assert_eq!(api.backend.blob_params(), params);
+
+    let eip1559_est = provider.estimate_eip1559_fees(None).await.unwrap();
+    let gas_price = provider.get_gas_price().await.unwrap();
+
+    let data = vec![1u8; DATA_GAS_PER_BLOB as usize * 2];
+    let sidecar: SidecarBuilder<SimpleCoder> = SidecarBuilder::from_slice(&data);
+    let num_blobs = sidecar.clone().take().len();
+    assert!(num_blobs > 2);
+
+    let tx = TransactionRequest::default()
+        .with_from(from)
+        .with_to(to)
+        .with_nonce(0)
+        .with_max_fee_per_blob_gas(gas_price + 1)
+        .with_max_fee_per_gas(eip1559_est.max_fee_per_gas)
+        .with_max_priority_fee_per_gas(eip1559_est.max_priority_fee_per_gas)
+        .with_blob_sidecar(sidecar.build().unwrap());
+    let mut tx = WithOtherFields::new(tx);
+    tx.populate_blob_hashes();
+
+    // exceeds the custom max blob count
+    let err = provider.send_transaction(tx.clone()).await.unwrap_err();
+    assert!(err.to_string().contains("too many blobs"));
+
+    let sidecar: SidecarBuilder<SimpleCoder> = SidecarBuilder::from_slice(b"Hello World");
+    tx.set_blob_sidecar(sidecar.build().unwrap());
+    tx.populate_blob_hashes();
+
+    let receipt = provider.send_transaction(tx).await.unwrap().get_receipt().await.unwrap();
+    let block = provider
+        .get_block_by_number(receipt.block_number.unwrap().into(), false)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(block.header.blob_gas_used, Some(DATA_GAS_PER_BLOB as u128));
+
+    // a single blob hits the custom target, so the excess blob gas doesn't change
+    api.mine_one().await;
+    let next = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(next.header.excess_blob_gas, block.header.excess_blob_gas);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_mine_blobs_when_exceeds_max_blobs() {
     let node_config = NodeConfig::test().with_hardfork(Some(Hardfork::Cancun));
@@ -204,3 +262,82 @@ async fn can_check_blob_fields_on_genesis() {
     assert_eq!(block.header.blob_gas_used, Some(0));
     assert_eq!(block.header.excess_blob_gas, Some(0));
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_load_blob_sidecars_from_state() {
+    let node_config = NodeConfig::test().with_hardfork(Some(Hardfork::Cancun));
+    let (api, handle) = spawn(node_config).await;
+
+    let wallets = handle.dev_wallets().collect::<Vec<_>>();
+    let from = wallets[0].address();
+    let to = wallets[1].address();
+    let provider = http_provider(&handle.http_endpoint());
+
+    let eip1559_est = provider.estimate_eip1559_fees(None).await.unwrap();
+    let gas_price = provider.get_gas_price().await.unwrap();
+
+    let sidecar: SidecarBuilder<SimpleCoder> = SidecarBuilder::from_slice(b"Hello World");
+    let sidecar = sidecar.build().unwrap();
+    let versioned_hash = sidecar.versioned_hashes().next().unwrap();
+    let blobs = sidecar.blobs.clone();
+
+    let tx = TransactionRequest::default()
+        .with_from(from)
+        .with_to(to)
+        .with_nonce(0)
+        .with_max_fee_per_blob_gas(gas_price + 1)
+        .with_max_fee_per_gas(eip1559_est.max_fee_per_gas)
+        .with_max_priority_fee_per_gas(eip1559_est.max_priority_fee_per_gas)
+        .with_blob_sidecar(sidecar);
+    let mut tx = WithOtherFields::new(tx);
+    tx.populate_blob_hashes();
+
+    let receipt = provider.send_transaction(tx).await.unwrap().get_receipt().await.unwrap();
+    let tx_hash = receipt.transaction_hash;
+    assert_eq!(api.backend.get_blob_by_tx_hash(tx_hash), Some(blobs.clone()));
+
+    let state = api.anvil_dump_state().await.unwrap();
+
+    let (api, handle) = spawn(NodeConfig::test().with_hardfork(Some(Hardfork::Cancun))).await;
+    api.anvil_load_state(state).await.unwrap();
+
+    // the mined transaction is restored with the block
+    let provider = http_provider(&handle.http_endpoint());
+    let restored = provider.get_transaction_receipt(tx_hash).await.unwrap().unwrap();
+    assert_eq!(restored.block_hash, receipt.block_hash);
+    assert_eq!(api.backend.get_blob_by_tx_hash(tx_hash), Some(blobs.clone()));
+    assert!(api.backend.get_blob_by_versioned_hash(versioned_hash).is_some_and(|b| b == blobs[0]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_activate_cancun_at_timestamp() {
+    let activation = 2_000_000_000;
+    let node_config = NodeConfig::test()
+        .with_hardfork(Some(Hardfork::Shanghai))
+        .with_hardfork_activations([(activation, Hardfork::Cancun)]);
+    let (api, handle) = spawn(node_config).await;
+    let provider = http_provider(&handle.http_endpoint());
+
+    // returns the blob base fee: BLOBBASEFEE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let target = Address::random();
+    api.anvil_set_code(target, Bytes::from(hex!("4a60005260206000f3"))).await.unwrap();
+    let call = WithOtherFields::new(TransactionRequest::default().to(target));
+
+    api.evm_set_next_block_timestamp(activation - 1).unwrap();
+    api.mine_one().await;
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(block.header.excess_blob_gas, None);
+    assert_eq!(api.backend.spec_id(), SpecId::SHANGHAI);
+    assert!(provider.call(&call).await.is_err());
+
+    api.evm_set_next_block_timestamp(activation).unwrap();
+    api.mine_one().await;
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert!(block.header.excess_blob_gas.is_some());
+    assert_eq!(api.backend.spec_id(), SpecId::CANCUN);
+    provider.call(&call).await.unwrap();
+
+    // calls at historic blocks use the hardfork that was active at the block
+    assert!(provider.call(&call).block(BlockId::number(1)).await.is_err());
+    assert_eq!(api.backend.spec_id_at(activation - 1), SpecId::SHANGHAI);
+}
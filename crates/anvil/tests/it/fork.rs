@@ -5,16 +5,17 @@ use crate::{
     utils::{http_provider, http_provider_with_signer},
 };
 use alloy_network::{EthereumWallet, TransactionBuilder};
-use alloy_primitives::{address, bytes, Address, Bytes, TxHash, TxKind, U256};
+use alloy_primitives::{address, bytes, Address, Bytes, TxHash, TxKind, B256, U256};
 use alloy_provider::Provider;
 use alloy_rpc_types::{
     anvil::Forking,
     request::{TransactionInput, TransactionRequest},
-    BlockId, BlockNumberOrTag,
+    BlockId, BlockNumberOrTag, Withdrawal,
 };
 use alloy_serde::WithOtherFields;
 use alloy_signer_local::PrivateKeySigner;
 use anvil::{eth::EthApi, spawn, NodeConfig, NodeHandle};
+use anvil_core::eth::trie::ordered_trie_root;
 use foundry_common::provider::get_http_provider;
 use foundry_config::Config;
 use foundry_test_utils::rpc::{self, next_http_rpc_endpoint};
@@ -1280,3 +1281,117 @@ async fn test_immutable_fork_transaction_hash() {
         assert_eq!(tx.inner.hash.to_string(), expected.0.to_string());
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_finalized_tag_resolves_to_upstream() {
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    origin_api.anvil_mine(Some(U256::from(100)), None).await.unwrap();
+
+    // use a shorter epoch so that the local computation would differ from the forked chain
+    let (fork_api, _fork_handle) = spawn(
+        NodeConfig::test()
+            .with_slots_in_an_epoch(1)
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint())),
+    )
+    .await;
+    assert_eq!(fork_api.block_number().unwrap(), U256::from(100));
+
+    for tag in [BlockNumberOrTag::Safe, BlockNumberOrTag::Finalized] {
+        let upstream = origin_api.block_by_number(tag).await.unwrap().unwrap();
+        let forked = fork_api.block_by_number(tag).await.unwrap().unwrap();
+        assert_eq!(forked.header.hash, upstream.header.hash);
+    }
+    let finalized = fork_api.block_by_number(BlockNumberOrTag::Finalized).await.unwrap().unwrap();
+    assert_eq!(finalized.header.number, Some(100 - 64));
+
+    // locally mined blocks are still resolved locally
+    fork_api.anvil_mine(Some(U256::from(10)), None).await.unwrap();
+    let finalized = fork_api.block_by_number(BlockNumberOrTag::Finalized).await.unwrap().unwrap();
+    assert_eq!(finalized.header.number, Some(108));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_locally_modified_accounts() {
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let (read, balance, storage) = (Address::random(), Address::random(), Address::random());
+    origin_api.anvil_set_balance(read, U256::from(1)).await.unwrap();
+    origin_api.anvil_set_storage_at(read, U256::from(1), B256::with_last_byte(1)).await.unwrap();
+    origin_api.anvil_set_balance(storage, U256::from(1)).await.unwrap();
+    origin_api.evm_mine(None).await.unwrap();
+
+    let (api, _handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(origin_handle.http_endpoint()))).await;
+    assert!(api.backend.locally_modified_accounts().await.is_empty());
+
+    // reading from the fork doesn't modify anything
+    assert_eq!(api.balance(read, None).await.unwrap(), U256::from(1));
+    assert_eq!(api.storage_at(read, U256::from(1), None).await.unwrap(), B256::with_last_byte(1));
+    assert!(api.backend.locally_modified_accounts().await.is_empty());
+
+    api.anvil_set_balance(balance, U256::from(2)).await.unwrap();
+    api.anvil_set_storage_at(storage, U256::from(1), B256::with_last_byte(2)).await.unwrap();
+
+    let mut expected = vec![balance, storage];
+    expected.sort();
+    assert_eq!(api.backend.locally_modified_accounts().await, expected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_prefetch_accounts() {
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let account = Address::random();
+    origin_api.anvil_set_balance(account, U256::from(1)).await.unwrap();
+    origin_api.anvil_set_code(account, bytes!("00")).await.unwrap();
+    origin_api.anvil_set_storage_at(account, U256::from(1), B256::with_last_byte(1)).await.unwrap();
+    origin_api.evm_mine(None).await.unwrap();
+
+    let (api, _handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(origin_handle.http_endpoint()))).await;
+    api.backend.prefetch_accounts(vec![(account, vec![U256::from(1)])]).await.unwrap();
+
+    assert_eq!(api.balance(account, None).await.unwrap(), U256::from(1));
+    assert_eq!(api.get_code(account, None).await.unwrap(), bytes!("00"));
+    assert_eq!(
+        api.storage_at(account, U256::from(1), None).await.unwrap(),
+        B256::with_last_byte(1)
+    );
+    // prefetching doesn't modify any account
+    assert!(api.backend.locally_modified_accounts().await.is_empty());
+
+    // no-op if not forking
+    origin_api.backend.prefetch_accounts(vec![(Address::random(), vec![])]).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_drops_withdrawals_that_cant_be_credited() {
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let snapshot = origin_api.evm_snapshot().await.unwrap();
+    origin_api.mine_one().await;
+    let (api, handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .fork_request_retries(Some(0)),
+    )
+    .await;
+    let provider = handle.http_provider();
+
+    // cached before the forked block is reverted on the forked node, after which fetching
+    // accounts from it fails
+    let credited = Address::random();
+    assert_eq!(provider.get_balance(credited).await.unwrap(), U256::ZERO);
+    assert!(origin_api.evm_revert(snapshot).await.unwrap());
+
+    let withdrawals = vec![
+        Withdrawal { index: 0, validator_index: 1, address: credited, amount: 2 },
+        Withdrawal { index: 1, validator_index: 2, address: Address::random(), amount: 3 },
+    ];
+    api.backend.queue_withdrawals(withdrawals.clone());
+    api.mine_one().await;
+
+    // the withdrawal whose recipient can't be fetched is not included
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(block.withdrawals, Some(withdrawals[..1].to_vec()));
+    let root = ordered_trie_root(withdrawals[..1].iter().map(alloy_rlp::encode));
+    assert_eq!(block.header.withdrawals_root, Some(root));
+    assert_eq!(provider.get_balance(credited).await.unwrap(), U256::from(2_000_000_000u64));
+}
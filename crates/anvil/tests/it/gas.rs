@@ -2,11 +2,20 @@
 
 use crate::utils::http_provider_with_signer;
 use alloy_network::{EthereumWallet, TransactionBuilder};
-use alloy_primitives::{Address, U256};
+use alloy_primitives::{Address, Bytes, B256, U256};
 use alloy_provider::Provider;
-use alloy_rpc_types::{BlockId, TransactionRequest};
+use alloy_rpc_types::{BlockId, BlockNumberOrTag, TransactionRequest};
 use alloy_serde::WithOtherFields;
-use anvil::{eth::fees::INITIAL_BASE_FEE, spawn, NodeConfig};
+use anvil::{
+    eth::{
+        error::{BlockchainError, FeeHistoryError},
+        fees::{FeeManager, INITIAL_BASE_FEE},
+    },
+    spawn, NodeConfig,
+};
+use foundry_evm::revm::primitives::{
+    calc_blob_gasprice, fake_exponential, BlobExcessGasAndPrice, SpecId, MIN_BLOB_GASPRICE,
+};
 
 const GAS_TRANSFER: u128 = 21_000;
 
@@ -129,6 +138,70 @@ async fn test_basefee_empty_block() {
     assert!(next_base_fee < base_fee);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_basefee_custom_gas_target() {
+    let (api, handle) = spawn(NodeConfig::test().with_base_fee(Some(INITIAL_BASE_FEE))).await;
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let signer: EthereumWallet = wallet.clone().into();
+
+    let provider = http_provider_with_signer(&handle.http_endpoint(), signer);
+
+    let tx = TransactionRequest::default().with_to(Address::random()).with_value(U256::from(1337));
+    let tx = WithOtherFields::new(tx);
+
+    let latest_base_fee = || async {
+        provider
+            .get_block(BlockId::latest(), false.into())
+            .await
+            .unwrap()
+            .unwrap()
+            .header
+            .base_fee_per_gas
+            .unwrap()
+    };
+
+    // a transfer exactly hits the target, so the base fee is unchanged even though the block is
+    // far from half full
+    api.backend.set_gas_target(Some(GAS_TRANSFER));
+    provider.send_transaction(tx.clone()).await.unwrap().get_receipt().await.unwrap();
+    let base_fee = latest_base_fee().await;
+    assert_eq!(api.backend.base_fee(), base_fee);
+
+    // half the target, so the base fee decreases by half the max change
+    api.backend.set_gas_target(Some(GAS_TRANSFER * 2));
+    provider.send_transaction(tx.clone()).await.unwrap().get_receipt().await.unwrap();
+    assert_eq!(latest_base_fee().await, base_fee);
+    assert_eq!(api.backend.base_fee(), base_fee - base_fee / 16);
+
+    // back to gas limit / elasticity
+    api.backend.set_gas_target(None);
+    provider.send_transaction(tx.clone()).await.unwrap().get_receipt().await.unwrap();
+    let base_fee = latest_base_fee().await;
+    assert!(api.backend.base_fee() < base_fee - base_fee / 16);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_basefee_floor() {
+    let (api, _handle) = spawn(NodeConfig::test().with_base_fee(Some(INITIAL_BASE_FEE))).await;
+
+    // empty blocks decrease the base fee by the max change
+    api.mine_one().await;
+    assert_eq!(api.backend.base_fee(), INITIAL_BASE_FEE - INITIAL_BASE_FEE / 8);
+
+    let floor = INITIAL_BASE_FEE / 2;
+    api.backend.set_base_fee_floor(Some(floor));
+    for _ in 0..10 {
+        api.mine_one().await;
+        assert!(api.backend.base_fee() >= floor);
+    }
+    assert_eq!(api.backend.base_fee(), floor);
+
+    api.backend.set_base_fee_floor(None);
+    api.mine_one().await;
+    assert!(api.backend.base_fee() < floor);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_respect_base_fee() {
     let base_fee = 50u128;
@@ -150,6 +223,56 @@ async fn test_respect_base_fee() {
     provider.send_transaction(tx.clone()).await.unwrap().get_receipt().await.unwrap();
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_accept_underpriced() {
+    let base_fee = 50u128;
+    let (api, handle) = spawn(NodeConfig::test().with_base_fee(Some(base_fee))).await;
+    let provider = handle.http_provider();
+
+    let tx = TransactionRequest::default().with_to(Address::random()).with_value(U256::from(100));
+    let mut underpriced = WithOtherFields::new(tx);
+    underpriced.set_gas_price(base_fee - 1);
+
+    let err = provider.send_transaction(underpriced.clone()).await.unwrap_err();
+    assert!(err.to_string().contains("max fee per gas less than block base fee"), "{err}");
+
+    api.backend.set_accept_underpriced(true);
+    let receipt =
+        provider.send_transaction(underpriced).await.unwrap().get_receipt().await.unwrap();
+    assert!(receipt.inner.inner.status());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transaction_gas_refund() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    // clears storage slots 0, 1 and 2: (PUSH1 0 PUSH1 slot SSTORE)* STOP
+    let target = Address::random();
+    let code = Bytes::from_static(&[
+        0x60, 0x00, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x01, 0x55, 0x60, 0x00, 0x60, 0x02, 0x55,
+        0x00,
+    ]);
+    api.anvil_set_code(target, code).await.unwrap();
+    for slot in 0..3u64 {
+        api.anvil_set_storage_at(target, U256::from(slot), B256::with_last_byte(1)).await.unwrap();
+    }
+
+    let tx = WithOtherFields::new(TransactionRequest::default().with_to(target));
+    let receipt = provider.send_transaction(tx).await.unwrap().get_receipt().await.unwrap();
+    let refund = api.backend.transaction_gas_refund(receipt.transaction_hash).unwrap();
+    // three EIP-3529 storage clear refunds, which exceed the cap of a fifth of the gas used
+    // before the refund
+    assert_eq!(refund, 3 * 4_800);
+    assert!(refund * 4 > receipt.gas_used as u64);
+
+    let tx = WithOtherFields::new(TransactionRequest::default().with_to(Address::random()));
+    let receipt = provider.send_transaction(tx).await.unwrap().get_receipt().await.unwrap();
+    assert_eq!(api.backend.transaction_gas_refund(receipt.transaction_hash), Some(0));
+
+    assert_eq!(api.backend.transaction_gas_refund(B256::random()), None);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_tip_above_fee_cap() {
     let base_fee = 50u128;
@@ -193,3 +316,203 @@ async fn test_can_use_fee_history() {
         assert!(receipt.inner.inner.is_success());
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_block_avg_priority_fee() {
+    let base_fee = 1_000_000_000u128;
+    let (api, handle) = spawn(NodeConfig::test().with_base_fee(Some(base_fee))).await;
+    let provider = handle.http_provider();
+    let accounts = handle.dev_accounts().collect::<Vec<_>>();
+
+    // empty blocks have no priority fees
+    assert_eq!(api.backend.block_avg_priority_fee(BlockNumberOrTag::Latest).unwrap(), 0);
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    // (tip, calldata) pairs, each non-zero calldata byte costs an extra 16 gas
+    let txs = [(1_000_000_000u128, vec![]), (3_000_000_000u128, vec![1u8; 100])];
+    let mut hashes = Vec::new();
+    for (sender, (tip, input)) in accounts.iter().zip(txs.iter()) {
+        let tx = TransactionRequest::default()
+            .with_from(*sender)
+            .with_to(Address::random())
+            .with_input(input.clone())
+            .with_max_fee_per_gas(base_fee * 10)
+            .with_max_priority_fee_per_gas(*tip);
+        let pending = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+        hashes.push(*pending.tx_hash());
+    }
+    api.mine_one().await;
+
+    let gas_used = [GAS_TRANSFER, GAS_TRANSFER + 100 * 16];
+    for (hash, gas_used) in hashes.iter().zip(gas_used) {
+        let receipt = provider.get_transaction_receipt(*hash).await.unwrap().unwrap();
+        assert_eq!(receipt.block_number, Some(1));
+        assert_eq!(receipt.gas_used, gas_used);
+    }
+
+    let expected = (txs[0].0 * gas_used[0] + txs[1].0 * gas_used[1]) / (gas_used[0] + gas_used[1]);
+    let avg = api.backend.block_avg_priority_fee(BlockNumberOrTag::Number(1)).unwrap();
+    assert_eq!(avg, expected);
+    // more gas was spent with the higher tip, so this is above the simple mean
+    assert!(avg > (txs[0].0 + txs[1].0) / 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_block_priority_fees() {
+    let base_fee = 1_000_000_000u128;
+    let coinbase = Address::random();
+    let (api, handle) = spawn(NodeConfig::test().with_base_fee(Some(base_fee))).await;
+    let provider = handle.http_provider();
+    let accounts = handle.dev_accounts().collect::<Vec<_>>();
+    api.anvil_set_coinbase(coinbase).await.unwrap();
+
+    assert_eq!(api.backend.block_priority_fees(BlockNumberOrTag::Latest), Some(U256::ZERO));
+    assert_eq!(api.backend.block_priority_fees(BlockNumberOrTag::Number(10)), None);
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let tips = [1_000_000_000u128, 3_000_000_000u128];
+    for (sender, tip) in accounts.iter().zip(tips) {
+        let tx = TransactionRequest::default()
+            .with_from(*sender)
+            .with_to(Address::random())
+            .with_max_fee_per_gas(base_fee * 10)
+            .with_max_priority_fee_per_gas(tip);
+        let _ = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+    }
+    api.mine_one().await;
+
+    let fees = api.backend.block_priority_fees(BlockNumberOrTag::Number(1)).unwrap();
+    assert_eq!(fees, U256::from((tips[0] + tips[1]) * GAS_TRANSFER));
+    // the base fee is burned, so the beneficiary receives exactly the priority fees
+    assert_eq!(provider.get_balance(coinbase).await.unwrap(), fees);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_backend_fee_history() {
+    let base_fee = 1_000_000_000u128;
+    let (api, handle) = spawn(NodeConfig::test().with_base_fee(Some(base_fee))).await;
+    let provider = handle.http_provider();
+    let accounts = handle.dev_accounts().collect::<Vec<_>>();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let tips = [2_000_000_000u128, 1_000_000_000, 3_000_000_000];
+    for (sender, tip) in accounts.iter().zip(tips) {
+        let tx = TransactionRequest::default()
+            .with_from(*sender)
+            .with_to(Address::random())
+            .with_max_fee_per_gas(base_fee * 10)
+            .with_max_priority_fee_per_gas(tip);
+        let _ = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+    }
+    api.mine_one().await;
+
+    let history =
+        api.backend.fee_history(2, BlockNumberOrTag::Latest, &[0.0, 50.0, 100.0]).unwrap();
+    let block = api.block_by_number(BlockNumberOrTag::Latest).await.unwrap().unwrap();
+
+    assert_eq!(history.oldest_block, 0);
+    assert_eq!(history.base_fee_per_gas.len(), 3);
+    assert_eq!(history.base_fee_per_gas[1], block.header.base_fee_per_gas.unwrap());
+    assert_eq!(history.base_fee_per_gas[2], api.backend.base_fee());
+    assert_eq!(
+        history.gas_used_ratio,
+        vec![0f64, block.header.gas_used as f64 / block.header.gas_limit as f64]
+    );
+    assert_eq!(history.base_fee_per_blob_gas.len(), 3);
+    assert_eq!(history.blob_gas_used_ratio, vec![0f64, 0f64]);
+    assert_eq!(
+        history.reward,
+        Some(vec![vec![0, 0, 0], vec![1_000_000_000, 2_000_000_000, 3_000_000_000]])
+    );
+
+    let err = api.backend.fee_history(1, BlockNumberOrTag::Latest, &[50.0, 10.0]).unwrap_err();
+    assert!(matches!(err, BlockchainError::FeeHistory(FeeHistoryError::InvalidRewardPercentiles)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_gas_limit_targeting() {
+    let gas_limit = 30_000_000u128;
+    let (api, handle) = spawn(NodeConfig::test().with_gas_limit(Some(gas_limit))).await;
+    let provider = handle.http_provider();
+
+    // fixed by default
+    api.mine_one().await;
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(block.header.gas_limit, gas_limit);
+
+    let target = 30_100_000;
+    api.backend.set_gas_limit_targeting(true, target);
+    let mut parent_gas_limit = gas_limit;
+    for _ in 0..5 {
+        api.mine_one().await;
+        let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+        let expected = (parent_gas_limit + parent_gas_limit / 1024 - 1).min(target as u128);
+        assert_eq!(block.header.gas_limit, expected);
+        parent_gas_limit = expected;
+    }
+    // the target is reached and kept
+    assert_eq!(parent_gas_limit, target as u128);
+
+    // moves back down once the target is lowered, and stays put once disabled
+    api.backend.set_gas_limit_targeting(true, 30_000_000);
+    api.mine_one().await;
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(block.header.gas_limit, parent_gas_limit - (parent_gas_limit / 1024 - 1));
+
+    api.backend.set_gas_limit_targeting(false, 0);
+    api.mine_one().await;
+    let next = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(next.header.gas_limit, block.header.gas_limit);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_gas_limit_target() {
+    let gas_limit = 30_000_000u128;
+    let (api, handle) = spawn(NodeConfig::test().with_gas_limit(Some(gas_limit))).await;
+    let provider = handle.http_provider();
+
+    let target = 29_990_000;
+    api.backend.set_gas_limit_target(Some(target));
+    api.mine_one().await;
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(block.header.gas_limit, target as u128);
+
+    // `None` keeps the gas limit fixed
+    api.backend.set_gas_limit_target(None);
+    api.backend.set_gas_limit(gas_limit);
+    api.mine_one().await;
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(block.header.gas_limit, gas_limit);
+}
+
+#[test]
+fn test_blob_base_fee_update_fraction() {
+    let excess_blob_gas = 10_000_000;
+    let fees = FeeManager::new(
+        SpecId::CANCUN,
+        INITIAL_BASE_FEE,
+        INITIAL_BASE_FEE,
+        BlobExcessGasAndPrice::new(excess_blob_gas),
+    );
+    let default_price = calc_blob_gasprice(excess_blob_gas);
+    assert_eq!(fees.base_fee_per_blob_gas(), default_price);
+
+    // a smaller fraction makes the blob base fee grow faster, and reprices the next block
+    fees.set_blob_base_fee_update_fraction(Some(1_000_000));
+    let price = fake_exponential(MIN_BLOB_GASPRICE, excess_blob_gas, 1_000_000);
+    assert!(price > default_price);
+    assert_eq!(fees.base_fee_per_blob_gas(), price);
+    assert_eq!(fees.get_next_block_blob_base_fee_per_gas(excess_blob_gas as u128), price);
+
+    // new excess blob gas is priced with the custom fraction as well
+    fees.set_blob_excess_gas_and_price(BlobExcessGasAndPrice::new(2 * excess_blob_gas));
+    assert_eq!(
+        fees.base_fee_per_blob_gas(),
+        fake_exponential(MIN_BLOB_GASPRICE, 2 * excess_blob_gas, 1_000_000)
+    );
+
+    fees.set_blob_base_fee_update_fraction(None);
+    assert_eq!(fees.base_fee_per_blob_gas(), calc_blob_gasprice(2 * excess_blob_gas));
+}
@@ -1,10 +1,11 @@
 //! genesis.json tests
 
+use crate::abi::MockERC20;
 use alloy_genesis::Genesis;
-use alloy_primitives::{Address, U256};
+use alloy_primitives::{Address, B256, U256};
 use alloy_provider::Provider;
 use anvil::{spawn, NodeConfig};
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn can_apply_genesis() {
@@ -48,3 +49,58 @@ async fn can_apply_genesis() {
     let expected: U256 = U256::from_str_radix("ffffffffffffffffffffffffff", 16).unwrap();
     assert_eq!(balance, expected);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_genesis_code_from_file() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let tmp = tempfile::tempdir().unwrap();
+    let code = MockERC20::DEPLOYED_BYTECODE.clone();
+
+    let hex_file = tmp.path().join("erc20.hex");
+    std::fs::write(&hex_file, format!("{code}\n")).unwrap();
+    let hex_addr = Address::random();
+    api.backend.set_genesis_code_from_file(hex_addr, &hex_file).await.unwrap();
+    assert_eq!(provider.get_code_at(hex_addr).await.unwrap(), code);
+
+    let bin_file = tmp.path().join("erc20.bin");
+    std::fs::write(&bin_file, &code).unwrap();
+    let bin_addr = Address::random();
+    api.backend.set_genesis_code_from_file(bin_addr, &bin_file).await.unwrap();
+    assert_eq!(provider.get_code_at(bin_addr).await.unwrap(), code);
+
+    assert!(api
+        .backend
+        .set_genesis_code_from_file(Address::random(), &tmp.path().join("missing"))
+        .await
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_export_alloc() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let addr = Address::random();
+    api.anvil_set_balance(addr, U256::from(1337)).await.unwrap();
+    api.anvil_set_nonce(addr, U256::from(7)).await.unwrap();
+    api.anvil_set_code(addr, MockERC20::DEPLOYED_BYTECODE.clone()).await.unwrap();
+    api.anvil_set_storage_at(addr, U256::from(1), B256::with_last_byte(42)).await.unwrap();
+
+    let alloc = api.backend.export_alloc().await.unwrap();
+    let account = &alloc[&addr];
+    assert_eq!(account.balance, U256::from(1337));
+    assert_eq!(account.nonce, Some(7));
+    assert_eq!(account.code, Some(MockERC20::DEPLOYED_BYTECODE.clone()));
+    assert_eq!(
+        account.storage,
+        Some(BTreeMap::from([(B256::from(U256::from(1)), B256::with_last_byte(42))]))
+    );
+
+    // the exported alloc bootstraps another node with the same account
+    let genesis = Genesis::default().extend_accounts(alloc);
+    let (_api, handle) = spawn(NodeConfig::test().with_genesis(Some(genesis))).await;
+    let provider = handle.http_provider();
+    assert_eq!(provider.get_balance(addr).await.unwrap(), U256::from(1337));
+    assert_eq!(provider.get_transaction_count(addr).await.unwrap(), 7);
+    assert_eq!(provider.get_code_at(addr).await.unwrap(), MockERC20::DEPLOYED_BYTECODE);
+    assert_eq!(provider.get_storage_at(addr, U256::from(1)).await.unwrap(), U256::from(42));
+}
@@ -1,13 +1,18 @@
 //! log/event related tests
 
 use crate::{
-    abi::SimpleStorage::{self},
+    abi::{
+        MockERC20,
+        SimpleStorage::{self},
+    },
     utils::{http_provider_with_signer, ws_provider_with_signer},
 };
-use alloy_network::EthereumWallet;
-use alloy_primitives::B256;
+use alloy_network::{EthereumWallet, TransactionBuilder};
+use alloy_primitives::{hex, Address, Bloom, B256, U256};
 use alloy_provider::Provider;
-use alloy_rpc_types::{BlockNumberOrTag, Filter};
+use alloy_rpc_types::{BlockNumberOrTag, Filter, Log, TransactionRequest};
+use alloy_serde::WithOtherFields;
+use alloy_sol_types::SolEvent;
 use anvil::{spawn, NodeConfig};
 use futures::StreamExt;
 
@@ -147,6 +152,97 @@ async fn get_all_events() {
     }
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn get_events_skips_non_matching_blocks() {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let account = wallet.address();
+    let signer: EthereumWallet = wallet.into();
+
+    let provider = http_provider_with_signer(&handle.http_endpoint(), signer);
+
+    let first = SimpleStorage::deploy(&provider, "first".to_string()).await.unwrap();
+    let second = SimpleStorage::deploy(&provider, "second".to_string()).await.unwrap();
+
+    // every call is mined in its own block, only every third block emits from `second`
+    for i in 0..9 {
+        let contract = if i % 3 == 0 { &second } else { &first };
+        let _ = contract
+            .setValue(i.to_string())
+            .from(account)
+            .send()
+            .await
+            .unwrap()
+            .get_receipt()
+            .await
+            .unwrap();
+    }
+
+    let filter = Filter::new().from_block(BlockNumberOrTag::Earliest);
+    let logs = provider.get_logs(&filter.clone().address(*first.address())).await.unwrap();
+    assert_eq!(logs.len(), 7);
+    let logs = provider.get_logs(&filter.clone().address(*second.address())).await.unwrap();
+    assert_eq!(logs.len(), 4);
+    assert!(logs.iter().all(|log| log.address() == *second.address()));
+
+    let logs = provider
+        .get_logs(&filter.clone().event_signature(SimpleStorage::ValueChanged::SIGNATURE_HASH))
+        .await
+        .unwrap();
+    assert_eq!(logs.len(), 11);
+
+    let logs = provider.get_logs(&filter.clone().address(Address::random())).await.unwrap();
+    assert!(logs.is_empty());
+    let logs = provider.get_logs(&filter.event_signature(B256::random())).await.unwrap();
+    assert!(logs.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_event_signatures_in_range() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let owner = wallet.address();
+    let signer: EthereumWallet = wallet.into();
+
+    let provider = http_provider_with_signer(&handle.http_endpoint(), signer);
+
+    let token =
+        MockERC20::deploy(&provider, "Token".to_string(), "TKN".to_string(), 18).await.unwrap();
+    token.mint(owner, U256::from(1337)).send().await.unwrap().get_receipt().await.unwrap();
+    let storage = SimpleStorage::deploy(&provider, "initial value".to_string()).await.unwrap();
+    let start = provider.get_block_number().await.unwrap();
+
+    for _ in 0..3 {
+        let _ = token
+            .transfer(Address::random(), U256::from(100))
+            .send()
+            .await
+            .unwrap()
+            .get_receipt()
+            .await
+            .unwrap();
+    }
+    // empty blocks in between
+    api.anvil_mine(Some(U256::from(5)), None).await.unwrap();
+    let _ = storage.setValue("hi".to_string()).send().await.unwrap().get_receipt().await.unwrap();
+
+    let best = provider.get_block_number().await.unwrap();
+    let signatures = api.backend.event_signatures_in_range(0, best).unwrap();
+    assert_eq!(signatures.len(), 2);
+    assert_eq!(signatures.get(&MockERC20::Transfer::SIGNATURE_HASH), Some(&4));
+    assert_eq!(signatures.get(&SimpleStorage::ValueChanged::SIGNATURE_HASH), Some(&2));
+
+    // the mint's and the constructor's events are not part of the range
+    let signatures = api.backend.event_signatures_in_range(start + 1, best).unwrap();
+    assert_eq!(signatures.get(&MockERC20::Transfer::SIGNATURE_HASH), Some(&3));
+    assert_eq!(signatures.get(&SimpleStorage::ValueChanged::SIGNATURE_HASH), Some(&1));
+
+    assert!(api.backend.event_signatures_in_range(0, best + 1).is_err());
+    assert!(api.backend.event_signatures_in_range(best, 0).is_err());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn watch_events() {
     let (_api, handle) = spawn(NodeConfig::test()).await;
@@ -202,3 +298,82 @@ async fn watch_events() {
         assert_eq!(log.1.block_hash.unwrap(), hash);
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_keep_reverted_logs() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+
+    // emits a `LOG1` with topic 1 and data 42, then reverts
+    let target = Address::random();
+    let code = hex::decode("602a600052600160206000a160006000fd").unwrap();
+    api.anvil_set_code(target, code.into()).await.unwrap();
+
+    let send = || {
+        let tx = TransactionRequest::default().with_from(from).with_to(target);
+        let provider = provider.clone();
+        async move {
+            provider
+                .send_transaction(WithOtherFields::new(tx))
+                .await
+                .unwrap()
+                .get_receipt()
+                .await
+                .unwrap()
+        }
+    };
+
+    assert!(!api.backend.keep_reverted_logs());
+    let receipt = send().await;
+    assert!(!receipt.inner.inner.status());
+    assert!(receipt.inner.inner.logs().is_empty());
+    assert!(receipt.other.get("preRevertLogs").is_none());
+
+    api.backend.set_keep_reverted_logs(true);
+    let receipt = send().await;
+    assert!(!receipt.inner.inner.status());
+    assert!(receipt.inner.inner.logs().is_empty());
+    assert_eq!(receipt.inner.inner.bloom(), Bloom::default());
+
+    let logs = receipt.other.get_deserialized::<Vec<Log>>("preRevertLogs").unwrap().unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].address(), target);
+    assert_eq!(logs[0].topics(), &[B256::with_last_byte(1)]);
+    assert_eq!(logs[0].data().data, B256::with_last_byte(42).0.to_vec());
+    assert_eq!(logs[0].transaction_hash, Some(receipt.transaction_hash));
+    assert_eq!(logs[0].block_number, receipt.block_number);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn block_receipts_have_consecutive_log_indices() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+
+    // emits a `LOG1` with topic 1 and data 42
+    let target = Address::random();
+    let code = hex::decode("602a600052600160206000a100").unwrap();
+    api.anvil_set_code(target, code.into()).await.unwrap();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let mut hashes = vec![];
+    for _ in 0..3 {
+        let tx = TransactionRequest::default().with_from(from).with_to(target);
+        hashes.push(*provider.send_transaction(WithOtherFields::new(tx)).await.unwrap().tx_hash());
+    }
+    api.mine_one().await;
+
+    let receipts = api.block_receipts(BlockNumberOrTag::Latest).await.unwrap().unwrap();
+    assert_eq!(receipts.len(), hashes.len());
+    for (index, (receipt, hash)) in receipts.iter().zip(&hashes).enumerate() {
+        assert_eq!(receipt.transaction_hash, *hash);
+        let logs = &receipt.inner.inner.as_receipt_with_bloom().receipt.logs;
+        let log_indices: Vec<_> = logs.iter().map(|log| log.log_index).collect();
+        assert_eq!(log_indices, vec![Some(index as u64)]);
+
+        // matches the receipt of the single transaction
+        let single = api.transaction_receipt(*hash).await.unwrap().unwrap();
+        assert_eq!(&single.inner.inner.as_receipt_with_bloom().receipt.logs, logs);
+    }
+}
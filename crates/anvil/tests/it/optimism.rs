@@ -3,7 +3,7 @@
 use crate::utils::http_provider_with_signer;
 use alloy_eips::eip2718::Encodable2718;
 use alloy_network::{EthereumWallet, TransactionBuilder};
-use alloy_primitives::{b256, U128, U256};
+use alloy_primitives::{address, b256, U128, U256};
 use alloy_provider::Provider;
 use alloy_rpc_types::{optimism::OptimismTransactionFields, TransactionRequest};
 use alloy_serde::WithOtherFields;
@@ -144,3 +144,45 @@ async fn test_send_value_raw_deposit_transaction() {
     let after_balance_to = provider.get_balance(to).await.unwrap();
     assert_eq!(after_balance_to, before_balance_to + send_value);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_receipt_l1_fee_fields() {
+    let (api, handle) =
+        spawn(NodeConfig::test().with_optimism(true).with_hardfork(Some(Hardfork::Paris))).await;
+    let provider = handle.http_provider();
+
+    // L1 base fee, fee overhead and fee scalar of the `L1Block` predeploy
+    let l1_block = address!("4200000000000000000000000000000000000015");
+    for (slot, value) in [(1u64, 1_000_000_000u64), (5, 188), (6, 684_000)] {
+        api.anvil_set_storage_at(l1_block, U256::from(slot), U256::from(value).into())
+            .await
+            .unwrap();
+    }
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let tx = TransactionRequest::default()
+        .with_from(accounts[0].address())
+        .with_to(accounts[1].address())
+        .with_value(U256::from(1234))
+        .with_gas_limit(21000);
+    let receipt = provider
+        .send_transaction(WithOtherFields::new(tx))
+        .await
+        .unwrap()
+        .get_receipt()
+        .await
+        .unwrap();
+
+    let l1_gas_used = receipt.other.get_deserialized::<U256>("l1GasUsed").unwrap().unwrap();
+    assert!(l1_gas_used > U256::ZERO);
+    assert_eq!(
+        receipt.other.get_deserialized::<U256>("l1GasPrice").unwrap().unwrap(),
+        U256::from(1_000_000_000u64)
+    );
+    assert_eq!(receipt.other.get_deserialized::<String>("l1FeeScalar").unwrap().unwrap(), "0.684");
+    assert_eq!(
+        receipt.other.get_deserialized::<U256>("l1Fee").unwrap().unwrap(),
+        (l1_gas_used + U256::from(188)) * U256::from(1_000_000_000u64) * U256::from(684_000) /
+            U256::from(1_000_000)
+    );
+}
@@ -1,7 +1,18 @@
 //! tests for `eth_getProof`
 
-use alloy_primitives::{address, fixed_bytes, Address, Bytes, B256, U256};
-use anvil::{eth::EthApi, spawn, NodeConfig};
+use alloy_network::TransactionBuilder;
+use alloy_primitives::{address, fixed_bytes, keccak256, Address, Bytes, B256, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types::{BlockNumberOrTag, TransactionRequest};
+use alloy_serde::WithOtherFields;
+use alloy_trie::{HashBuilder, Nibbles};
+use anvil::{
+    eth::{
+        backend::mem::{state, BlockRequest},
+        EthApi,
+    },
+    spawn, NodeConfig,
+};
 use std::{collections::BTreeMap, str::FromStr};
 
 async fn verify_account_proof(
@@ -131,3 +142,182 @@ async fn can_get_random_account_proofs() {
             .unwrap_or_else(|_| panic!("Failed to get proof for {acc:?}"));
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_compute_trie_root() {
+    let target = address!("1ed9b1dd266b607ee278726d324b855a093394a6");
+
+    let (api, _handle) = spawn(NodeConfig::empty_state()).await;
+
+    // root of the empty trie
+    assert_eq!(
+        api.backend.compute_trie_root(Vec::new()),
+        fixed_bytes!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+    );
+
+    let storage: BTreeMap<U256, B256> =
+        serde_json::from_str(include_str!("../../test-data/storage_sample.json")).unwrap();
+    let mut entries = Vec::new();
+    for (key, value) in storage {
+        api.anvil_set_storage_at(target, key, value).await.unwrap();
+        entries
+            .push((B256::from(key), Bytes::from(alloy_rlp::encode(U256::from_be_bytes(value.0)))));
+    }
+
+    let root = api.backend.compute_trie_root(entries.clone());
+    let proof = api.get_proof(target, Vec::new(), None).await.unwrap();
+    assert_eq!(root, proof.storage_hash);
+    // the root node of the storage proofs in `test_storage_proof`
+    let root_node = Bytes::from_str("0xf9019180a0aafd5b14a6edacd149e110ba6776a654f2dbffca340902be933d011113f2750380a0a502c93b1918c4c6534d4593ae03a5a23fa10ebc30ffb7080b297bff2446e42da02eb2bf45fd443bd1df8b6f9c09726a4c6252a0f7896a131a081e39a7f644b38980a0a9cf7f673a0bce76fd40332afe8601542910b48dea44e93933a3e5e930da5d19a0ddf79db0a36d0c8134ba143bcb541cd4795a9a2bae8aca0ba24b8d8963c2a77da0b973ec0f48f710bf79f63688485755cbe87f9d4c68326bb83c26af620802a80ea0f0855349af6bf84afc8bca2eda31c8ef8c5139be1929eeb3da4ba6b68a818cb0a0c271e189aeeb1db5d59d7fe87d7d6327bbe7cfa389619016459196497de3ccdea0e7503ba5799e77aa31bbe1310c312ca17b2c5bcc8fa38f266675e8f154c2516ba09278b846696d37213ab9d20a5eb42b03db3173ce490a2ef3b2f3b3600579fc63a0e9041059114f9c910adeca12dbba1fef79b2e2c8899f2d7213cd22dfe4310561a047c59da56bb2bf348c9dd2a2e8f5538a92b904b661cfe54a4298b85868bbe4858080").unwrap();
+    assert_eq!(root, keccak256(root_node));
+
+    // duplicate keys are overwritten by the last value
+    let (key, value) = entries[0].clone();
+    entries.insert(0, (key, Bytes::from(alloy_rlp::encode(U256::from(1)))));
+    entries.push((key, value));
+    assert_eq!(api.backend.compute_trie_root(entries), root);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_compute_state_root_at() {
+    let (api, _handle) = spawn(NodeConfig::empty_state()).await;
+
+    let empty_root =
+        fixed_bytes!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+    assert_eq!(api.backend.state_root_at(None).await.unwrap(), empty_root);
+
+    let target = Address::random();
+    api.anvil_set_balance(target, U256::from(1337)).await.unwrap();
+    api.anvil_set_storage_at(target, U256::from(1), B256::with_last_byte(1)).await.unwrap();
+
+    // the root commits to the account as returned by `eth_getProof`
+    let proof = api.get_proof(target, Vec::new(), None).await.unwrap();
+    let mut account = Vec::new();
+    let fields: [&dyn alloy_rlp::Encodable; 4] =
+        [&0u64, &U256::from(1337), &proof.storage_hash, &proof.code_hash];
+    alloy_rlp::encode_list::<_, dyn alloy_rlp::Encodable>(&fields, &mut account);
+    let mut builder = HashBuilder::default();
+    builder.add_leaf(Nibbles::unpack(keccak256(target)), &account);
+    assert_eq!(api.backend.state_root_at(None).await.unwrap(), builder.root());
+
+    // historic states
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+    let tx =
+        TransactionRequest::default().with_from(from).with_to(target).with_value(U256::from(1));
+    let tx = WithOtherFields::new(tx);
+
+    provider.send_transaction(tx.clone()).await.unwrap().get_receipt().await.unwrap();
+    let root = api.backend.state_root_at(None).await.unwrap();
+    let block = api.block_by_number(BlockNumberOrTag::Latest).await.unwrap().unwrap();
+    assert_eq!(block.header.state_root, root);
+
+    provider.send_transaction(tx).await.unwrap().get_receipt().await.unwrap();
+    assert_ne!(api.backend.state_root_at(None).await.unwrap(), root);
+    assert_eq!(api.backend.state_root_at(Some(BlockRequest::Number(1))).await.unwrap(), root);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_compute_storage_root_at() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+
+    let empty_root =
+        fixed_bytes!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+    let target = Address::random();
+    assert_eq!(api.backend.storage_root_at(target, None).await.unwrap(), empty_root);
+
+    api.anvil_set_storage_at(target, U256::from(1), B256::with_last_byte(1)).await.unwrap();
+    let root = api.backend.storage_root_at(target, None).await.unwrap();
+    assert_ne!(root, empty_root);
+
+    // the root matches the storage hash returned by `eth_getProof`
+    let proof = api.get_proof(target, Vec::new(), None).await.unwrap();
+    assert_eq!(proof.storage_hash, root);
+
+    api.anvil_set_storage_at(target, U256::from(2), B256::with_last_byte(2)).await.unwrap();
+    assert_ne!(api.backend.storage_root_at(target, None).await.unwrap(), root);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_compute_state_root_of_mined_blocks() {
+    let (api, _handle) = spawn(NodeConfig::test().with_compute_state_root(false)).await;
+    api.evm_mine(None).await.unwrap();
+    let block = api.block_by_number(BlockNumberOrTag::Latest).await.unwrap().unwrap();
+    assert_eq!(block.header.state_root, B256::ZERO);
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let from = handle.dev_wallets().next().unwrap().address();
+    api.anvil_set_balance(Address::random(), U256::from(1)).await.unwrap();
+    api.evm_mine(None).await.unwrap();
+
+    // the root node of any account proof hashes to the state root
+    let block = api.block_by_number(BlockNumberOrTag::Latest).await.unwrap().unwrap();
+    let proof = api.get_proof(from, Vec::new(), None).await.unwrap();
+    assert_eq!(block.header.state_root, keccak256(&proof.account_proof[0]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_prove_accounts_at_historic_block() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let (first, second) = (Address::random(), Address::random());
+    api.anvil_set_balance(first, U256::from(1)).await.unwrap();
+    api.anvil_set_balance(second, U256::from(2)).await.unwrap();
+    api.evm_mine(None).await.unwrap();
+    let snapshot = api.evm_snapshot().await.unwrap();
+    let root = api.backend.state_root_at(None).await.unwrap();
+
+    api.evm_mine(None).await.unwrap();
+    api.anvil_set_balance(first, U256::from(3)).await.unwrap();
+    api.evm_mine(None).await.unwrap();
+
+    // all proofs at the same historic block commit to its state root
+    for (address, balance) in [(first, 1), (second, 2), (first, 1)] {
+        let proof = api
+            .backend
+            .prove_account_at(address, Vec::new(), Some(BlockRequest::Number(1)))
+            .await
+            .unwrap();
+        assert_eq!(proof.balance, U256::from(balance));
+        assert_eq!(keccak256(&proof.account_proof[0]), root);
+    }
+
+    // the state of the block changes once new blocks are mined on top of a reverted snapshot
+    assert!(api.evm_revert(snapshot).await.unwrap());
+    api.anvil_set_balance(first, U256::from(4)).await.unwrap();
+    api.evm_mine(None).await.unwrap();
+    let proof = api
+        .backend
+        .prove_account_at(first, Vec::new(), Some(BlockRequest::Number(1)))
+        .await
+        .unwrap();
+    assert_eq!(proof.balance, U256::from(4));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_verify_proofs_against_state_root() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let from = handle.dev_wallets().next().unwrap().address();
+    let target = Address::random();
+    api.anvil_set_storage_at(target, U256::from(1), B256::with_last_byte(1)).await.unwrap();
+    api.anvil_set_storage_at(target, U256::from(2), B256::with_last_byte(2)).await.unwrap();
+    // zero values are not part of the storage trie
+    api.anvil_set_storage_at(target, U256::from(3), B256::ZERO).await.unwrap();
+    api.evm_mine(None).await.unwrap();
+    let block = api.block_by_number(BlockNumberOrTag::Latest).await.unwrap().unwrap();
+
+    let keys = (1..=4u64).map(|slot| B256::from(U256::from(slot))).collect::<Vec<_>>();
+    for address in [from, target, Address::random()] {
+        let proof = api.get_proof(address, keys.clone(), None).await.unwrap();
+        state::verify_account_proof(block.header.state_root, &proof).unwrap();
+    }
+
+    // tampered proofs are rejected
+    let mut proof = api.get_proof(target, keys.clone(), None).await.unwrap();
+    proof.storage_proof[0].value = U256::from(5);
+    assert!(state::verify_account_proof(block.header.state_root, &proof).is_err());
+
+    let mut proof = api.get_proof(target, keys, None).await.unwrap();
+    proof.balance = U256::from(1);
+    assert!(state::verify_account_proof(block.header.state_root, &proof).is_err());
+}
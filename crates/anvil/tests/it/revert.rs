@@ -1,11 +1,14 @@
 use crate::abi::VendingMachine;
 use alloy_network::TransactionBuilder;
-use alloy_primitives::{bytes, U256};
+use alloy_primitives::{bytes, Bytes, U256};
 use alloy_provider::Provider;
 use alloy_rpc_types::TransactionRequest;
 use alloy_serde::WithOtherFields;
-use alloy_sol_types::sol;
-use anvil::{spawn, NodeConfig};
+use alloy_sol_types::{sol, Revert, SolError};
+use anvil::{
+    eth::{backend::mem::CallResult, fees::FeeDetails},
+    spawn, NodeConfig,
+};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_deploy_reverting() {
@@ -124,3 +127,42 @@ async fn test_solc_revert_custom_errors() {
     let s = err.to_string();
     assert!(s.contains("execution reverted"), "{s:?}");
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_and_decode_revert() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let sender = handle.dev_accounts().next().unwrap();
+    let provider = handle.http_provider();
+
+    let contract = VendingMachine::deploy(&provider).await.unwrap();
+
+    let request = |call: Bytes, value: U256| {
+        let tx = TransactionRequest::default()
+            .with_from(sender)
+            .with_to(*contract.address())
+            .with_input(call)
+            .with_value(value);
+        WithOtherFields::new(tx)
+    };
+
+    let call = contract.buy(U256::from(100)).calldata().clone();
+    let result = api
+        .backend
+        .call_and_decode(request(call, U256::from(1)), FeeDetails::zero(), None, None)
+        .await
+        .unwrap();
+    let CallResult::Revert { output, reason, selector, .. } = result else {
+        panic!("expected revert: {result:?}")
+    };
+    assert!(reason.unwrap().contains("Not enough Ether provided."));
+    assert_eq!(selector, Some(Revert::SELECTOR.into()));
+    assert_eq!(output, Bytes::from(Revert::from("Not enough Ether provided.").abi_encode()));
+
+    let call = contract.buy(U256::ZERO).calldata().clone();
+    let result = api
+        .backend
+        .call_and_decode(request(call, U256::ZERO), FeeDetails::zero(), None, None)
+        .await
+        .unwrap();
+    assert!(result.is_success(), "{result:?}");
+}
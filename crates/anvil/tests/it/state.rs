@@ -1,6 +1,11 @@
 //! general eth api tests
 
-use anvil::{spawn, NodeConfig};
+use alloy_network::TransactionBuilder;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types::TransactionRequest;
+use alloy_serde::WithOtherFields;
+use anvil::{eth::error::BlockchainError, spawn, NodeConfig};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn can_load_state() {
@@ -21,3 +26,122 @@ async fn can_load_state() {
     let num2 = api.block_number().unwrap();
     assert_eq!(num, num2);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_load_state_with_impersonated_accounts() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+
+    let impersonate = Address::random();
+    api.anvil_impersonate_account(impersonate).await.unwrap();
+    api.anvil_auto_impersonate_account(true).await.unwrap();
+
+    let state = api.anvil_dump_state().await.unwrap();
+
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    assert!(api.backend.impersonated_accounts().is_empty());
+    assert!(!api.backend.is_auto_impersonating());
+
+    api.anvil_load_state(state).await.unwrap();
+
+    assert_eq!(api.backend.impersonated_accounts(), vec![impersonate]);
+    assert!(api.backend.is_auto_impersonating());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_load_state_without_cheats() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    api.mine_one().await;
+
+    let mut state = api.serialized_state().await.unwrap();
+    state.cheats = None;
+    let json = serde_json::to_vec(&state).unwrap();
+
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    api.anvil_load_state(json.into()).await.unwrap();
+
+    assert_eq!(api.block_number().unwrap(), U256::from(1));
+    assert!(api.backend.impersonated_accounts().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_verify_hash_chain() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    for _ in 0..3 {
+        api.mine_one().await;
+    }
+    api.backend.verify_hash_chain(0, 3).unwrap();
+
+    // break the link between block 1 and 2
+    let mut state = api.serialized_state().await.unwrap();
+    let block = state.blocks.iter_mut().find(|block| block.header.number == 2).unwrap();
+    block.header.parent_hash = B256::random();
+
+    let (api, _handle) = spawn(NodeConfig::test().with_init_state(Some(state))).await;
+    api.backend.verify_hash_chain(0, 1).unwrap();
+    let err = api.backend.verify_hash_chain(0, 3).unwrap_err();
+    assert!(matches!(err, BlockchainError::BrokenHashChain(2)), "{err:?}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_load_state_chunked() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+
+    let address = Address::random();
+    api.anvil_set_code(address, vec![0x60, 0x00].into()).await.unwrap();
+    api.anvil_set_storage_at(address, U256::from(1), B256::random()).await.unwrap();
+    api.mine_one().await;
+
+    let state = api.serialized_state().await.unwrap();
+    let paths = api.backend.dump_state_chunked(tmp.path(), 3).await.unwrap();
+    assert_eq!(paths.len(), state.accounts.len().div_ceil(3));
+    assert!(paths.iter().all(|path| path.exists()));
+
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    api.backend.load_state_chunked(tmp.path()).await.unwrap();
+
+    let loaded = api.serialized_state().await.unwrap();
+    assert_eq!(api.block_number().unwrap(), U256::from(1));
+    assert_eq!(
+        serde_json::to_value(&loaded.accounts).unwrap(),
+        serde_json::to_value(&state.accounts).unwrap()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_replay_from_dump() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+    for i in 0..3 {
+        let tx = TransactionRequest::default()
+            .with_from(accounts[i])
+            .with_to(accounts[i + 1])
+            .with_value(U256::from(1000));
+        provider.send_transaction(WithOtherFields::new(tx)).await.unwrap().watch().await.unwrap();
+    }
+    // PUSH1 42 PUSH1 1 SSTORE STOP
+    let tx = TransactionRequest::default()
+        .with_from(accounts[0])
+        .with_deploy_code(Bytes::from_static(&[0x60, 0x2a, 0x60, 0x01, 0x55, 0x00]));
+    provider.send_transaction(WithOtherFields::new(tx)).await.unwrap().watch().await.unwrap();
+
+    let state = api.serialized_state().await.unwrap();
+    let root = api.backend.state_root_at(None).await.unwrap();
+
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    assert_eq!(api.backend.replay_from_dump(state.clone()).await.unwrap(), root);
+    api.backend.load_state(state.clone()).await.unwrap();
+    assert_eq!(api.backend.state_root_at(None).await.unwrap(), root);
+
+    // a dump whose state was tampered with no longer matches its history
+    let mut tampered = state;
+    tampered.accounts.get_mut(&accounts[0]).unwrap().balance += U256::from(1);
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    api.backend.load_state(tampered.clone()).await.unwrap();
+    assert_ne!(
+        api.backend.replay_from_dump(tampered).await.unwrap(),
+        api.backend.state_root_at(None).await.unwrap()
+    );
+}
@@ -6,11 +6,16 @@ use alloy_provider::{
     Provider,
 };
 use alloy_rpc_types::{
+    state::{AccountOverride, StateOverride},
     trace::{
-        geth::{GethDebugTracingCallOptions, GethTrace},
+        geth::{
+            DefaultFrame, GethDebugBuiltInTracerType, GethDebugTracerType,
+            GethDebugTracingCallOptions, GethDebugTracingOptions, GethDefaultTracingOptions,
+            GethTrace,
+        },
         parity::{Action, LocalizedTransactionTrace},
     },
-    BlockNumberOrTag, TransactionRequest,
+    BlockId, BlockNumberOrTag, TransactionRequest,
 };
 use alloy_serde::WithOtherFields;
 use alloy_sol_types::sol;
@@ -138,6 +143,103 @@ async fn test_transfer_debug_trace_call() {
     }
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_trace_call_pending_with_overrides() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+    let (target, reader) = (Address::random(), Address::random());
+
+    // PUSH20 target BALANCE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let mut code = vec![0x73];
+    code.extend_from_slice(target.as_slice());
+    code.extend_from_slice(&[0x31, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+    api.anvil_set_code(reader, Bytes::from(code)).await.unwrap();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let tx =
+        TransactionRequest::default().with_from(from).with_to(target).with_value(U256::from(5));
+    let _ = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+
+    let call = WithOtherFields::new(TransactionRequest::default().with_from(from).with_to(reader));
+    let balance = |frame: DefaultFrame| U256::from_be_slice(&frame.return_value);
+
+    // the call is traced after the pending transactions
+    let frame = api
+        .debug_trace_call(call.clone(), Some(BlockId::latest()), Default::default())
+        .await
+        .unwrap();
+    assert_eq!(balance(frame), U256::ZERO);
+    let frame = api
+        .debug_trace_call(call.clone(), Some(BlockId::pending()), Default::default())
+        .await
+        .unwrap();
+    assert_eq!(balance(frame), U256::from(5));
+
+    // overrides apply on top of the pending state
+    let overrides = StateOverride::from([(
+        target,
+        AccountOverride { balance: Some(U256::from(100)), ..Default::default() },
+    )]);
+    let opts = GethDebugTracingCallOptions::default().with_state_overrides(overrides);
+    let frame = api.debug_trace_call(call, Some(BlockId::pending()), opts).await.unwrap();
+    assert_eq!(balance(frame), U256::from(100));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trace_bundle() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let from = handle.dev_wallets().next().unwrap().address();
+    let (target, reader) = (Address::random(), Address::random());
+
+    // PUSH20 target BALANCE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let mut code = vec![0x73];
+    code.extend_from_slice(target.as_slice());
+    code.extend_from_slice(&[0x31, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+    api.anvil_set_code(reader, Bytes::from(code)).await.unwrap();
+
+    let transfer =
+        TransactionRequest::default().with_from(from).with_to(target).with_value(U256::from(5));
+    let read = TransactionRequest::default().with_from(from).with_to(reader);
+    let requests = vec![WithOtherFields::new(transfer), WithOtherFields::new(read)];
+
+    // every call sees the state changes of the previous calls, on top of the overrides
+    let overrides = StateOverride::from([(
+        target,
+        AccountOverride { balance: Some(U256::from(100)), ..Default::default() },
+    )]);
+    let traces = api
+        .backend
+        .trace_bundle(requests.clone(), Default::default(), None, Some(overrides), None)
+        .await
+        .unwrap();
+    assert_eq!(traces.len(), 2);
+    match &traces[1] {
+        GethTrace::Default(frame) => {
+            assert_eq!(U256::from_be_slice(&frame.return_value), U256::from(105));
+            assert!(!frame.struct_logs.is_empty());
+        }
+        trace => panic!("unexpected trace: {trace:?}"),
+    }
+
+    let opts = GethDebugTracingOptions::default()
+        .with_tracer(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::CallTracer));
+    let traces = api.backend.trace_bundle(requests, opts, None, None, None).await.unwrap();
+    match &traces[0] {
+        GethTrace::CallTracer(frame) => {
+            assert_eq!(frame.to, Some(target));
+            assert_eq!(frame.value, Some(U256::from(5)));
+        }
+        trace => panic!("unexpected trace: {trace:?}"),
+    }
+    match &traces[1] {
+        GethTrace::CallTracer(frame) => {
+            assert_eq!(U256::from_be_slice(frame.output.as_ref().unwrap()), U256::from(5));
+        }
+        trace => panic!("unexpected trace: {trace:?}"),
+    }
+}
+
 // <https://github.com/foundry-rs/foundry/issues/2656>
 #[tokio::test(flavor = "multi_thread")]
 async fn test_trace_address_fork() {
@@ -598,3 +700,166 @@ async fn test_trace_address_fork2() {
         }
     })
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replay_transaction() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_accounts().next().unwrap();
+
+    // reverts if slot 0 is already set, otherwise sets it:
+    // PUSH1 0 SLOAD PUSH1 12 JUMPI PUSH1 1 PUSH1 0 SSTORE STOP JUMPDEST PUSH1 0 DUP1 REVERT
+    let target = Address::random();
+    api.anvil_set_code(target, Bytes::from(hex!("600054600c576001600055005b600080fd")))
+        .await
+        .unwrap();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let mut hashes = vec![];
+    for _ in 0..2 {
+        let tx = TransactionRequest::default().from(from).to(target).with_gas_limit(100_000);
+        let tx = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+        hashes.push(*tx.tx_hash());
+    }
+    api.mine_one().await;
+
+    // the second transaction sees the storage written by the first one in the same block
+    for (hash, failed) in hashes.into_iter().zip([false, true]) {
+        let trace = api.backend.replay_transaction(hash, Default::default()).await.unwrap();
+        match trace {
+            GethTrace::Default(frame) => {
+                assert_eq!(frame.failed, failed);
+                assert!(!frame.struct_logs.is_empty());
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_trace_transaction_struct_log_toggles() {
+    let (api, handle) = spawn(NodeConfig::test().with_steps_tracing(true)).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_accounts().next().unwrap();
+
+    // PUSH1 1 PUSH1 0 MSTORE PUSH1 1 PUSH1 0 SSTORE STOP
+    let target = Address::random();
+    api.anvil_set_code(target, Bytes::from(hex!("600160005260016000550000"))).await.unwrap();
+
+    let tx = TransactionRequest::default().from(from).to(target).with_gas_limit(100_000);
+    let tx = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+    let hash = *tx.tx_hash();
+    tx.get_receipt().await.unwrap();
+
+    let trace = |config: GethDefaultTracingOptions| {
+        let api = api.clone();
+        async move {
+            let opts = GethDebugTracingOptions { config, ..Default::default() };
+            match api.debug_trace_transaction(hash, opts).await.unwrap() {
+                GethTrace::Default(frame) => frame,
+                _ => unreachable!(),
+            }
+        }
+    };
+
+    let frame = trace(GethDefaultTracingOptions::default().enable_memory()).await;
+    assert!(frame.struct_logs.iter().any(|log| log.memory.as_ref().is_some_and(|m| !m.is_empty())));
+    assert!(frame.struct_logs.iter().any(|log| log.storage.is_some()));
+    assert!(frame.struct_logs.iter().all(|log| log.stack.is_some()));
+
+    let frame = trace(
+        GethDefaultTracingOptions::default().disable_memory().disable_stack().disable_storage(),
+    )
+    .await;
+    assert!(!frame.struct_logs.is_empty());
+    assert!(frame.struct_logs.iter().all(|log| log.memory.is_none()));
+    assert!(frame.struct_logs.iter().all(|log| log.stack.is_none()));
+    assert!(frame.struct_logs.iter().all(|log| log.storage.is_none()));
+
+    let frame = trace(GethDefaultTracingOptions::default().with_limit(2)).await;
+    assert_eq!(frame.struct_logs.len(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_trace_block() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_accounts().next().unwrap();
+
+    // reverts if slot 0 is already set, otherwise sets it
+    let target = Address::random();
+    api.anvil_set_code(target, Bytes::from(hex!("600054600c576001600055005b600080fd")))
+        .await
+        .unwrap();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let mut hashes = vec![];
+    for _ in 0..2 {
+        let tx = TransactionRequest::default().from(from).to(target).with_gas_limit(100_000);
+        let tx = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+        hashes.push(*tx.tx_hash());
+    }
+    api.mine_one().await;
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+
+    let traces = provider
+        .debug_trace_block_by_number(BlockNumberOrTag::Latest, Default::default())
+        .await
+        .unwrap();
+    assert_eq!(traces.len(), 2);
+    for ((trace, hash), failed) in traces.iter().zip(&hashes).zip([false, true]) {
+        assert_eq!(trace.tx_hash(), Some(*hash));
+        match trace.success() {
+            Some(GethTrace::Default(frame)) => assert_eq!(frame.failed, failed),
+            _ => unreachable!(),
+        }
+    }
+
+    let opts = GethDebugTracingOptions::default()
+        .with_tracer(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::CallTracer));
+    let traces =
+        provider.debug_trace_block_by_hash(block.header.hash.unwrap(), opts).await.unwrap();
+    assert_eq!(traces.len(), 2);
+    for (trace, failed) in traces.iter().zip([false, true]) {
+        match trace.success() {
+            Some(GethTrace::CallTracer(frame)) => {
+                assert_eq!(frame.to, Some(target));
+                assert_eq!(frame.error.is_some(), failed);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_trace_block_uses_block_hardfork() {
+    let activation = 2_000_000_000;
+    let node_config = NodeConfig::test()
+        .with_hardfork(Some(Hardfork::Shanghai))
+        .with_hardfork_activations([(activation, Hardfork::Cancun)]);
+    let (api, handle) = spawn(node_config).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_accounts().next().unwrap();
+
+    // BLOBBASEFEE STOP, which is an invalid opcode before cancun
+    let target = Address::random();
+    api.anvil_set_code(target, Bytes::from(hex!("4a00"))).await.unwrap();
+
+    api.evm_set_next_block_timestamp(activation - 1).unwrap();
+    let tx = TransactionRequest::default().from(from).to(target).with_gas_limit(100_000);
+    provider.send_transaction(WithOtherFields::new(tx)).await.unwrap().get_receipt().await.unwrap();
+    let number = provider.get_block_number().await.unwrap();
+
+    api.evm_set_next_block_timestamp(activation).unwrap();
+    api.mine_one().await;
+
+    let traces = provider
+        .debug_trace_block_by_number(BlockNumberOrTag::Number(number), Default::default())
+        .await
+        .unwrap();
+    assert_eq!(traces.len(), 1);
+    match traces[0].success() {
+        Some(GethTrace::Default(frame)) => assert!(frame.failed),
+        _ => unreachable!(),
+    }
+}
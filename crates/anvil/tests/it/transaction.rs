@@ -2,18 +2,37 @@ use crate::{
     abi::{Greeter, MulticallContract, SimpleStorage},
     utils::{connect_pubsub, http_provider_with_signer},
 };
+use alloy_eips::eip2718::Encodable2718;
 use alloy_network::{EthereumWallet, TransactionBuilder};
-use alloy_primitives::{Address, Bytes, FixedBytes, U256};
+use alloy_primitives::{keccak256, Address, Bytes, FixedBytes, B256, I256, U256};
 use alloy_provider::Provider;
+use alloy_rlp::Decodable;
 use alloy_rpc_types::{
     state::{AccountOverride, StateOverride},
     AccessList, AccessListItem, BlockId, BlockNumberOrTag, BlockTransactions, TransactionRequest,
 };
 use alloy_serde::WithOtherFields;
-use anvil::{spawn, Hardfork, NodeConfig};
+use anvil::{
+    eth::{
+        backend::mem::{transaction_build, transaction_build_with_base_fee},
+        error::{BlockchainError, InvalidTransactionError},
+        pool::transactions::{PoolTransaction, TransactionPriority},
+    },
+    spawn, Hardfork, NodeConfig, CHAIN_ID,
+};
+use anvil_core::eth::{
+    block::Block,
+    transaction::{MaybeImpersonatedTransaction, PendingTransaction, TypedTransaction},
+};
 use eyre::Ok;
+use foundry_evm::revm::DatabaseRef;
 use futures::{future::join_all, FutureExt, StreamExt};
-use std::{collections::HashSet, str::FromStr, time::Duration};
+use std::{
+    collections::{BTreeMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 use tokio::time::timeout;
 
 #[tokio::test(flavor = "multi_thread")]
@@ -95,6 +114,314 @@ async fn can_order_transactions() {
     assert_eq!(block.transactions, BlockTransactions::Hashes(vec![higher_price, lower_price]))
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_order_transactions_by_effective_gas_price() {
+    let (api, handle) = spawn(NodeConfig::test().with_order_by_effective_gas_price(true)).await;
+    let provider = handle.http_provider();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let accounts = handle.dev_wallets().collect::<Vec<_>>();
+    let alice = accounts[0].address();
+    let bob = accounts[1].address();
+    let gwei = 1_000_000_000u128;
+
+    // (sender, nonce, max fee, tip): alice's first tx has the highest max fee but the lowest tip
+    let txs = [
+        (alice, 0, 100 * gwei, gwei),
+        (alice, 1, 100 * gwei, 10 * gwei),
+        (bob, 0, 10 * gwei, 5 * gwei),
+    ];
+    let mut hashes = Vec::new();
+    for (from, nonce, max_fee, tip) in txs {
+        let tx = TransactionRequest::default()
+            .with_from(from)
+            .with_to(Address::random())
+            .with_nonce(nonce)
+            .with_max_fee_per_gas(max_fee)
+            .with_max_priority_fee_per_gas(tip);
+        let pending = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+        hashes.push(*pending.tx_hash());
+    }
+
+    api.mine_one().await;
+
+    // bob pays the higher effective price, alice's txs stay in nonce order
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    assert_eq!(
+        block.transactions,
+        BlockTransactions::Hashes(vec![hashes[2], hashes[0], hashes[1]])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_preview_pending_block_with_replacement() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let from = wallet.address();
+    let to = Address::random();
+    let replacement_to = Address::random();
+
+    let tx = TransactionRequest::default().with_from(from).with_to(to).with_value(U256::from(1));
+    let pending = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+    let original = provider.get_transaction_by_hash(*pending.tx_hash()).await.unwrap().unwrap();
+    let original = Arc::new(PoolTransaction::try_from(original.inner).unwrap());
+
+    let replacement = TransactionRequest::default()
+        .with_from(from)
+        .with_to(replacement_to)
+        .with_value(U256::from(2))
+        .with_nonce(0)
+        .with_chain_id(CHAIN_ID)
+        .with_gas_limit(21_000)
+        .with_max_fee_per_gas(original.gas_price() * 2)
+        .with_max_priority_fee_per_gas(original.gas_price());
+    let replacement = replacement.build(&EthereumWallet::from(wallet)).await.unwrap();
+    let replacement =
+        PendingTransaction::new(TypedTransaction::from(replacement)).map(|pending_transaction| {
+            PoolTransaction {
+                pending_transaction,
+                requires: vec![],
+                provides: vec![],
+                priority: TransactionPriority(0),
+            }
+        });
+    let replacement = Arc::new(replacement.unwrap());
+
+    let (block, balances) = api
+        .backend
+        .with_pending_block_replacing(vec![original], replacement.clone(), |db, info| {
+            let balance = |addr| db.basic_ref(addr).unwrap().unwrap_or_default().balance;
+            (info, (balance(to), balance(replacement_to)))
+        })
+        .await;
+
+    assert_eq!(block.transactions.len(), 1);
+    assert_eq!(block.transactions[0].transaction_hash, replacement.hash());
+    assert_eq!(balances, (U256::ZERO, U256::from(2)));
+
+    // the pool itself is left untouched
+    assert_eq!(api.txpool_status().await.unwrap().pending, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_pending_block_headroom() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+    let gas_limit = api.backend.gas_limit();
+
+    assert_eq!(api.backend.pending_block_headroom(vec![]).await.unwrap() as u128, gas_limit);
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let mut pool_transactions = vec![];
+    for to in &accounts[1..4] {
+        let tx = TransactionRequest::default().with_from(accounts[0]).with_to(*to);
+        let pending = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+        let tx = provider.get_transaction_by_hash(*pending.tx_hash()).await.unwrap().unwrap();
+        pool_transactions.push(Arc::new(PoolTransaction::try_from(tx.inner).unwrap()));
+    }
+
+    let headroom = api.backend.pending_block_headroom(pool_transactions).await.unwrap();
+    assert_eq!(headroom as u128, gas_limit - 3 * 21_000);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_pending_block_gas_used() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+    let usage = api.backend.pending_block_gas_used(vec![]).await.unwrap();
+    assert_eq!(usage.gas_limit as u128, api.backend.gas_limit());
+    assert_eq!(usage.gas_used, 0);
+    assert!(usage.included.is_empty());
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let mut pool_transactions = vec![];
+    for (to, nonce) in accounts[1..4].iter().zip([0, 1, 3]) {
+        let tx =
+            TransactionRequest::default().with_from(accounts[0]).with_to(*to).with_nonce(nonce);
+        let pending = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+        let tx = provider.get_transaction_by_hash(*pending.tx_hash()).await.unwrap().unwrap();
+        pool_transactions.push(Arc::new(PoolTransaction::try_from(tx.inner).unwrap()));
+    }
+
+    // the last transaction has a nonce gap, so it's not included
+    let usage = api.backend.pending_block_gas_used(pool_transactions.clone()).await.unwrap();
+    assert_eq!(usage.gas_used, 2 * 21_000);
+    assert_eq!(usage.blob_gas_used, 0);
+    assert_eq!(
+        usage.included,
+        pool_transactions
+            .iter()
+            .zip([true, true, false])
+            .map(|(tx, included)| (tx.hash(), included))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_dry_run_mine() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let mut pool_transactions = vec![];
+    for (to, nonce) in accounts[1..4].iter().zip([0, 1, 3]) {
+        let tx =
+            TransactionRequest::default().with_from(accounts[0]).with_to(*to).with_nonce(nonce);
+        let pending = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+        let tx = provider.get_transaction_by_hash(*pending.tx_hash()).await.unwrap().unwrap();
+        pool_transactions.push(Arc::new(PoolTransaction::try_from(tx.inner).unwrap()));
+    }
+    let balance = provider.get_balance(accounts[1]).await.unwrap();
+
+    // the last transaction has a nonce gap, so it's rejected
+    let outcome = api.backend.dry_run_mine(pool_transactions.clone()).await.unwrap();
+    assert_eq!(outcome.included, vec![pool_transactions[0].hash(), pool_transactions[1].hash()]);
+    assert_eq!(outcome.invalid.len(), 1);
+    assert_eq!(outcome.invalid[0].0, pool_transactions[2].hash());
+    assert!(matches!(outcome.invalid[0].1, InvalidTransactionError::NonceTooHigh));
+    assert_eq!(outcome.gas_used, 2 * 21_000);
+
+    // nothing is committed
+    assert_eq!(api.block_number().unwrap(), U256::ZERO);
+    assert_eq!(provider.get_balance(accounts[1]).await.unwrap(), balance);
+    assert_eq!(api.txpool_status().await.unwrap().pending, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_allow_sender_with_code() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+    let (sender, other) = (accounts[0], accounts[1]);
+    api.anvil_set_code(sender, Bytes::from_static(&[0x00])).await.unwrap();
+    api.backend.set_eip3607_enabled(true);
+
+    let tx = |from, to| WithOtherFields::new(TransactionRequest::default().from(from).to(to));
+    let err = provider.send_transaction(tx(sender, other)).await.unwrap_err();
+    assert!(err.to_string().contains("sender not an eoa"), "{err}");
+    api.call(tx(sender, other), None, None).await.unwrap_err();
+
+    // only the allowed sender is exempt from EIP-3607
+    api.backend.allow_sender_with_code(sender, true);
+    api.call(tx(sender, other), None, None).await.unwrap();
+    let pending = provider.send_transaction(tx(sender, other)).await.unwrap();
+    assert!(pending.get_receipt().await.unwrap().inner.inner.status());
+    api.anvil_set_code(other, Bytes::from_static(&[0x00])).await.unwrap();
+    provider.send_transaction(tx(other, sender)).await.unwrap_err();
+
+    api.backend.allow_sender_with_code(sender, false);
+    provider.send_transaction(tx(sender, other)).await.unwrap_err();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_reorg_with_replacement_transactions() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let mut wallets = handle.dev_wallets();
+    let (alice, bob) = (wallets.next().unwrap().address(), wallets.next().unwrap());
+    let to = Address::random();
+
+    let mut hashes = Vec::new();
+    for from in [alice, bob.address(), alice] {
+        let tx =
+            TransactionRequest::default().with_from(from).with_to(to).with_value(U256::from(1));
+        let receipt = provider
+            .send_transaction(WithOtherFields::new(tx))
+            .await
+            .unwrap()
+            .get_receipt()
+            .await
+            .unwrap();
+        hashes.push(receipt.transaction_hash);
+    }
+
+    let replacement = |nonce| {
+        let tx = TransactionRequest::default()
+            .with_from(bob.address())
+            .with_to(to)
+            .with_value(U256::from(2))
+            .with_nonce(nonce)
+            .with_chain_id(CHAIN_ID)
+            .with_gas_limit(21_000)
+            .with_max_fee_per_gas(api.backend.base_fee() * 2)
+            .with_max_priority_fee_per_gas(0);
+        let wallet = EthereumWallet::from(bob.clone());
+        async move {
+            let tx = tx.build(&wallet).await.unwrap();
+            let pending_transaction = PendingTransaction::new(TypedTransaction::from(tx)).unwrap();
+            Arc::new(PoolTransaction {
+                pending_transaction,
+                requires: vec![],
+                provides: vec![],
+                priority: TransactionPriority(0),
+            })
+        }
+    };
+    let exclude = HashSet::from([hashes[1]]);
+
+    // injected transactions must line up with the rewound nonces, the chain is left untouched
+    let inject = BTreeMap::from([(1, vec![(0, replacement(1).await)])]);
+    let err = api.backend.reorg(2, &exclude, inject).await.unwrap_err();
+    assert!(matches!(
+        err,
+        BlockchainError::InvalidTransaction(InvalidTransactionError::NonceTooHigh)
+    ));
+    assert_eq!(api.block_number().unwrap(), U256::from(3));
+
+    let replacement = replacement(0).await;
+    let inject = BTreeMap::from([(1, vec![(0, replacement.clone())])]);
+    let outcomes = api.backend.reorg(2, &exclude, inject).await.unwrap();
+    assert_eq!(outcomes.len(), 2);
+    assert_eq!(api.block_number().unwrap(), U256::from(3));
+
+    let block = provider.get_block(BlockId::number(2), false.into()).await.unwrap().unwrap();
+    assert!(block.transactions.is_empty());
+    let block = provider.get_block(BlockId::number(3), false.into()).await.unwrap().unwrap();
+    assert_eq!(block.transactions, BlockTransactions::Hashes(vec![replacement.hash(), hashes[2]]));
+
+    assert!(provider.get_transaction_receipt(hashes[1]).await.unwrap().is_none());
+    assert!(provider.get_transaction_receipt(hashes[0]).await.unwrap().is_some());
+    assert_eq!(provider.get_balance(to).await.unwrap(), U256::from(4));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_build_pending_transaction_with_base_fee() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let wallet = handle.dev_wallets().next().unwrap();
+    let base_fee = api.backend.base_fee();
+
+    let tx = TransactionRequest::default()
+        .with_from(wallet.address())
+        .with_to(Address::random())
+        .with_nonce(0)
+        .with_chain_id(CHAIN_ID)
+        .with_gas_limit(21_000)
+        .with_max_fee_per_gas(base_fee * 3)
+        .with_max_priority_fee_per_gas(base_fee);
+    let tx = tx.build(&EthereumWallet::from(wallet)).await.unwrap();
+    let tx = MaybeImpersonatedTransaction::new(TypedTransaction::from(tx));
+
+    // by default the gas price of a pending transaction is its max fee
+    let built = transaction_build(None, tx.clone(), None, None, Some(base_fee));
+    assert_eq!(built.gas_price, Some(base_fee * 3));
+
+    let built = transaction_build_with_base_fee(None, tx.clone(), None, None, base_fee);
+    assert_eq!(built.gas_price, Some(base_fee * 2));
+
+    // capped by the max fee
+    let built = transaction_build_with_base_fee(None, tx, None, None, base_fee * 10);
+    assert_eq!(built.gas_price, Some(base_fee * 3));
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_respect_nonces() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -745,6 +1072,32 @@ async fn can_get_raw_transaction() {
     assert_eq!(res1.unwrap(), res2.unwrap());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_raw_block() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let from = handle.dev_wallets().next().unwrap().address();
+    let tx = TransactionRequest::default().from(from).value(U256::from(1488)).to(Address::random());
+    let tx = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+    let hash = *tx.tx_hash();
+    tx.get_receipt().await.unwrap();
+
+    let raw_tx = api.backend.raw_transaction(hash).unwrap();
+    assert_eq!(keccak256(&raw_tx), hash);
+    assert_eq!(api.raw_transaction(hash).await.unwrap(), Some(raw_tx.clone()));
+
+    let block = provider.get_block(BlockId::latest(), false.into()).await.unwrap().unwrap();
+    let raw = api.raw_block(BlockId::latest()).await.unwrap().unwrap();
+    let decoded = Block::decode(&mut raw.as_ref()).unwrap();
+    assert_eq!(decoded.header.hash_slow(), block.header.hash.unwrap());
+    assert_eq!(decoded.transactions.len(), 1);
+    assert_eq!(decoded.transactions[0].transaction.encoded_2718(), raw_tx.to_vec());
+
+    assert!(api.backend.raw_transaction(B256::random()).is_none());
+    assert!(api.backend.raw_block(BlockNumberOrTag::Number(100)).is_none());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_first_nonce_is_zero() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -960,6 +1313,32 @@ async fn can_stream_pending_transactions() {
     }
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_stream_full_pending_transactions() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let provider = handle.http_provider();
+    let ws_provider = connect_pubsub(&handle.ws_endpoint()).await;
+    let mut notifications = api.backend.new_pending_transaction_notifications();
+
+    let mut sub = ws_provider.subscribe_full_pending_transactions().await.unwrap().into_stream();
+
+    let accounts = provider.get_accounts().await.unwrap();
+    let tx = TransactionRequest::default().from(accounts[0]).to(accounts[1]).value(U256::from(1));
+    let tx = WithOtherFields::new(tx);
+    let pending = provider.send_transaction(tx).await.unwrap();
+
+    let notified = notifications.next().await.unwrap();
+    assert_eq!(notified.hash(), *pending.tx_hash());
+
+    let tx = sub.next().await.unwrap();
+    assert_eq!(tx.hash, *pending.tx_hash());
+    assert_eq!(tx.from, accounts[0]);
+    assert_eq!(tx.to, Some(accounts[1]));
+    assert!(tx.block_number.is_none());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_tx_access_list() {
     /// returns a String representation of the AccessList, with sorted
@@ -1236,3 +1615,135 @@ async fn can_mine_multiple_in_block() {
     let txs = block.transactions.hashes().copied().collect::<Vec<_>>();
     assert_eq!(txs, vec![first, second]);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_cap_transactions_per_block() {
+    let (api, _handle) = spawn(NodeConfig::test().with_max_transactions_per_block(Some(2))).await;
+
+    // disable auto mine
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let tx = TransactionRequest {
+        from: Some("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".parse().unwrap()),
+        ..Default::default()
+    };
+    let mut hashes = vec![];
+    for _ in 0..5 {
+        hashes.push(api.send_transaction(WithOtherFields::new(tx.clone())).await.unwrap());
+    }
+
+    // the excess transactions remain in the pool and are mined in the following blocks
+    for (expected, pending) in [(&hashes[..2], 3), (&hashes[2..4], 1), (&hashes[4..], 0)] {
+        api.anvil_mine(Some(U256::from(1)), None).await.unwrap();
+        let block = api.block_by_number(BlockNumberOrTag::Latest).await.unwrap().unwrap();
+        let txs = block.transactions.hashes().copied().collect::<Vec<_>>();
+        assert_eq!(txs, expected);
+        assert_eq!(api.txpool_status().await.unwrap().pending, pending);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_pin_nonce() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+    let (from, to) = (accounts[0], accounts[1]);
+
+    api.backend.pin_nonce(from, 5).await.unwrap();
+    for value in 1..=3u64 {
+        let tx = TransactionRequest::default()
+            .with_from(from)
+            .with_to(to)
+            .with_nonce(5)
+            .with_value(U256::from(value));
+        let receipt = provider
+            .send_transaction(WithOtherFields::new(tx))
+            .await
+            .unwrap()
+            .get_receipt()
+            .await
+            .unwrap();
+        assert!(receipt.inner.inner.inner.receipt.status.coerce_status());
+        assert_eq!(provider.get_transaction_count(from).await.unwrap(), 5);
+    }
+
+    api.backend.unpin_nonce(from);
+    let tx = TransactionRequest::default().with_from(from).with_to(to).with_nonce(5);
+    provider.send_transaction(WithOtherFields::new(tx)).await.unwrap().watch().await.unwrap();
+    assert_eq!(provider.get_transaction_count(from).await.unwrap(), 6);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_code_size_limit() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+
+    // init code that deploys `size` zero bytes: PUSH3 size PUSH1 0 RETURN
+    let deploy = |size: u32| {
+        let mut code = vec![0x62];
+        code.extend_from_slice(&size.to_be_bytes()[1..]);
+        code.extend_from_slice(&[0x60, 0x00, 0xf3]);
+        let tx = TransactionRequest::default()
+            .with_from(from)
+            .with_deploy_code(code)
+            .with_gas_limit(10_000_000);
+        let provider = provider.clone();
+        async move {
+            provider
+                .send_transaction(WithOtherFields::new(tx))
+                .await
+                .unwrap()
+                .get_receipt()
+                .await
+                .unwrap()
+                .inner
+                .inner
+                .status()
+        }
+    };
+
+    assert_eq!(api.backend.code_size_limit(), None);
+    assert!(!deploy(30_000).await);
+
+    api.backend.set_code_size_limit(Some(40_000));
+    assert!(deploy(30_000).await);
+
+    api.backend.set_code_size_limit(Some(100));
+    assert!(!deploy(200).await);
+    assert!(deploy(100).await);
+
+    api.backend.set_code_size_limit(None);
+    assert!(deploy(200).await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_sender_balance_change() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().map(|w| w.address()).collect();
+    let (from, to) = (accounts[0], accounts[1]);
+
+    // both transactions are mined in the same block, so the second one is replayed on top of the
+    // first one
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let mut pending = vec![];
+    for (nonce, to) in [(0u64, to), (1, from)] {
+        let tx = TransactionRequest::default()
+            .with_from(from)
+            .with_to(to)
+            .with_nonce(nonce)
+            .with_value(U256::from(1337));
+        pending.push(provider.send_transaction(WithOtherFields::new(tx)).await.unwrap());
+    }
+    api.mine_one().await;
+
+    for (pending, value) in pending.into_iter().zip([1337u64, 0]) {
+        let receipt = pending.get_receipt().await.unwrap();
+        let gas_cost = receipt.gas_used * receipt.effective_gas_price;
+        let change = api.backend.sender_balance_change(receipt.transaction_hash).await.unwrap();
+        assert_eq!(change, I256::try_from(value as u128 + gas_cost).unwrap());
+    }
+
+    assert!(api.backend.sender_balance_change(B256::random()).await.is_err());
+}
@@ -5,7 +5,7 @@ use alloy_primitives::U256;
 use alloy_provider::{ext::TxPoolApi, Provider};
 use alloy_rpc_types::TransactionRequest;
 use alloy_serde::WithOtherFields;
-use anvil::{spawn, NodeConfig};
+use anvil::{eth::fees::PoolFeeTier, spawn, NodeConfig};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn geth_txpool() {
@@ -56,3 +56,44 @@ async fn geth_txpool() {
         assert!(content.contains_key(&nonce.to_string()));
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_pool_fee_landscape() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let account = provider.get_accounts().await.unwrap().remove(0);
+    let base_fee = api.backend.base_fee();
+    let gwei = 1_000_000_000u128;
+
+    // (max fee, tip, gas limit)
+    let txs = [
+        (100 * gwei, gwei, 21_000),
+        (100 * gwei, gwei + gwei / 2, 30_000),
+        (100 * gwei, 3 * gwei, 21_000),
+        // the max fee caps the effective tip at 1 gwei
+        (base_fee + gwei, base_fee + gwei, 21_000),
+    ];
+    for (nonce, (max_fee, tip, gas)) in txs.into_iter().enumerate() {
+        let tx = TransactionRequest::default()
+            .with_from(account)
+            .with_to(account)
+            .with_nonce(nonce as u64)
+            .with_gas_limit(gas)
+            .with_max_fee_per_gas(max_fee)
+            .with_max_priority_fee_per_gas(tip);
+        let _ = provider.send_transaction(WithOtherFields::new(tx)).await.unwrap();
+    }
+
+    let landscape = api.pool_fee_landscape();
+    assert_eq!(landscape.base_fee, base_fee);
+    assert_eq!(
+        landscape.tiers,
+        vec![
+            PoolFeeTier { min_priority_fee: 3 * gwei, transactions: 1, gas: 21_000 },
+            PoolFeeTier { min_priority_fee: gwei, transactions: 3, gas: 72_000 },
+        ]
+    );
+}